@@ -0,0 +1,128 @@
+//! Integers packed as binary-coded decimal.
+
+use crate::{
+    check_len, ByteStructDebugJson, ByteStructError, ByteStructLen, ByteStructUnspecifiedByteOrder,
+    ErrorKind, RawBits,
+};
+use core::fmt;
+
+/// An integer packed as `DIGITS` binary-coded decimal digits, two digits per byte.
+///
+/// The value is held as `T`, which is an unsigned integer type.
+/// With big-endian byte order the most significant digits come first, as is usual in RTC registers,
+/// EMV and telecom formats. With little-endian byte order the bytes are reversed,
+/// while the more significant digit of each byte is still in the high nibble.
+/// If `DIGITS` is odd, the unused high nibble of the most significant byte is packed as zero.
+///
+/// `read_bytes_default_*` panics if a nibble is not a decimal digit, if the unused nibble isn't zero,
+/// or if the value doesn't fit in `T`, while `try_read_bytes_default_*` returns
+/// [`ErrorKind::InvalidValue`](enum.ErrorKind.html#variant.InvalidValue).
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_be]
+/// struct Transaction {
+///     amount: Bcd<u64, 12>,
+///     currency: Bcd<u16, 3>,
+/// }
+///
+/// let t = Transaction::read_bytes(&[0x00, 0x00, 0x00, 0x01, 0x23, 0x45, 0x09, 0x78][..]);
+/// assert_eq!(t.amount.get(), 12345);
+/// assert_eq!(t.currency.get(), 978);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct Bcd<T, const DIGITS: usize>(T);
+
+impl<T: RawBits, const DIGITS: usize> Bcd<T, DIGITS> {
+    /// The largest value that can be represented with `DIGITS` digits
+    pub const MAX: u128 = {
+        let mut max = 0;
+        let mut i = 0;
+        while i < DIGITS {
+            max = max * 10 + 9;
+            i += 1;
+        }
+        max
+    };
+
+    /// Creates the wrapper, or returns `None` if `value` has more than `DIGITS` digits
+    pub fn new(value: T) -> Option<Self> {
+        if value.to_u128() > Self::MAX {
+            return None;
+        }
+        Some(Bcd(value))
+    }
+
+    /// Returns the value
+    pub fn get(&self) -> T {
+        self.0
+    }
+
+    // Writes the digits with the most significant byte first
+    fn write_digits(&self, bytes: &mut [u8]) {
+        let mut value = self.0.to_u128();
+        for byte in bytes.iter_mut().rev() {
+            *byte = (((value / 10 % 10) << 4) | (value % 10)) as u8;
+            value /= 100;
+        }
+    }
+
+    // Reads the digits with the most significant byte first
+    fn read_digits<'a>(bytes: impl Iterator<Item = &'a u8>) -> Option<Self> {
+        let mut value: u128 = 0;
+        for (i, byte) in bytes.enumerate() {
+            let (high, low) = (byte >> 4, byte & 0xf);
+            if high > 9 || low > 9 || (i == 0 && DIGITS % 2 == 1 && high != 0) {
+                return None;
+            }
+            value = value * 100 + u128::from(high) * 10 + u128::from(low);
+        }
+        if value > u128::MAX >> (u128::BITS - T::BITS) {
+            return None;
+        }
+        Some(Bcd(T::from_u128(value)))
+    }
+}
+
+impl<T: RawBits, const DIGITS: usize> ByteStructLen for Bcd<T, DIGITS> {
+    const BYTE_LEN: usize = {
+        assert!(DIGITS > 0 && DIGITS <= 38, "Bcd supports from 1 to 38 digits");
+        DIGITS.div_ceil(2)
+    };
+}
+
+impl<T: RawBits, const DIGITS: usize> ByteStructUnspecifiedByteOrder for Bcd<T, DIGITS> {
+    fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+        let bytes = &mut bytes[..Self::BYTE_LEN];
+        self.write_digits(bytes);
+        bytes.reverse();
+    }
+    fn read_bytes_default_le(bytes: &[u8]) -> Self {
+        Self::read_digits(bytes[..Self::BYTE_LEN].iter().rev()).expect("Invalid BCD value")
+    }
+    fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+        self.write_digits(&mut bytes[..Self::BYTE_LEN]);
+    }
+    fn read_bytes_default_be(bytes: &[u8]) -> Self {
+        Self::read_digits(bytes[..Self::BYTE_LEN].iter()).expect("Invalid BCD value")
+    }
+    fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        Self::read_digits(bytes[..Self::BYTE_LEN].iter().rev())
+            .ok_or(ByteStructError::new(ErrorKind::InvalidValue, None, 0))
+    }
+    fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        Self::read_digits(bytes[..Self::BYTE_LEN].iter())
+            .ok_or(ByteStructError::new(ErrorKind::InvalidValue, None, 0))
+    }
+}
+
+impl<T: RawBits + fmt::Display, const DIGITS: usize> ByteStructDebugJson for Bcd<T, DIGITS> {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        write!(out, "{}", self.0)
+    }
+}
@@ -0,0 +1,251 @@
+//! Support types for members of bit field structures generated by `bitfields!`,
+//! and standalone bit range access on raw integers.
+
+use crate::__private::check_bit_range;
+use core::ops::Range;
+
+/// Conversion between a bit field member and its bits in the base integer.
+///
+/// This is implemented for unsigned integer types, which is the type of plain bit field members,
+/// for the access wrappers [`ReadOnly`], [`WriteOnly`] and [`WriteOneToClear`],
+/// and for [`Computed`].
+///
+/// [`ReadOnly`]: struct.ReadOnly.html
+/// [`WriteOnly`]: struct.WriteOnly.html
+/// [`WriteOneToClear`]: struct.WriteOneToClear.html
+/// [`Computed`]: struct.Computed.html
+pub trait BitFieldValue<B> {
+    /// Creates the member from its bits, already shifted down to the least significant bits
+    fn from_bits(bits: B) -> Self;
+
+    /// Returns the bits of the member to be shifted into place.
+    ///
+    /// `mask` has all bits within the member's width set.
+    fn to_bits(&self, mask: B) -> B;
+}
+
+macro_rules! bit_field_value_impl {
+    ($($base:ty),*) => {$(
+        impl BitFieldValue<$base> for $base {
+            fn from_bits(bits: $base) -> Self {
+                bits
+            }
+            fn to_bits(&self, _mask: $base) -> $base {
+                *self
+            }
+        }
+    )*}
+}
+
+bit_field_value_impl!(u8, u16, u32, u64, u128);
+
+/// A structure generated by `bitfields!`, whose members can be packed to / unpacked from raw bits as a whole.
+///
+/// The raw bits are widened to `u128` so that structures of any base type can be handled uniformly,
+/// such as by the bit-plane packing of `#[byte_struct_bit_planes]` fields.
+pub trait BitFieldStruct: Sized {
+    /// The sum of bit lengths of all members
+    const BITS: u32;
+
+    /// Packs the members into raw bits
+    fn to_raw_u128(&self) -> u128;
+
+    /// Unpacks the members from raw bits. Bits above `BITS` are ignored.
+    fn from_raw_u128(raw: u128) -> Self;
+}
+
+/// A bit field member that can only be read, declared with `=> ro` in `bitfields!`.
+///
+/// The value is unpacked as usual, but always packed as zero.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ReadOnly<B>(B);
+
+impl<B: Copy> ReadOnly<B> {
+    /// Returns the unpacked value
+    pub fn get(&self) -> B {
+        self.0
+    }
+}
+
+impl<B: Default> BitFieldValue<B> for ReadOnly<B> {
+    fn from_bits(bits: B) -> Self {
+        ReadOnly(bits)
+    }
+    fn to_bits(&self, _mask: B) -> B {
+        B::default()
+    }
+}
+
+/// A bit field member that can only be written, declared with `=> wo` in `bitfields!`.
+///
+/// The value is packed as usual, but always unpacked as zero, and can't be read back.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct WriteOnly<B>(B);
+
+impl<B> WriteOnly<B> {
+    /// Creates a member that packs `value`
+    pub fn new(value: B) -> Self {
+        WriteOnly(value)
+    }
+
+    /// Sets the value to be packed
+    pub fn set(&mut self, value: B) {
+        self.0 = value;
+    }
+}
+
+impl<B: Default + Copy> BitFieldValue<B> for WriteOnly<B> {
+    fn from_bits(_bits: B) -> Self {
+        WriteOnly(B::default())
+    }
+    fn to_bits(&self, _mask: B) -> B {
+        self.0
+    }
+}
+
+/// A write-1-to-clear bit field member, declared with `=> w1c` in `bitfields!`.
+///
+/// The unpacked value is the status reported by the hardware.
+/// The member is packed as zero, which leaves the status untouched,
+/// unless clearing is requested with [`set_clear`](#method.set_clear),
+/// in which case all of its bits are packed as one.
+/// This prevents a read-modify-write of the register from accidentally clearing the status.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct WriteOneToClear<B> {
+    status: B,
+    clear: bool,
+}
+
+impl<B: Copy + Default> WriteOneToClear<B> {
+    /// Creates a member that requests clearing the status when packed
+    pub fn clear() -> Self {
+        WriteOneToClear {
+            status: B::default(),
+            clear: true,
+        }
+    }
+
+    /// Returns the unpacked status
+    pub fn get(&self) -> B {
+        self.status
+    }
+
+    /// Sets whether packing the member clears the status
+    pub fn set_clear(&mut self, clear: bool) {
+        self.clear = clear;
+    }
+}
+
+impl<B: Default + Copy> BitFieldValue<B> for WriteOneToClear<B> {
+    fn from_bits(bits: B) -> Self {
+        WriteOneToClear {
+            status: bits,
+            clear: false,
+        }
+    }
+    fn to_bits(&self, mask: B) -> B {
+        if self.clear {
+            mask
+        } else {
+            B::default()
+        }
+    }
+}
+
+/// A bit field member whose packed bits are computed by the structure, declared with
+/// `=> fixed(value)`, `=> parity(even)` or `=> parity(odd)` in `bitfields!`.
+///
+/// The value is unpacked as usual, so that it can be inspected,
+/// but it is ignored when packing, and can be left as the default when creating the structure.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Computed<B>(B);
+
+impl<B: Copy> Computed<B> {
+    /// Returns the unpacked value
+    pub fn get(&self) -> B {
+        self.0
+    }
+}
+
+impl<B: Default> BitFieldValue<B> for Computed<B> {
+    fn from_bits(bits: B) -> Self {
+        Computed(bits)
+    }
+    fn to_bits(&self, _mask: B) -> B {
+        B::default()
+    }
+}
+
+/// An unsigned integer type whose bit ranges can be accessed with [`extract_bits`] and [`insert_bits`].
+///
+/// [`extract_bits`]: fn.extract_bits.html
+/// [`insert_bits`]: fn.insert_bits.html
+pub trait RawBits: Copy {
+    /// The width of the type in bits
+    const BITS: u32;
+
+    /// Widens the value to `u128`
+    fn to_u128(self) -> u128;
+
+    /// Narrows a `u128` that fits in the type
+    fn from_u128(value: u128) -> Self;
+}
+
+macro_rules! raw_bits_impl {
+    ($($t:ty),*) => {$(
+        impl RawBits for $t {
+            const BITS: u32 = <$t>::BITS;
+            fn to_u128(self) -> u128 {
+                self as u128
+            }
+            fn from_u128(value: u128) -> Self {
+                value as $t
+            }
+        }
+    )*}
+}
+
+raw_bits_impl!(u8, u16, u32, u64, u128);
+
+/// Returns the mask of bits `start .. end`, after checking the range against `bits`
+const fn bit_range_mask(start: u32, end: u32, bits: u32) -> u128 {
+    check_bit_range(start, end, bits);
+    if end - start == u128::BITS {
+        !0
+    } else {
+        ((1 << (end - start)) - 1) << start
+    }
+}
+
+/// Returns the bits of `raw` in `range`, shifted down to the least significant bits.
+///
+/// The range is numbered from the least significant bit, in the same way as members of `bitfields!`.
+/// Panics if the range extends past the width of `T`.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// assert_eq!(extract_bits::<u32>(0x1234_5678, 4..12), 0x67);
+/// ```
+pub fn extract_bits<T: RawBits>(raw: T, range: Range<u32>) -> T {
+    let mask = bit_range_mask(range.start, range.end, T::BITS);
+    T::from_u128((raw.to_u128() & mask) >> range.start)
+}
+
+/// Returns `raw` with the bits in `range` replaced by `value`.
+///
+/// Panics if the range extends past the width of `T`, or if `value` doesn't fit in the range.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// assert_eq!(insert_bits::<u32>(0x1234_5678, 4..12, 0xab), 0x1234_5ab8);
+/// ```
+pub fn insert_bits<T: RawBits>(raw: T, range: Range<u32>, value: T) -> T {
+    let mask = bit_range_mask(range.start, range.end, T::BITS);
+    let value = value.to_u128();
+    assert!(value <= mask >> range.start, "Value doesn't fit in the bit range");
+    T::from_u128((raw.to_u128() & !mask) | (value << range.start))
+}
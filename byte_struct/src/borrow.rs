@@ -0,0 +1,61 @@
+//! Unpacking into structures that borrow from the unpacked bytes.
+
+use crate::{check_len, ByteStructError, ByteStructLen, ByteStructWrite};
+
+/// A data structure that can be unpacked from raw bytes, borrowing from them for lifetime `'a`.
+///
+/// This allows opaque blobs in large inputs to be referred to in place instead of copied.
+/// It is implemented for `&'a [u8; N]`, and derived by
+/// [`#[derive(ByteStruct)]`](https://docs.rs/byte_struct_derive/*/byte_struct_derive/derive.ByteStruct.html)
+/// in place of [`ByteStructRead`](trait.ByteStructRead.html) for structures with a lifetime parameter.
+/// Fields whose type has the lifetime are borrowed, including `&'a [u8]` slices
+/// of a length given by `#[byte_struct(len = N)]`.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Record<'a> {
+///     id: u16,
+///     key: &'a [u8; 4],
+///     #[byte_struct(len = 2)]
+///     payload: &'a [u8],
+/// }
+///
+/// let bytes = [1, 0, b'a', b'b', b'c', b'd', 5, 6];
+/// let record = Record::read_bytes_ref(&bytes[..]);
+/// assert_eq!(record, Record { id: 1, key: b"abcd", payload: &[5, 6] });
+/// assert!(core::ptr::eq(record.payload, &bytes[6..]));
+/// ```
+pub trait ByteStructReadRef<'a>: ByteStructLen + Sized {
+    /// Unpacks raw bytes from a slice into a new struct that borrows from it
+    fn read_bytes_ref(bytes: &'a [u8]) -> Self;
+
+    /// Unpacks raw bytes from a slice into a new struct that borrows from it,
+    /// reporting malformed input as an error
+    ///
+    /// The default implementation only checks the length of the slice before calling
+    /// [`read_bytes_ref`](#tymethod.read_bytes_ref).
+    fn try_read_bytes_ref(bytes: &'a [u8]) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        Ok(Self::read_bytes_ref(bytes))
+    }
+}
+
+impl<const N: usize> ByteStructLen for &[u8; N] {
+    const BYTE_LEN: usize = N;
+}
+
+impl<const N: usize> ByteStructWrite for &[u8; N] {
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        bytes[..N].copy_from_slice(*self);
+    }
+}
+
+impl<'a, const N: usize> ByteStructReadRef<'a> for &'a [u8; N] {
+    fn read_bytes_ref(bytes: &'a [u8]) -> Self {
+        bytes[..N].try_into().unwrap()
+    }
+}
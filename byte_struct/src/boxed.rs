@@ -0,0 +1,81 @@
+//! Packing of boxed values, so that large fields can live on the heap.
+
+use crate::{
+    check_len, ByteStructDebugJson, ByteStructError, ByteStructLen, ByteStructRead,
+    ByteStructUnspecifiedByteOrder, ByteStructWrite,
+};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Result as FmtResult, Write};
+
+impl<T: ByteStructLen> ByteStructLen for Box<T> {
+    const BYTE_LEN: usize = T::BYTE_LEN;
+}
+
+/// Packed in the same way as the boxed value
+impl<T: ByteStructWrite> ByteStructWrite for Box<T> {
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        (**self).write_bytes(bytes);
+    }
+}
+
+impl<T: ByteStructRead> ByteStructRead for Box<T> {
+    fn read_bytes(bytes: &[u8]) -> Self {
+        Box::new(T::read_bytes(bytes))
+    }
+    fn try_read_bytes(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        T::try_read_bytes(bytes).map(Box::new)
+    }
+}
+
+impl<T: ByteStructDebugJson + ?Sized> ByteStructDebugJson for Box<T> {
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> FmtResult {
+        (**self).write_debug_json(out)
+    }
+}
+
+// Unpacks the elements one by one directly into a heap allocation,
+// so that the array never exists on the stack
+fn try_read_boxed_array<T: ByteStructLen, const N: usize>(
+    bytes: &[u8],
+    read: impl Fn(&[u8]) -> Result<T, ByteStructError>,
+) -> Result<Box<[T; N]>, ByteStructError> {
+    let len = T::BYTE_LEN;
+    let mut elements = Vec::with_capacity(N);
+    for i in 0..N {
+        let element =
+            read(&bytes[i * len..(i + 1) * len]).map_err(|e| e.within_element(i, i * len))?;
+        elements.push(element);
+    }
+    Ok(elements.into_boxed_slice().try_into().ok().unwrap())
+}
+
+/// Packed in the same way as the array, which is unpacked directly on the heap
+impl<T: ByteStructUnspecifiedByteOrder, const N: usize> ByteStructUnspecifiedByteOrder
+    for Box<[T; N]>
+{
+    fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+        (**self).write_bytes_default_le(bytes);
+    }
+    fn read_bytes_default_le(bytes: &[u8]) -> Self {
+        try_read_boxed_array(bytes, |bytes| Ok(T::read_bytes_default_le(bytes)))
+            .ok()
+            .unwrap()
+    }
+    fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+        (**self).write_bytes_default_be(bytes);
+    }
+    fn read_bytes_default_be(bytes: &[u8]) -> Self {
+        try_read_boxed_array(bytes, |bytes| Ok(T::read_bytes_default_be(bytes)))
+            .ok()
+            .unwrap()
+    }
+    fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        try_read_boxed_array(bytes, T::try_read_bytes_default_le)
+    }
+    fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        try_read_boxed_array(bytes, T::try_read_bytes_default_be)
+    }
+}
@@ -0,0 +1,95 @@
+//! Packing into and unpacking from `bytes::Buf` and `bytes::BufMut`.
+
+use crate::vectored::with_staging;
+use crate::{ByteStructError, ByteStructRead, ByteStructWrite, ErrorKind};
+use bytes::{Buf, BufMut};
+
+/// Unpacks structures from a [`bytes::Buf`], advancing past the packed bytes.
+///
+/// This is implemented for every `Buf`. If the packed bytes of a structure are not contiguous
+/// in the buffer, they are copied to a staging buffer before unpacking.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+/// use bytes::{Buf, BufMut};
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_be]
+/// struct Header {
+///     kind: u8,
+///     size: u16,
+/// }
+///
+/// let mut out = Vec::new();
+/// out.put_struct(&Header { kind: 1, size: 2 });
+/// out.put_u8(0xFF);
+///
+/// // The header is split across the two chunks of the chained buffer
+/// let mut buf = (&out[..1]).chain(&out[1..]);
+/// assert_eq!(buf.get_struct::<Header>(), Header { kind: 1, size: 2 });
+/// assert_eq!(buf.get_u8(), 0xFF);
+/// ```
+pub trait ByteStructBufExt: Buf {
+    /// Unpacks a structure from the buffer.
+    ///
+    /// Panics if fewer than `T::BYTE_LEN` bytes remain.
+    fn get_struct<T: ByteStructRead>(&mut self) -> T {
+        assert!(
+            self.remaining() >= T::BYTE_LEN,
+            "Buffer too short for the structure"
+        );
+        if self.chunk().len() >= T::BYTE_LEN {
+            let value = T::read_bytes(&self.chunk()[..T::BYTE_LEN]);
+            self.advance(T::BYTE_LEN);
+            return value;
+        }
+        with_staging(T::BYTE_LEN, |staging| {
+            self.copy_to_slice(staging);
+            T::read_bytes(staging)
+        })
+    }
+
+    /// Unpacks a structure from the buffer, reporting malformed input as an error.
+    ///
+    /// Nothing is consumed if fewer than `T::BYTE_LEN` bytes remain.
+    /// Otherwise, `T::BYTE_LEN` bytes are consumed even if unpacking fails.
+    fn try_get_struct<T: ByteStructRead>(&mut self) -> Result<T, ByteStructError> {
+        if self.remaining() < T::BYTE_LEN {
+            return Err(ByteStructError::new(
+                ErrorKind::ShortBuffer,
+                None,
+                self.remaining(),
+            ));
+        }
+        if self.chunk().len() >= T::BYTE_LEN {
+            let value = T::try_read_bytes(&self.chunk()[..T::BYTE_LEN]);
+            self.advance(T::BYTE_LEN);
+            return value;
+        }
+        with_staging(T::BYTE_LEN, |staging| {
+            self.copy_to_slice(staging);
+            T::try_read_bytes(staging)
+        })
+    }
+}
+
+impl<B: Buf + ?Sized> ByteStructBufExt for B {}
+
+/// Packs structures into a [`bytes::BufMut`].
+///
+/// This is implemented for every `BufMut`. The structure is packed into a staging buffer
+/// before it is put into the buffer.
+pub trait ByteStructBufMutExt: BufMut {
+    /// Packs a structure and puts it into the buffer.
+    ///
+    /// Panics if the buffer doesn't have `T::BYTE_LEN` bytes of remaining capacity.
+    fn put_struct<T: ByteStructWrite>(&mut self, value: &T) {
+        with_staging(T::BYTE_LEN, |staging| {
+            value.write_bytes(staging);
+            self.put_slice(staging);
+        })
+    }
+}
+
+impl<B: BufMut + ?Sized> ByteStructBufMutExt for B {}
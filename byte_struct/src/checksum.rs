@@ -0,0 +1,324 @@
+//! Checksums, such as the ones' complement checksum of the Internet protocols,
+//! and values packed along with their checksum.
+
+use crate::{
+    check_len, ByteStruct, ByteStructDebugJson, ByteStructError, ByteStructLen,
+    ByteStructUnspecifiedByteOrder, ErrorKind,
+};
+use core::fmt;
+use core::marker::PhantomData;
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+/// An accumulator of the Internet checksum of RFC 1071,
+/// used by the headers of IPv4, ICMP, UDP and TCP.
+///
+/// The checksum is the ones' complement of the ones' complement sum of the data
+/// taken as big-endian 16-bit words, with an odd trailing byte padded with zero.
+/// Data can be added in pieces of any length, with the same result as adding them at once.
+///
+/// Checksums that cover only the bytes of one structure can be computed by
+/// `#[byte_struct(internet_checksum)]` on the checksum field when deriving `ByteStruct`.
+/// The checksums of UDP and TCP also cover a pseudo-header taken from the IP header,
+/// and the payload after the header, which this type can accumulate along with the packed header.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+/// use std::net::Ipv4Addr;
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_be]
+/// struct UdpHeader {
+///     src_port: u16,
+///     dst_port: u16,
+///     len: u16,
+///     checksum: u16,
+/// }
+///
+/// let (src, dst) = (Ipv4Addr::new(192, 0, 2, 1), Ipv4Addr::new(192, 0, 2, 2));
+/// let mut datagram = [0; 12];
+/// UdpHeader { src_port: 1024, dst_port: 53, len: 12, checksum: 0 }.write_bytes(&mut datagram);
+/// datagram[8..].copy_from_slice(b"ping");
+///
+/// let mut checksum = InternetChecksum::new();
+/// checksum.add_ipv4_pseudo_header(src, dst, 17, 12);
+/// checksum.seal(&mut datagram, 6);
+///
+/// let mut checksum = InternetChecksum::new();
+/// checksum.add_ipv4_pseudo_header(src, dst, 17, 12);
+/// assert!(checksum.verify(&datagram));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct InternetChecksum {
+    sum: u32,
+    // The first byte of an incomplete word at the end of the data added so far
+    pending: Option<u8>,
+}
+
+impl InternetChecksum {
+    /// Creates an accumulator with no data added
+    pub const fn new() -> Self {
+        InternetChecksum {
+            sum: 0,
+            pending: None,
+        }
+    }
+
+    /// Adds `bytes` to the data covered by the checksum
+    pub fn add(&mut self, bytes: &[u8]) -> &mut Self {
+        let mut bytes = bytes;
+        if let Some(high) = self.pending.take() {
+            match bytes.split_first() {
+                Some((&low, rest)) => {
+                    self.add_word(u16::from_be_bytes([high, low]));
+                    bytes = rest;
+                }
+                None => self.pending = Some(high),
+            }
+        }
+        let mut words = bytes.chunks_exact(2);
+        for word in &mut words {
+            self.add_word(u16::from_be_bytes([word[0], word[1]]));
+        }
+        if let [last] = words.remainder() {
+            self.pending = Some(*last);
+        }
+        self
+    }
+
+    /// Adds the IPv4 pseudo-header of UDP and TCP, where `protocol` is the protocol number
+    /// of the IPv4 header, and `len` is the length of the UDP datagram or TCP segment
+    pub fn add_ipv4_pseudo_header(
+        &mut self,
+        src: Ipv4Addr,
+        dst: Ipv4Addr,
+        protocol: u8,
+        len: u16,
+    ) -> &mut Self {
+        self.add(&src.octets())
+            .add(&dst.octets())
+            .add(&[0, protocol])
+            .add(&len.to_be_bytes())
+    }
+
+    /// Adds the IPv6 pseudo-header of UDP, TCP and ICMPv6, where `next_header` is
+    /// the upper-layer protocol number, and `len` is the length of the upper-layer packet
+    pub fn add_ipv6_pseudo_header(
+        &mut self,
+        src: Ipv6Addr,
+        dst: Ipv6Addr,
+        next_header: u8,
+        len: u32,
+    ) -> &mut Self {
+        self.add(&src.octets())
+            .add(&dst.octets())
+            .add(&len.to_be_bytes())
+            .add(&[0, 0, 0, next_header])
+    }
+
+    /// Returns the checksum of the data added so far, to be stored as big-endian
+    pub fn checksum(&self) -> u16 {
+        let mut sum = self.sum;
+        if let Some(high) = self.pending {
+            sum += u32::from(high) << 8;
+        }
+        while sum > 0xFFFF {
+            sum = (sum & 0xFFFF) + (sum >> 16);
+        }
+        !(sum as u16)
+    }
+
+    /// Adds `bytes`, which hold the stored checksum, and returns `true`
+    /// if the stored checksum matches all the data added
+    pub fn verify(&mut self, bytes: &[u8]) -> bool {
+        self.add(bytes).checksum() == 0
+    }
+
+    /// Computes the checksum of the data added so far and `bytes`,
+    /// and stores it as big-endian in `bytes` at `offset`.
+    ///
+    /// The checksum is computed as if the two bytes at `offset` were zero.
+    ///
+    /// Panics if `bytes` is too short to contain the checksum at `offset`.
+    pub fn seal(&mut self, bytes: &mut [u8], offset: usize) {
+        bytes[offset..offset + 2].fill(0);
+        let checksum = self.add(bytes).checksum();
+        bytes[offset..offset + 2].copy_from_slice(&checksum.to_be_bytes());
+    }
+
+    fn add_word(&mut self, word: u16) {
+        self.sum += u32::from(word);
+        if self.sum > 0xFFFF {
+            self.sum = (self.sum & 0xFFFF) + 1;
+        }
+    }
+}
+
+/// Returns the Internet checksum of RFC 1071 of `bytes`. See
+/// [`InternetChecksum`](struct.InternetChecksum.html).
+pub fn internet_checksum(bytes: &[u8]) -> u16 {
+    InternetChecksum::new().add(bytes).checksum()
+}
+
+/// A checksum algorithm, computed over the packed bytes of a [`Checked`](struct.Checked.html) value.
+///
+/// This is implemented by [`InternetChecksum`](struct.InternetChecksum.html),
+/// and can be implemented for other algorithms such as CRCs.
+pub trait Checksum {
+    /// The checksum, packed in the byte order of the containing structure
+    type Output: ByteStructUnspecifiedByteOrder + PartialEq;
+
+    /// Computes the checksum of `bytes`
+    fn compute(bytes: &[u8]) -> Self::Output;
+}
+
+/// The Internet checksum, which matches the Internet protocols when packed as big-endian
+impl Checksum for InternetChecksum {
+    type Output = u16;
+
+    fn compute(bytes: &[u8]) -> u16 {
+        internet_checksum(bytes)
+    }
+}
+
+/// A value of `T` followed by the checksum `C` of its packed bytes.
+///
+/// The checksum is computed when packing, and verified when unpacking.
+/// A mismatch panics when unpacking, or fails with `ErrorKind::ValidationFailed`
+/// at the offset of the checksum.
+/// This allows each message body of a protocol to carry its own checksum,
+/// independently of the structures it is embedded in.
+///
+/// The checksum is packed in the byte order of the containing structure.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_be]
+/// struct Body {
+///     id: u16,
+///     value: u16,
+/// }
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_be]
+/// struct Message {
+///     kind: u8,
+///     body: Checked<Body, InternetChecksum>,
+/// }
+///
+/// let message = Message { kind: 1, body: Checked::new(Body { id: 0x0102, value: 0x0304 }) };
+/// let mut bytes = [0; Message::BYTE_LEN];
+/// message.write_bytes(&mut bytes[..]);
+/// assert_eq!(bytes, [1, 1, 2, 3, 4, 0xFB, 0xF9]);
+/// assert_eq!(Message::read_bytes(&bytes[..]), message);
+///
+/// bytes[2] = 0;
+/// assert_eq!(Message::try_read_bytes(&bytes[..]).unwrap_err().offset, 5);
+/// ```
+pub struct Checked<T, C> {
+    /// The value covered by the checksum
+    pub value: T,
+    checksum: PhantomData<C>,
+}
+
+impl<T, C> Checked<T, C> {
+    /// Creates a checked value holding `value`
+    pub const fn new(value: T) -> Self {
+        Checked {
+            value,
+            checksum: PhantomData,
+        }
+    }
+
+    /// Returns the value covered by the checksum
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: ByteStruct, C: Checksum> Checked<T, C> {
+    fn write_with(&self, bytes: &mut [u8], write: fn(&C::Output, &mut [u8])) {
+        let (body, checksum) = bytes[..Self::BYTE_LEN].split_at_mut(T::BYTE_LEN);
+        self.value.write_bytes(body);
+        write(&C::compute(body), checksum);
+    }
+
+    fn try_read_with(
+        bytes: &[u8],
+        read: fn(&[u8]) -> Result<C::Output, ByteStructError>,
+    ) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        let (body, checksum) = bytes[..Self::BYTE_LEN].split_at(T::BYTE_LEN);
+        let value = T::try_read_bytes(body)?;
+        if read(checksum)? != C::compute(body) {
+            return Err(ByteStructError::new(
+                ErrorKind::ValidationFailed,
+                None,
+                T::BYTE_LEN,
+            ));
+        }
+        Ok(Self::new(value))
+    }
+}
+
+impl<T: Clone, C> Clone for Checked<T, C> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<T: Copy, C> Copy for Checked<T, C> {}
+
+impl<T: PartialEq, C> PartialEq for Checked<T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq, C> Eq for Checked<T, C> {}
+
+impl<T: Default, C> Default for Checked<T, C> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for Checked<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Checked({:?})", self.value)
+    }
+}
+
+impl<T: ByteStructLen, C: Checksum> ByteStructLen for Checked<T, C> {
+    const BYTE_LEN: usize = crate::__private::len_sum(&[T::BYTE_LEN, C::Output::BYTE_LEN]);
+}
+
+impl<T: ByteStruct, C: Checksum> ByteStructUnspecifiedByteOrder for Checked<T, C> {
+    fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+        self.write_with(bytes, C::Output::write_bytes_default_le);
+    }
+    fn read_bytes_default_le(bytes: &[u8]) -> Self {
+        Self::try_read_bytes_default_le(bytes).expect("Checksum mismatch")
+    }
+    fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+        self.write_with(bytes, C::Output::write_bytes_default_be);
+    }
+    fn read_bytes_default_be(bytes: &[u8]) -> Self {
+        Self::try_read_bytes_default_be(bytes).expect("Checksum mismatch")
+    }
+    fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        Self::try_read_with(bytes, C::Output::try_read_bytes_default_le)
+    }
+    fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        Self::try_read_with(bytes, C::Output::try_read_bytes_default_be)
+    }
+}
+
+impl<T: ByteStructDebugJson, C> ByteStructDebugJson for Checked<T, C> {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        self.value.write_debug_json(out)
+    }
+}
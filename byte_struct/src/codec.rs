@@ -0,0 +1,261 @@
+//! Splitting a byte stream into packed structures,
+//! or into frames made of a packed header and a variable-length payload.
+
+use crate::{ByteStructError, ByteStructRead, ByteStructWrite, ErrorKind};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+/// A frame split from a byte stream by [`FrameCodec`](struct.FrameCodec.html).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Frame<H> {
+    /// The unpacked header
+    pub header: H,
+    /// The payload following the header, whose length is given by the header
+    pub payload: Vec<u8>,
+}
+
+/// An encoder and decoder of frames that consist of a header of type `H`
+/// followed by a payload whose length is given by the header.
+///
+/// Bytes received from a stream are appended with [`extend`](#method.extend) as they arrive,
+/// and complete frames are taken with [`decode`](#method.decode).
+/// Partial frames stay buffered until the rest of their bytes arrive.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_be]
+/// struct Header {
+///     kind: u8,
+///     payload_len: u16,
+/// }
+///
+/// let mut codec = FrameCodec::new(|header: &Header| header.payload_len as usize);
+/// let mut stream = Vec::new();
+/// codec.encode(&Header { kind: 1, payload_len: 2 }, &[0xAA, 0xBB], &mut stream);
+///
+/// codec.extend(&stream[..4]);
+/// assert_eq!(codec.decode(), Ok(None));
+/// codec.extend(&stream[4..]);
+/// let frame = codec.decode().unwrap().unwrap();
+/// assert_eq!(frame.header, Header { kind: 1, payload_len: 2 });
+/// assert_eq!(frame.payload, [0xAA, 0xBB]);
+/// ```
+pub struct FrameCodec<H> {
+    payload_len: fn(&H) -> usize,
+    max_payload_len: usize,
+    buffer: Vec<u8>,
+}
+
+impl<H> FrameCodec<H> {
+    /// Creates a codec that gets the length of the payload from the header with `payload_len`
+    pub fn new(payload_len: fn(&H) -> usize) -> Self {
+        FrameCodec {
+            payload_len,
+            max_payload_len: usize::MAX,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Sets the maximum payload length accepted by [`decode`](#method.decode),
+    /// so that a corrupted header can't make the codec buffer an arbitrary amount of bytes.
+    pub fn with_max_payload_len(mut self, max_payload_len: usize) -> Self {
+        self.max_payload_len = max_payload_len;
+        self
+    }
+
+    /// Appends bytes received from the stream
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the number of bytes buffered but not yet decoded
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Discards all buffered bytes
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+}
+
+impl<H: ByteStructRead> FrameCodec<H> {
+    /// Takes the next complete frame from the buffered bytes.
+    ///
+    /// Returns `Ok(None)` if the buffer doesn't contain a complete frame yet.
+    /// If the header fails to unpack, or it gives a payload length longer than the maximum,
+    /// all buffered bytes are discarded, since the start of the next frame can't be found,
+    /// and the error is returned.
+    pub fn decode(&mut self) -> Result<Option<Frame<H>>, ByteStructError> {
+        if self.buffer.len() < H::BYTE_LEN {
+            return Ok(None);
+        }
+        let header = match H::try_read_bytes(&self.buffer) {
+            Ok(header) => header,
+            Err(error) => {
+                self.buffer.clear();
+                return Err(error);
+            }
+        };
+        let payload_len = (self.payload_len)(&header);
+        if payload_len > self.max_payload_len {
+            self.buffer.clear();
+            return Err(ByteStructError::new(ErrorKind::InvalidValue, None, 0));
+        }
+        let frame_len = match H::BYTE_LEN.checked_add(payload_len) {
+            Some(frame_len) if frame_len <= self.buffer.len() => frame_len,
+            _ => return Ok(None),
+        };
+        let payload = self.buffer[H::BYTE_LEN..frame_len].to_vec();
+        self.buffer.drain(..frame_len);
+        Ok(Some(Frame { header, payload }))
+    }
+}
+
+impl<H: ByteStructWrite> FrameCodec<H> {
+    /// Packs the header followed by the payload, and appends them to `out`.
+    ///
+    /// Panics if the length of `payload` doesn't match the payload length given by the header.
+    pub fn encode(&self, header: &H, payload: &[u8], out: &mut Vec<u8>) {
+        assert_eq!(
+            (self.payload_len)(header),
+            payload.len(),
+            "Payload length doesn't match the header"
+        );
+        let start = out.len();
+        out.resize(start + H::BYTE_LEN, 0);
+        header.write_bytes(&mut out[start..]);
+        out.extend_from_slice(payload);
+    }
+}
+
+/// A parser that splits a byte stream into consecutive packed structures of type `T`.
+///
+/// Bytes are pushed in chunks of any size as they arrive, and the structures completed by
+/// each chunk are unpacked from it. Only the bytes of a structure that spans chunks are buffered,
+/// so the parser never holds more than `T::BYTE_LEN` bytes.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Sample {
+///     channel: u8,
+///     value: u16,
+/// }
+///
+/// let mut parser = StreamParser::<Sample>::new();
+/// assert_eq!(parser.push(&[1, 0x34]).count(), 0);
+/// let samples: Vec<_> = parser.push(&[0x12, 2, 0x78, 0x56, 3]).collect();
+/// assert_eq!(
+///     samples,
+///     [
+///         Ok(Sample { channel: 1, value: 0x1234 }),
+///         Ok(Sample { channel: 2, value: 0x5678 }),
+///     ]
+/// );
+/// assert_eq!(parser.buffered_len(), 1);
+/// ```
+pub struct StreamParser<T> {
+    buffer: Vec<u8>,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: ByteStructRead> StreamParser<T> {
+    /// Creates a parser with an empty buffer.
+    ///
+    /// Panics if `T` is zero-sized, since the stream would then contain endless structures.
+    pub fn new() -> Self {
+        assert!(
+            T::BYTE_LEN > 0,
+            "Zero-sized structures can't be parsed from a stream"
+        );
+        StreamParser {
+            buffer: Vec::with_capacity(T::BYTE_LEN),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Pushes bytes received from the stream, and returns an iterator over the structures
+    /// they complete.
+    ///
+    /// Each structure is unpacked with `try_read_bytes`, and one that fails to unpack is yielded
+    /// as an error without affecting the following ones. The bytes of an incomplete structure
+    /// at the end are buffered when the iterator is dropped. Complete structures that are not
+    /// taken from the iterator are discarded, keeping the parser aligned with the stream.
+    pub fn push<'a>(&'a mut self, bytes: &'a [u8]) -> StreamParserIter<'a, T> {
+        StreamParserIter {
+            parser: self,
+            bytes,
+        }
+    }
+}
+
+impl<T> StreamParser<T> {
+    /// Returns the number of bytes buffered for the next structure
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Discards the buffered bytes, such as after a gap in the stream
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+}
+
+impl<T: ByteStructRead> Default for StreamParser<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An iterator over the structures completed by the bytes pushed to a
+/// [`StreamParser`](struct.StreamParser.html).
+pub struct StreamParserIter<'a, T: ByteStructRead> {
+    parser: &'a mut StreamParser<T>,
+    bytes: &'a [u8],
+}
+
+impl<T: ByteStructRead> Iterator for StreamParserIter<'_, T> {
+    type Item = Result<T, ByteStructError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let buffer = &mut self.parser.buffer;
+        if buffer.is_empty() {
+            if self.bytes.len() < T::BYTE_LEN {
+                return None;
+            }
+            let (record, rest) = self.bytes.split_at(T::BYTE_LEN);
+            self.bytes = rest;
+            return Some(T::try_read_bytes(record));
+        }
+        let missing = T::BYTE_LEN - buffer.len();
+        if self.bytes.len() < missing {
+            return None;
+        }
+        buffer.extend_from_slice(&self.bytes[..missing]);
+        self.bytes = &self.bytes[missing..];
+        let value = T::try_read_bytes(buffer);
+        buffer.clear();
+        Some(value)
+    }
+}
+
+impl<T: ByteStructRead> Drop for StreamParserIter<'_, T> {
+    fn drop(&mut self) {
+        let buffer = &mut self.parser.buffer;
+        let total = buffer.len() + self.bytes.len();
+        if total < T::BYTE_LEN {
+            buffer.extend_from_slice(self.bytes);
+        } else {
+            let partial = total % T::BYTE_LEN;
+            buffer.clear();
+            buffer.extend_from_slice(&self.bytes[self.bytes.len() - partial..]);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Parsers of packed structures for the `nom` and `winnow` parser combinator libraries.
+
+use crate::ByteStructRead;
+
+/// A `nom` parser that unpacks a structure from the start of the input.
+///
+/// This lets fixed-size parts of a format, such as headers, be defined as byte_struct structures
+/// while the variable-length parts are parsed with `nom` combinators.
+/// The input is treated as complete: fewer than `T::BYTE_LEN` bytes fail with `ErrorKind::Eof`,
+/// and bytes that fail to unpack fail with `ErrorKind::Verify`, both as recoverable errors.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+/// use nom::{bytes::complete::take, IResult, Parser};
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_be]
+/// struct Header {
+///     kind: u8,
+///     size: u16,
+/// }
+///
+/// fn record(input: &[u8]) -> IResult<&[u8], (Header, &[u8])> {
+///     let (input, header) = nom_parse::<Header, _>(input)?;
+///     let (input, body) = take(header.size)(input)?;
+///     Ok((input, (header, body)))
+/// }
+///
+/// let (rest, (header, body)) = record(&[1, 0, 2, 0xAA, 0xBB, 0xCC]).unwrap();
+/// assert_eq!(header, Header { kind: 1, size: 2 });
+/// assert_eq!(body, [0xAA, 0xBB]);
+/// assert_eq!(rest, [0xCC]);
+/// ```
+#[cfg(feature = "nom")]
+pub fn nom_parse<'a, T, E>(input: &'a [u8]) -> nom::IResult<&'a [u8], T, E>
+where
+    T: ByteStructRead,
+    E: nom::error::ParseError<&'a [u8]>,
+{
+    use nom::error::ErrorKind;
+    if input.len() < T::BYTE_LEN {
+        return Err(nom::Err::Error(E::from_error_kind(input, ErrorKind::Eof)));
+    }
+    match T::try_read_bytes(input) {
+        Ok(value) => Ok((&input[T::BYTE_LEN..], value)),
+        Err(_) => Err(nom::Err::Error(E::from_error_kind(
+            input,
+            ErrorKind::Verify,
+        ))),
+    }
+}
+
+/// A `winnow` parser that unpacks a structure from the start of the input.
+///
+/// See [`nom_parse`](fn.nom_parse.html) for the purpose. The input is treated as complete,
+/// and both input that is too short and bytes that fail to unpack are reported
+/// with `ParserError::from_input`, leaving the input unchanged.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+/// use winnow::{token::take, Parser};
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_be]
+/// struct Header {
+///     kind: u8,
+///     size: u16,
+/// }
+///
+/// fn record<'a>(input: &mut &'a [u8]) -> winnow::Result<(Header, &'a [u8])> {
+///     let header: Header = winnow_parse.parse_next(input)?;
+///     let body = take(header.size).parse_next(input)?;
+///     Ok((header, body))
+/// }
+///
+/// let mut input = &[1, 0, 2, 0xAA, 0xBB, 0xCC][..];
+/// let (header, body) = record(&mut input).unwrap();
+/// assert_eq!(header, Header { kind: 1, size: 2 });
+/// assert_eq!(body, [0xAA, 0xBB]);
+/// assert_eq!(input, [0xCC]);
+/// ```
+#[cfg(feature = "winnow")]
+pub fn winnow_parse<'a, T, E>(input: &mut &'a [u8]) -> Result<T, E>
+where
+    T: ByteStructRead,
+    E: winnow::error::ParserError<&'a [u8]>,
+{
+    if input.len() < T::BYTE_LEN {
+        return Err(E::from_input(input));
+    }
+    let value = T::try_read_bytes(input).map_err(|_| E::from_input(input))?;
+    *input = &input[T::BYTE_LEN..];
+    Ok(value)
+}
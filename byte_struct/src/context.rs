@@ -0,0 +1,87 @@
+//! Packing and unpacking that depend on a context given by the caller.
+
+use crate::{check_len, ByteStructError, ByteStructLen};
+
+/// A type that can be unpacked from raw bytes given a context of type `C`.
+///
+/// The context carries state from outside of the bytes that changes how they are interpreted,
+/// such as a byte order or a format version taken from a file header.
+/// This trait can be derived by
+/// [`#[derive(ByteStruct)]`](https://docs.rs/byte_struct_derive/*/byte_struct_derive/derive.ByteStruct.html)
+/// with `#[byte_struct(context = "Type")]` on the struct, which passes the context to the fields
+/// marked with `#[byte_struct(ctx)]`.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// struct Endian {
+///     big: bool,
+/// }
+///
+/// // A value in the byte order chosen by the context
+/// #[derive(PartialEq, Debug)]
+/// struct Native(u32);
+///
+/// impl ByteStructLen for Native {
+///     const BYTE_LEN: usize = 4;
+/// }
+///
+/// impl ByteStructReadCtx<Endian> for Native {
+///     fn read_bytes_with_ctx(bytes: &[u8], ctx: &Endian) -> Self {
+///         if ctx.big {
+///             Native(u32::read_bytes_default_be(&bytes[..4]))
+///         } else {
+///             Native(u32::read_bytes_default_le(&bytes[..4]))
+///         }
+///     }
+/// }
+///
+/// impl ByteStructWriteCtx<Endian> for Native {
+///     fn write_bytes_with_ctx(&self, bytes: &mut [u8], ctx: &Endian) {
+///         if ctx.big {
+///             self.0.write_bytes_default_be(&mut bytes[..4]);
+///         } else {
+///             self.0.write_bytes_default_le(&mut bytes[..4]);
+///         }
+///     }
+/// }
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct(context = "Endian", endian = "le")]
+/// struct Record {
+///     kind: u8,
+///     #[byte_struct(ctx)]
+///     value: Native,
+/// }
+///
+/// let bytes = [1, 0, 0, 0, 2];
+/// let record = Record::read_bytes_with_ctx(&bytes, &Endian { big: true });
+/// assert_eq!(record, Record { kind: 1, value: Native(2) });
+/// ```
+pub trait ByteStructReadCtx<C: ?Sized>: ByteStructLen {
+    /// Unpacks raw bytes from a slice into a new struct with the given context
+    fn read_bytes_with_ctx(bytes: &[u8], ctx: &C) -> Self;
+
+    /// Unpacks raw bytes from a slice into a new struct with the given context,
+    /// reporting malformed input as an error
+    ///
+    /// The default implementation only checks the length of the slice before calling
+    /// [`read_bytes_with_ctx`](#tymethod.read_bytes_with_ctx).
+    fn try_read_bytes_with_ctx(bytes: &[u8], ctx: &C) -> Result<Self, ByteStructError>
+    where
+        Self: Sized,
+    {
+        check_len::<Self>(bytes)?;
+        Ok(Self::read_bytes_with_ctx(bytes, ctx))
+    }
+}
+
+/// A type that can be packed into raw bytes given a context of type `C`.
+///
+/// This is the packing counterpart of [`ByteStructReadCtx`](trait.ByteStructReadCtx.html),
+/// and can be derived in the same way.
+pub trait ByteStructWriteCtx<C: ?Sized>: ByteStructLen {
+    /// Packs the struct into raw bytes and write to a slice with the given context
+    fn write_bytes_with_ctx(&self, bytes: &mut [u8], ctx: &C);
+}
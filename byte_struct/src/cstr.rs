@@ -0,0 +1,130 @@
+//! NUL-terminated strings, stored in a fixed reserved size.
+
+use crate::json::{write_bytes_json, write_json_escaped};
+use crate::{
+    check_len, ByteStructDebugJson, ByteStructError, ByteStructLen, ByteStructRead,
+    ByteStructWrite, ErrorKind,
+};
+use core::ffi::CStr;
+use core::fmt;
+
+/// A NUL-terminated string in `MAX` reserved bytes, as a `char name[MAX]` member of a C struct.
+///
+/// The string can be up to `MAX - 1` bytes long, so that it is always followed by a NUL.
+/// When packing, the bytes after the string are filled with NUL.
+/// When unpacking, the string ends at the first NUL, and the bytes after it are ignored.
+/// Reserved bytes without a NUL panic when unpacking, or fail with `ErrorKind::InvalidValue`.
+///
+/// The content is kept as bytes, and can be viewed as UTF-8 with [`as_str`](#method.as_str),
+/// or as a `CStr` with [`as_c_str`](#method.as_c_str).
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Entry {
+///     name: CStrField<8>,
+///     size: u32,
+/// }
+///
+/// let entry = Entry::read_bytes(&b"boot\0xyz\x10\0\0\0"[..]);
+/// assert_eq!(entry.name.as_str(), Some("boot"));
+/// assert_eq!(entry.size, 16);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CStrField<const MAX: usize> {
+    bytes: [u8; MAX],
+    len: usize,
+}
+
+impl<const MAX: usize> CStrField<MAX> {
+    /// Creates a string holding `bytes`, or returns `None` if it is longer than `MAX - 1` bytes
+    /// or contains a NUL
+    pub fn new(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() >= MAX || bytes.contains(&0) {
+            return None;
+        }
+        let mut value = Self::default();
+        value.bytes[..bytes.len()].copy_from_slice(bytes);
+        value.len = bytes.len();
+        Some(value)
+    }
+
+    /// Returns the length of the string in bytes, without the NUL
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the string is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bytes of the string, without the NUL
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    /// Returns the string as UTF-8, or `None` if it isn't valid UTF-8
+    pub fn as_str(&self) -> Option<&str> {
+        core::str::from_utf8(self.as_bytes()).ok()
+    }
+
+    /// Returns the string as a `CStr`
+    pub fn as_c_str(&self) -> &CStr {
+        CStr::from_bytes_with_nul(&self.bytes[..=self.len]).unwrap()
+    }
+}
+
+impl<const MAX: usize> Default for CStrField<MAX> {
+    fn default() -> Self {
+        CStrField {
+            bytes: [0; MAX],
+            len: 0,
+        }
+    }
+}
+
+impl<const MAX: usize> fmt::Debug for CStrField<MAX> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CStrField({:?})", self.as_c_str())
+    }
+}
+
+impl<const MAX: usize> ByteStructLen for CStrField<MAX> {
+    const BYTE_LEN: usize = {
+        assert!(MAX > 0, "CStrField needs room for the NUL");
+        MAX
+    };
+}
+
+impl<const MAX: usize> ByteStructWrite for CStrField<MAX> {
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        bytes[..MAX].copy_from_slice(&self.bytes);
+    }
+}
+
+impl<const MAX: usize> ByteStructRead for CStrField<MAX> {
+    fn read_bytes(bytes: &[u8]) -> Self {
+        Self::try_read_bytes(bytes).expect("C string without NUL")
+    }
+    fn try_read_bytes(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        let len = bytes[..MAX]
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or(ByteStructError::new(ErrorKind::InvalidValue, None, 0))?;
+        Ok(Self::new(&bytes[..len]).unwrap())
+    }
+}
+
+impl<const MAX: usize> ByteStructDebugJson for CStrField<MAX> {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        match self.as_str() {
+            Some(s) => write_json_escaped(s, out),
+            None => write_bytes_json(self.as_bytes(), out),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Annotated hex dump of packed structures.
+
+use crate::{ByteOrder, ByteStructLayout};
+use core::fmt;
+use core::marker::PhantomData;
+
+/// Formats packed bytes as a hex dump annotated with the field layout of `T`.
+///
+/// Each line shows the offset, the name and the byte order of a field, followed by its bytes.
+/// Bytes not covered by any field are shown as padding,
+/// and fields that extend past the end of the input are marked as truncated.
+///
+/// Available with the `dump` feature.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Header {
+///     magic: u32,
+///     #[byte_struct_be]
+///     size: u16,
+/// }
+///
+/// let bytes = [0x7f, 0x45, 0x4c, 0x46, 0x01, 0x02];
+/// assert_eq!(
+///     HexDump::<Header>::new(&bytes).to_string(),
+///     "0000  magic  le  7f 45 4c 46\n\
+///      0004  size   be  01 02\n"
+/// );
+/// ```
+pub struct HexDump<'a, T> {
+    bytes: &'a [u8],
+    phantom: PhantomData<T>,
+}
+
+impl<'a, T: ByteStructLayout> HexDump<'a, T> {
+    /// Creates a hex dump of packed bytes of `T`
+    pub fn new(bytes: &'a [u8]) -> Self {
+        HexDump {
+            bytes,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ByteStructLayout> fmt::Display for HexDump<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name_width = T::FIELDS
+            .iter()
+            .map(|field| field.name.len())
+            .chain(core::iter::once("(padding)".len()).filter(|_| has_padding::<T>()))
+            .max()
+            .unwrap_or(0);
+        let mut pos = 0;
+        for field in T::FIELDS {
+            if field.offset > pos {
+                self.line(f, pos, field.offset, "(padding)", "  ", name_width)?;
+            }
+            let order = match field.byte_order {
+                ByteOrder::Little => "le",
+                ByteOrder::Big => "be",
+                ByteOrder::Fixed => "  ",
+            };
+            let end = field.offset + field.len;
+            self.line(f, field.offset, end, field.name, order, name_width)?;
+            pos = pos.max(end);
+        }
+        if T::BYTE_LEN > pos {
+            self.line(f, pos, T::BYTE_LEN, "(padding)", "  ", name_width)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> HexDump<'_, T> {
+    fn line(
+        &self,
+        f: &mut fmt::Formatter,
+        start: usize,
+        end: usize,
+        name: &str,
+        order: &str,
+        name_width: usize,
+    ) -> fmt::Result {
+        write!(f, "{:04x}  {:<width$}  {} ", start, name, order, width = name_width)?;
+        let available = end.min(self.bytes.len());
+        for byte in self.bytes.get(start..available).unwrap_or(&[]) {
+            write!(f, " {:02x}", byte)?;
+        }
+        if available < end {
+            write!(f, " (truncated)")?;
+        }
+        writeln!(f)
+    }
+}
+
+fn has_padding<T: ByteStructLayout>() -> bool {
+    let mut pos = 0;
+    for field in T::FIELDS {
+        if field.offset > pos {
+            return true;
+        }
+        pos = pos.max(field.offset + field.len);
+    }
+    T::BYTE_LEN > pos
+}
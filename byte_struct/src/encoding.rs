@@ -0,0 +1,227 @@
+//! Fixed-size strings in legacy text encodings, decoded with `encoding_rs`.
+
+use crate::json::write_json_escaped;
+use crate::{ByteStructDebugJson, ByteStructLen, ByteStructRead, ByteStructWrite};
+use alloc::borrow::Cow;
+use core::fmt;
+use core::marker::PhantomData;
+
+/// A text encoding of [`EncodedString`](struct.EncodedString.html).
+///
+/// This is implemented by marker types for common encodings,
+/// and can be implemented for other encodings of `encoding_rs`
+/// with [`encoding`](#tymethod.encoding).
+pub trait TextEncoding {
+    /// The encoding to decode and encode with
+    fn encoding() -> &'static encoding_rs::Encoding;
+
+    /// Decodes `bytes`, or returns `None` if they are malformed
+    fn decode(bytes: &[u8]) -> Option<Cow<'_, str>> {
+        Self::encoding().decode_without_bom_handling_and_without_replacement(bytes)
+    }
+
+    /// Decodes `bytes`, with malformed sequences replaced by U+FFFD
+    fn decode_lossy(bytes: &[u8]) -> Cow<'_, str> {
+        Self::encoding().decode_without_bom_handling(bytes).0
+    }
+
+    /// Encodes `s`, or returns `None` if it has characters the encoding can't represent
+    fn encode(s: &str) -> Option<Cow<'_, [u8]>> {
+        let (bytes, _, unmappable) = Self::encoding().encode(s);
+        if unmappable {
+            return None;
+        }
+        Some(bytes)
+    }
+}
+
+/// Shift-JIS, as used by Japanese software, in the Windows-31J variant of the WHATWG standard
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct ShiftJis;
+
+/// EUC-JP
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct EucJp;
+
+/// Windows-1252, the superset of Latin-1 that the WHATWG standard decodes Latin-1 labels as
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Windows1252;
+
+/// ISO-8859-1, where each byte is the code point of the same value
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Latin1;
+
+impl TextEncoding for ShiftJis {
+    fn encoding() -> &'static encoding_rs::Encoding {
+        encoding_rs::SHIFT_JIS
+    }
+}
+
+impl TextEncoding for EucJp {
+    fn encoding() -> &'static encoding_rs::Encoding {
+        encoding_rs::EUC_JP
+    }
+}
+
+impl TextEncoding for Windows1252 {
+    fn encoding() -> &'static encoding_rs::Encoding {
+        encoding_rs::WINDOWS_1252
+    }
+}
+
+// encoding_rs has no Encoding for the actual ISO-8859-1, only conversion functions
+impl TextEncoding for Latin1 {
+    fn encoding() -> &'static encoding_rs::Encoding {
+        encoding_rs::WINDOWS_1252
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Cow<'_, str>> {
+        Some(encoding_rs::mem::decode_latin1(bytes))
+    }
+
+    fn decode_lossy(bytes: &[u8]) -> Cow<'_, str> {
+        encoding_rs::mem::decode_latin1(bytes)
+    }
+
+    fn encode(s: &str) -> Option<Cow<'_, [u8]>> {
+        if !encoding_rs::mem::is_str_latin1(s) {
+            return None;
+        }
+        Some(encoding_rs::mem::encode_latin1_lossy(s))
+    }
+}
+
+/// A string of `N` bytes in the text encoding `E`, padded with NUL.
+///
+/// The bytes are packed and unpacked as they are, so values round-trip losslessly
+/// even if they aren't valid in the encoding, and [`raw`](#method.raw) gives access to them.
+/// The string is the bytes before the trailing NUL padding,
+/// decoded with [`to_str`](#method.to_str) or [`to_str_lossy`](#method.to_str_lossy).
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct SaveSlot {
+///     name: EncodedString<ShiftJis, 8>,
+///     level: u8,
+/// }
+///
+/// let slot = SaveSlot::read_bytes(&b"\x83\x8a\x83\x93\x83\x4e\0\0\x05"[..]);
+/// assert_eq!(slot.name.to_str().unwrap(), "リンク");
+/// assert_eq!(slot.name, EncodedString::encode("リンク").unwrap());
+/// ```
+pub struct EncodedString<E, const N: usize> {
+    bytes: [u8; N],
+    encoding: PhantomData<E>,
+}
+
+impl<E, const N: usize> EncodedString<E, N> {
+    /// Creates a string from its raw bytes, including the NUL padding
+    pub const fn from_raw(bytes: [u8; N]) -> Self {
+        EncodedString {
+            bytes,
+            encoding: PhantomData,
+        }
+    }
+
+    /// Returns the raw bytes, including the NUL padding
+    pub const fn raw(&self) -> &[u8; N] {
+        &self.bytes
+    }
+
+    /// Returns the encoded bytes of the string, without the NUL padding
+    pub fn as_bytes(&self) -> &[u8] {
+        let len = self
+            .bytes
+            .iter()
+            .rposition(|b| *b != 0)
+            .map_or(0, |i| i + 1);
+        &self.bytes[..len]
+    }
+}
+
+impl<E: TextEncoding, const N: usize> EncodedString<E, N> {
+    /// Encodes `s`, or returns `None` if it has characters the encoding can't represent,
+    /// or if it is encoded into more than `N` bytes
+    pub fn encode(s: &str) -> Option<Self> {
+        let encoded = E::encode(s)?;
+        if encoded.len() > N {
+            return None;
+        }
+        let mut bytes = [0; N];
+        bytes[..encoded.len()].copy_from_slice(&encoded);
+        Some(Self::from_raw(bytes))
+    }
+
+    /// Decodes the string, or returns `None` if it is malformed in the encoding
+    pub fn to_str(&self) -> Option<Cow<'_, str>> {
+        E::decode(self.as_bytes())
+    }
+
+    /// Decodes the string, with malformed sequences replaced by U+FFFD
+    pub fn to_str_lossy(&self) -> Cow<'_, str> {
+        E::decode_lossy(self.as_bytes())
+    }
+}
+
+impl<E, const N: usize> Clone for EncodedString<E, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E, const N: usize> Copy for EncodedString<E, N> {}
+
+impl<E, const N: usize> PartialEq for EncodedString<E, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl<E, const N: usize> Eq for EncodedString<E, N> {}
+
+impl<E, const N: usize> core::hash::Hash for EncodedString<E, N> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.bytes.hash(state);
+    }
+}
+
+impl<E, const N: usize> Default for EncodedString<E, N> {
+    fn default() -> Self {
+        Self::from_raw([0; N])
+    }
+}
+
+impl<E: TextEncoding, const N: usize> fmt::Debug for EncodedString<E, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.to_str() {
+            Some(s) => write!(f, "EncodedString({:?})", s),
+            None => write!(f, "EncodedString({:?})", self.as_bytes()),
+        }
+    }
+}
+
+impl<E, const N: usize> ByteStructLen for EncodedString<E, N> {
+    const BYTE_LEN: usize = N;
+}
+
+impl<E, const N: usize> ByteStructWrite for EncodedString<E, N> {
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        bytes[..N].copy_from_slice(&self.bytes);
+    }
+}
+
+impl<E, const N: usize> ByteStructRead for EncodedString<E, N> {
+    fn read_bytes(bytes: &[u8]) -> Self {
+        Self::from_raw(bytes[..N].try_into().unwrap())
+    }
+}
+
+impl<E: TextEncoding, const N: usize> ByteStructDebugJson for EncodedString<E, N> {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        write_json_escaped(&self.to_str_lossy(), out)
+    }
+}
@@ -0,0 +1,139 @@
+//! Fields holding enums that are packed as integers.
+
+use crate::{ByteStructDebugJson, ByteStructError, ByteStructLen, ByteStructUnspecifiedByteOrder};
+use core::fmt;
+
+/// A field of enum type `E` packed as the integer type `W`.
+///
+/// `E` converts from `W` with `TryFrom` and back with `Into`, as implemented by
+/// `#[derive(TryFromPrimitive, IntoPrimitive)]` of the `num_enum` crate or by hand.
+/// Values that don't correspond to any variant are kept as [`Unknown`](#variant.Unknown)
+/// instead of failing, and are packed back as they are.
+/// To reject them instead, validate the field with
+/// [`is_known`](#method.is_known), e.g. `#[byte_struct_validate = "EnumField::is_known"]`.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(Clone, Copy, PartialEq, Debug)]
+/// enum Command {
+///     Reset,
+///     Start,
+/// }
+///
+/// impl TryFrom<u16> for Command {
+///     type Error = ();
+///     fn try_from(value: u16) -> Result<Self, ()> {
+///         match value {
+///             0 => Ok(Command::Reset),
+///             1 => Ok(Command::Start),
+///             _ => Err(()),
+///         }
+///     }
+/// }
+///
+/// impl From<Command> for u16 {
+///     fn from(command: Command) -> u16 {
+///         command as u16
+///     }
+/// }
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Request {
+///     command: EnumField<Command, u16>,
+///     #[byte_struct_validate = "EnumField::is_known"]
+///     next: EnumField<Command, u16>,
+/// }
+///
+/// let request = Request::read_bytes(&[0x01, 0x00, 0x00, 0x00][..]);
+/// assert_eq!(request.command, EnumField::Known(Command::Start));
+/// assert_eq!(Request::read_bytes(&[0x07, 0x00, 0x00, 0x00][..]).command, EnumField::Unknown(7));
+/// assert!(Request::try_read_bytes(&[0x00, 0x00, 0x07, 0x00][..]).is_err());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum EnumField<E, W> {
+    /// A value that corresponds to a variant of `E`
+    Known(E),
+    /// A raw value that doesn't correspond to any variant of `E`
+    Unknown(W),
+}
+
+impl<E: TryFrom<W> + Into<W> + Copy, W: Copy> EnumField<E, W> {
+    /// Converts a raw value, keeping it as `Unknown` if it doesn't correspond to any variant
+    pub fn from_raw(raw: W) -> Self {
+        match E::try_from(raw) {
+            Ok(value) => EnumField::Known(value),
+            Err(_) => EnumField::Unknown(raw),
+        }
+    }
+
+    /// Returns the raw value that is packed
+    pub fn raw(&self) -> W {
+        match *self {
+            EnumField::Known(value) => value.into(),
+            EnumField::Unknown(raw) => raw,
+        }
+    }
+
+    /// Returns the variant, or `None` if the value is unknown
+    pub fn known(&self) -> Option<E> {
+        match *self {
+            EnumField::Known(value) => Some(value),
+            EnumField::Unknown(_) => None,
+        }
+    }
+
+    /// Returns `true` if the value corresponds to a variant
+    pub fn is_known(&self) -> bool {
+        matches!(self, EnumField::Known(_))
+    }
+}
+
+impl<E, W> From<E> for EnumField<E, W> {
+    fn from(value: E) -> Self {
+        EnumField::Known(value)
+    }
+}
+
+impl<E, W: ByteStructLen> ByteStructLen for EnumField<E, W> {
+    const BYTE_LEN: usize = W::BYTE_LEN;
+}
+
+/// Packed in the same way as the raw value
+impl<E, W> ByteStructUnspecifiedByteOrder for EnumField<E, W>
+where
+    E: TryFrom<W> + Into<W> + Copy,
+    W: ByteStructUnspecifiedByteOrder + Copy,
+{
+    fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+        self.raw().write_bytes_default_le(bytes);
+    }
+    fn read_bytes_default_le(bytes: &[u8]) -> Self {
+        EnumField::from_raw(W::read_bytes_default_le(bytes))
+    }
+    fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+        self.raw().write_bytes_default_be(bytes);
+    }
+    fn read_bytes_default_be(bytes: &[u8]) -> Self {
+        EnumField::from_raw(W::read_bytes_default_be(bytes))
+    }
+    fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        W::try_read_bytes_default_le(bytes).map(EnumField::from_raw)
+    }
+    fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        W::try_read_bytes_default_be(bytes).map(EnumField::from_raw)
+    }
+}
+
+// Written as the raw value, as the enum itself has no JSON form
+impl<E, W> ByteStructDebugJson for EnumField<E, W>
+where
+    E: TryFrom<W> + Into<W> + Copy,
+    W: ByteStructDebugJson + Copy,
+{
+    fn write_debug_json<W2: fmt::Write + ?Sized>(&self, out: &mut W2) -> fmt::Result {
+        self.raw().write_debug_json(out)
+    }
+}
@@ -0,0 +1,204 @@
+//! Errors reported by the fallible unpacking functions.
+
+use core::fmt;
+
+/// The reason why unpacking failed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The input slice ended before the field
+    ShortBuffer,
+    /// The field didn't hold the constant value it is required to hold
+    BadMagic,
+    /// The field held a value that has no corresponding representation, such as an unknown tag
+    InvalidValue,
+    /// The field was rejected by its validation function
+    ValidationFailed,
+}
+
+impl ErrorKind {
+    // A short description used when displaying errors
+    pub(crate) const fn reason(self) -> &'static str {
+        match self {
+            ErrorKind::ShortBuffer => "input too short",
+            ErrorKind::BadMagic => "bad magic value",
+            ErrorKind::InvalidValue => "invalid value",
+            ErrorKind::ValidationFailed => "validation failed",
+        }
+    }
+}
+
+/// The maximum number of segments recorded by an [`ErrorPath`](struct.ErrorPath.html)
+pub const MAX_ERROR_PATH_DEPTH: usize = 5;
+
+/// A step in the path from the outermost structure to the value that failed to unpack.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PathSegment {
+    /// A named field of a structure
+    Field(&'static str),
+    /// An element of an array
+    Index(usize),
+}
+
+/// The path of fields and array indices leading to the value that failed to unpack,
+/// such as `s.s1.c[1]`.
+///
+/// The path is assembled from the innermost value outwards as the error is passed up
+/// through [`ByteStructError::within`](struct.ByteStructError.html#method.within)
+/// and [`ByteStructError::within_element`](struct.ByteStructError.html#method.within_element).
+/// It is stored without allocation, so only the innermost [`MAX_ERROR_PATH_DEPTH`] segments are kept.
+/// Outer segments beyond that are dropped and the path is marked as truncated.
+///
+/// [`MAX_ERROR_PATH_DEPTH`]: constant.MAX_ERROR_PATH_DEPTH.html
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ErrorPath {
+    // Innermost segment first. The depth is kept small so that the error stays cheap to return.
+    segments: [PathSegment; MAX_ERROR_PATH_DEPTH],
+    len: u8,
+    truncated: bool,
+}
+
+impl ErrorPath {
+    /// The path with no segments
+    pub const EMPTY: ErrorPath = ErrorPath {
+        segments: [PathSegment::Index(0); MAX_ERROR_PATH_DEPTH],
+        len: 0,
+        truncated: false,
+    };
+
+    /// Returns the path with `segment` added as the new outermost segment
+    pub const fn prepend(mut self, segment: PathSegment) -> Self {
+        if (self.len as usize) < MAX_ERROR_PATH_DEPTH {
+            self.segments[self.len as usize] = segment;
+            self.len += 1;
+        } else {
+            self.truncated = true;
+        }
+        self
+    }
+
+    /// Returns the number of recorded segments
+    pub const fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Returns `true` if no segment is recorded
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if outer segments were dropped because the path was too deep
+    pub const fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Iterates over the recorded segments from the outermost to the innermost
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = PathSegment> + '_ {
+        self.segments[..self.len()].iter().rev().copied()
+    }
+}
+
+impl Default for ErrorPath {
+    fn default() -> Self {
+        ErrorPath::EMPTY
+    }
+}
+
+impl fmt::Display for ErrorPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.truncated {
+            f.write_str("...")?;
+        }
+        for (i, segment) in self.iter().enumerate() {
+            match segment {
+                PathSegment::Field(name) if i == 0 => f.write_str(name)?,
+                PathSegment::Field(name) => write!(f, ".{}", name)?,
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An error produced by the fallible unpacking functions such as
+/// [`ByteStructRead::try_read_bytes`](trait.ByteStructRead.html#method.try_read_bytes).
+///
+/// The error records the innermost field that failed and its offset relative to the start of
+/// the outermost structure being unpacked, along with the path of fields and array indices
+/// leading to it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ByteStructError {
+    /// The reason of the failure
+    pub kind: ErrorKind,
+    /// The name of the field that failed, if the failure is attributed to a named field
+    pub field: Option<&'static str>,
+    /// The offset of the field that failed
+    pub offset: usize,
+    /// The path from the outermost structure to the value that failed
+    pub path: ErrorPath,
+}
+
+impl ByteStructError {
+    /// Creates an error for a field at the given offset.
+    ///
+    /// The path starts with `field` if it is given.
+    pub const fn new(kind: ErrorKind, field: Option<&'static str>, offset: usize) -> Self {
+        ByteStructError {
+            kind,
+            field,
+            offset,
+            path: match field {
+                Some(field) => ErrorPath::EMPTY.prepend(PathSegment::Field(field)),
+                None => ErrorPath::EMPTY,
+            },
+        }
+    }
+
+    /// Rebases an error reported by a nested value onto its parent.
+    ///
+    /// `offset` is the offset of the nested value inside the parent,
+    /// and `field` is the parent's field name for the nested value,
+    /// which is added to the path, and recorded as `field` only if the nested error
+    /// isn't attributed to a field yet.
+    pub const fn within(self, field: Option<&'static str>, offset: usize) -> Self {
+        ByteStructError {
+            kind: self.kind,
+            field: match self.field {
+                Some(field) => Some(field),
+                None => field,
+            },
+            offset: self.offset + offset,
+            path: match field {
+                Some(field) => self.path.prepend(PathSegment::Field(field)),
+                None => self.path,
+            },
+        }
+    }
+
+    /// Rebases an error reported by an array element onto the array.
+    ///
+    /// `index` is the index of the element, which is added to the path,
+    /// and `offset` is the offset of the element inside the array.
+    pub const fn within_element(self, index: usize, offset: usize) -> Self {
+        ByteStructError {
+            path: self.path.prepend(PathSegment::Index(index)),
+            ..self.within(None, offset)
+        }
+    }
+}
+
+impl fmt::Display for ByteStructError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = self.kind.reason();
+        if !self.path.is_empty() {
+            write!(f, "{} for field `{}` at offset {}", reason, self.path, self.offset)
+        } else {
+            match self.field {
+                Some(field) => write!(f, "{} for field `{}` at offset {}", reason, field, self.offset),
+                None => write!(f, "{} at offset {}", reason, self.offset),
+            }
+        }
+    }
+}
+
+impl core::error::Error for ByteStructError {}
@@ -0,0 +1,78 @@
+//! Packing of flag types defined with the `bitflags` crate, available with the `bitflags` feature.
+
+/// Implements [`ByteStructUnspecifiedByteOrder`] for types defined with `bitflags::bitflags!`.
+///
+/// The flags are packed as their underlying integer, following the byte order attributes
+/// of the containing structure. All bits are kept when unpacking, including bits that don't
+/// correspond to any defined flag, so that packing them back reproduces the input.
+/// [`ByteStructDebugJson`] is also implemented, writing the bits as a number.
+///
+/// Available with the `bitflags` feature.
+///
+/// [`ByteStructUnspecifiedByteOrder`]: trait.ByteStructUnspecifiedByteOrder.html
+/// [`ByteStructDebugJson`]: trait.ByteStructDebugJson.html
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// bitflags::bitflags! {
+///     #[derive(Clone, Copy, PartialEq, Debug)]
+///     struct Permissions: u16 {
+///         const READ = 1 << 0;
+///         const WRITE = 1 << 1;
+///     }
+/// }
+///
+/// bitflags_byte_struct!(Permissions);
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_be]
+/// struct Entry {
+///     id: u16,
+///     permissions: Permissions,
+/// }
+///
+/// let entry = Entry::read_bytes(&[0x00, 0x01, 0x00, 0x03][..]);
+/// assert_eq!(entry.permissions, Permissions::READ | Permissions::WRITE);
+/// ```
+#[macro_export]
+macro_rules! bitflags_byte_struct {
+    ($($t:ty),+ $(,)?) => {$(
+        impl $crate::ByteStructLen for $t {
+            const BYTE_LEN: usize =
+                <<$t as $crate::__private::Flags>::Bits as $crate::ByteStructLen>::BYTE_LEN;
+        }
+
+        impl $crate::ByteStructUnspecifiedByteOrder for $t {
+            fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+                $crate::ByteStructUnspecifiedByteOrder::write_bytes_default_le(
+                    &$crate::__private::Flags::bits(self),
+                    bytes,
+                );
+            }
+            fn read_bytes_default_le(bytes: &[u8]) -> Self {
+                $crate::__private::Flags::from_bits_retain(
+                    $crate::ByteStructUnspecifiedByteOrder::read_bytes_default_le(bytes),
+                )
+            }
+            fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+                $crate::ByteStructUnspecifiedByteOrder::write_bytes_default_be(
+                    &$crate::__private::Flags::bits(self),
+                    bytes,
+                );
+            }
+            fn read_bytes_default_be(bytes: &[u8]) -> Self {
+                $crate::__private::Flags::from_bits_retain(
+                    $crate::ByteStructUnspecifiedByteOrder::read_bytes_default_be(bytes),
+                )
+            }
+        }
+
+        impl $crate::ByteStructDebugJson for $t {
+            fn write_debug_json<W: core::fmt::Write + ?Sized>(&self, out: &mut W) -> core::fmt::Result {
+                $crate::ByteStructDebugJson::write_debug_json(&$crate::__private::Flags::bits(self), out)
+            }
+        }
+    )+};
+}
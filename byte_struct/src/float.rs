@@ -0,0 +1,138 @@
+//! Floating point values with a byte order fixed by their type,
+//! and 16-bit floating point types with the `half` feature.
+
+use crate::{
+    ByteStructDebugJson, ByteStructLen, ByteStructRead, ByteStructUnspecifiedByteOrder, ByteStructWrite,
+};
+use core::fmt;
+
+macro_rules! ordered_float_impl {
+    ($(#[$doc:meta])* $name:ident($t:ty), $write:ident, $read:ident) => {
+        $(#[$doc])*
+        ///
+        /// The bits of the value are packed exactly, including the sign of zero and the payload of NaN.
+        #[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Default)]
+        pub struct $name(pub $t);
+
+        impl From<$t> for $name {
+            fn from(value: $t) -> Self {
+                $name(value)
+            }
+        }
+
+        impl From<$name> for $t {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl ByteStructLen for $name {
+            const BYTE_LEN: usize = <$t>::BYTE_LEN;
+        }
+
+        impl ByteStructWrite for $name {
+            fn write_bytes(&self, bytes: &mut [u8]) {
+                self.0.$write(bytes);
+            }
+        }
+
+        impl ByteStructRead for $name {
+            fn read_bytes(bytes: &[u8]) -> Self {
+                $name(<$t>::$read(bytes))
+            }
+        }
+
+        impl ByteStructDebugJson for $name {
+            fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+                self.0.write_debug_json(out)
+            }
+        }
+    };
+}
+
+ordered_float_impl!(
+    /// An `f32` always packed as little-endian, regardless of the byte order attributes
+    /// of the containing structure.
+    F32Le(f32),
+    write_bytes_default_le,
+    read_bytes_default_le
+);
+
+ordered_float_impl!(
+    /// An `f32` always packed as big-endian, regardless of the byte order attributes
+    /// of the containing structure.
+    F32Be(f32),
+    write_bytes_default_be,
+    read_bytes_default_be
+);
+
+ordered_float_impl!(
+    /// An `f64` always packed as little-endian, regardless of the byte order attributes
+    /// of the containing structure.
+    F64Le(f64),
+    write_bytes_default_le,
+    read_bytes_default_le
+);
+
+ordered_float_impl!(
+    /// An `f64` always packed as big-endian, regardless of the byte order attributes
+    /// of the containing structure.
+    ///
+    /// # Example
+    /// ```
+    /// use byte_struct::*;
+    ///
+    /// #[derive(ByteStruct)]
+    /// struct Sample {
+    ///     value: F64Be,
+    /// }
+    ///
+    /// let sample = Sample::read_bytes(&[0x40, 0x09, 0x21, 0xfb, 0x54, 0x44, 0x2d, 0x18][..]);
+    /// assert_eq!(sample.value.0, core::f64::consts::PI);
+    /// ```
+    F64Be(f64),
+    write_bytes_default_be,
+    read_bytes_default_be
+);
+
+macro_rules! half_float_impl {
+    ($($t:ty),*) => {$(
+        #[cfg(feature = "half")]
+        impl ByteStructLen for $t {
+            const BYTE_LEN: usize = 2;
+        }
+
+        /// Packed as the 16 bits of the value
+        #[cfg(feature = "half")]
+        impl ByteStructUnspecifiedByteOrder for $t {
+            fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+                self.to_bits().write_bytes_default_le(bytes);
+            }
+            fn read_bytes_default_le(bytes: &[u8]) -> Self {
+                <$t>::from_bits(u16::read_bytes_default_le(bytes))
+            }
+            fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+                self.to_bits().write_bytes_default_be(bytes);
+            }
+            fn read_bytes_default_be(bytes: &[u8]) -> Self {
+                <$t>::from_bits(u16::read_bytes_default_be(bytes))
+            }
+            fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, crate::ByteStructError> {
+                u16::try_read_bytes_default_le(bytes).map(<$t>::from_bits)
+            }
+            fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, crate::ByteStructError> {
+                u16::try_read_bytes_default_be(bytes).map(<$t>::from_bits)
+            }
+        }
+
+        // Every value is exactly representable as f32
+        #[cfg(feature = "half")]
+        impl ByteStructDebugJson for $t {
+            fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+                self.to_f32().write_debug_json(out)
+            }
+        }
+    )*}
+}
+
+half_float_impl!(half::f16, half::bf16);
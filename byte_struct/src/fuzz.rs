@@ -0,0 +1,35 @@
+//! Generation of structures from fuzzer input with `arbitrary`.
+
+use crate::ByteStructRead;
+use arbitrary::{Error, Result, Unstructured};
+
+/// Generates a structure by unpacking `T::BYTE_LEN` bytes taken from the fuzzer input.
+///
+/// Every generated value is one that unpacking can actually produce, so harnesses exercise
+/// code that handles unpacked structures with structurally valid values.
+/// Bytes that fail to unpack, such as those rejected by validation, are reported as
+/// `Error::IncorrectFormat`, which makes the fuzzer discard the input.
+///
+/// This is what `Arbitrary` derived with `#[byte_struct(arbitrary)]` does,
+/// and can be used to implement `Arbitrary` by hand for other types.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Header {
+///     kind: u8,
+///     size: u16,
+/// }
+///
+/// let mut u = arbitrary::Unstructured::new(&[1, 2, 0, 0xFF]);
+/// let header: Header = arbitrary_read(&mut u).unwrap();
+/// assert_eq!(header, Header { kind: 1, size: 2 });
+/// assert!(arbitrary_read::<Header>(&mut u).is_err());
+/// ```
+pub fn arbitrary_read<T: ByteStructRead>(u: &mut Unstructured) -> Result<T> {
+    let bytes = u.bytes(T::BYTE_LEN)?;
+    T::try_read_bytes(bytes).map_err(|_| Error::IncorrectFormat)
+}
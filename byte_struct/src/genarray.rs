@@ -0,0 +1,83 @@
+//! Packing of `generic_array::GenericArray`, in the same way as arrays.
+
+// generic-array 0.14 marks itself deprecated in favour of 1.x,
+// but 0.14 is what most of the ecosystem still depends on
+#![allow(deprecated)]
+
+use crate::{
+    check_len, ByteStructDebugJson, ByteStructError, ByteStructLen, ByteStructUnspecifiedByteOrder,
+};
+use core::fmt::{Result as FmtResult, Write};
+use generic_array::sequence::GenericSequence;
+use generic_array::{ArrayLength, GenericArray};
+
+// Unpacks the elements one by one, stopping at the first element that fails
+fn try_generate<T, N: ArrayLength<T>>(
+    mut f: impl FnMut(usize) -> Result<T, ByteStructError>,
+) -> Result<GenericArray<T, N>, ByteStructError> {
+    let mut error = None;
+    let elements = (0..N::USIZE).map_while(|i| f(i).map_err(|e| error = Some(e)).ok());
+    let array = GenericArray::from_exact_iter(elements);
+    match error {
+        Some(error) => Err(error),
+        None => Ok(array.unwrap()),
+    }
+}
+
+impl<T: ByteStructLen, N: ArrayLength<T>> ByteStructLen for GenericArray<T, N> {
+    const BYTE_LEN: usize = crate::__private::len_mul(N::USIZE, T::BYTE_LEN);
+}
+
+impl<T: ByteStructUnspecifiedByteOrder, N: ArrayLength<T>> ByteStructUnspecifiedByteOrder
+    for GenericArray<T, N>
+{
+    fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+        let len = T::BYTE_LEN;
+        for (i, element) in self.iter().enumerate() {
+            element.write_bytes_default_le(&mut bytes[i * len..(i + 1) * len]);
+        }
+    }
+    fn read_bytes_default_le(bytes: &[u8]) -> Self {
+        let len = T::BYTE_LEN;
+        GenericArray::generate(|i| T::read_bytes_default_le(&bytes[i * len..(i + 1) * len]))
+    }
+    fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+        let len = T::BYTE_LEN;
+        for (i, element) in self.iter().enumerate() {
+            element.write_bytes_default_be(&mut bytes[i * len..(i + 1) * len]);
+        }
+    }
+    fn read_bytes_default_be(bytes: &[u8]) -> Self {
+        let len = T::BYTE_LEN;
+        GenericArray::generate(|i| T::read_bytes_default_be(&bytes[i * len..(i + 1) * len]))
+    }
+    fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        let len = T::BYTE_LEN;
+        try_generate(|i| {
+            T::try_read_bytes_default_le(&bytes[i * len..(i + 1) * len])
+                .map_err(|e| e.within_element(i, i * len))
+        })
+    }
+    fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        let len = T::BYTE_LEN;
+        try_generate(|i| {
+            T::try_read_bytes_default_be(&bytes[i * len..(i + 1) * len])
+                .map_err(|e| e.within_element(i, i * len))
+        })
+    }
+}
+
+impl<T: ByteStructDebugJson, N: ArrayLength<T>> ByteStructDebugJson for GenericArray<T, N> {
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> FmtResult {
+        out.write_char('[')?;
+        for (i, element) in self.iter().enumerate() {
+            if i != 0 {
+                out.write_char(',')?;
+            }
+            element.write_debug_json(out)?;
+        }
+        out.write_char(']')
+    }
+}
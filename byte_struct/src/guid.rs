@@ -0,0 +1,102 @@
+//! Packing of UUIDs, available with the `uuid` feature.
+//!
+//! `Uuid` itself is packed in the RFC 4122 layout, where all 16 bytes are in big-endian order,
+//! regardless of the byte order attributes of the containing structure.
+//! [`Guid`](struct.Guid.html) selects the mixed-endian layout instead.
+
+use crate::json::write_json_string;
+use crate::{ByteStructDebugJson, ByteStructLen, ByteStructRead, ByteStructWrite};
+use core::fmt;
+use uuid::Uuid;
+
+impl ByteStructLen for Uuid {
+    const BYTE_LEN: usize = 16;
+}
+
+impl ByteStructWrite for Uuid {
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        bytes[..16].copy_from_slice(self.as_bytes());
+    }
+}
+
+impl ByteStructRead for Uuid {
+    fn read_bytes(bytes: &[u8]) -> Self {
+        Uuid::from_bytes(bytes[..16].try_into().unwrap())
+    }
+}
+
+/// A UUID packed in the mixed-endian layout used by Microsoft GUIDs,
+/// such as the type and partition GUIDs in GPT partition tables and the identifiers in Windows structures.
+///
+/// The first three groups of the UUID are packed as little-endian, and the last 8 bytes as they are.
+/// The packed layout is the only difference from `Uuid`; the value and its text form are the same.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+/// use uuid::Uuid;
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct GptPartitionEntry {
+///     type_guid: Guid,
+///     partition_guid: Guid,
+///     first_lba: u64,
+///     last_lba: u64,
+///     attributes: u64,
+///     name: [u16; 36],
+/// }
+///
+/// let efi_system = Guid(Uuid::from_u128(0xC12A7328_F81F_11D2_BA4B_00A0C93EC93B));
+/// let mut bytes = [0; 16];
+/// efi_system.write_bytes(&mut bytes[..]);
+/// assert_eq!(&bytes[..8], [0x28, 0x73, 0x2A, 0xC1, 0x1F, 0xF8, 0xD2, 0x11]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct Guid(pub Uuid);
+
+impl From<Uuid> for Guid {
+    fn from(uuid: Uuid) -> Self {
+        Guid(uuid)
+    }
+}
+
+impl From<Guid> for Uuid {
+    fn from(guid: Guid) -> Self {
+        guid.0
+    }
+}
+
+impl fmt::Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl ByteStructLen for Guid {
+    const BYTE_LEN: usize = 16;
+}
+
+impl ByteStructWrite for Guid {
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        bytes[..16].copy_from_slice(&self.0.to_bytes_le());
+    }
+}
+
+impl ByteStructRead for Guid {
+    fn read_bytes(bytes: &[u8]) -> Self {
+        Guid(Uuid::from_bytes_le(bytes[..16].try_into().unwrap()))
+    }
+}
+
+impl ByteStructDebugJson for Uuid {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        write_json_string(self, out)
+    }
+}
+
+impl ByteStructDebugJson for Guid {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        write_json_string(self, out)
+    }
+}
@@ -0,0 +1,171 @@
+//! Machine-readable rendering of unpacked values without allocation.
+
+use crate::{Computed, ReadOnly, WriteOneToClear, WriteOnly};
+use core::fmt::{Result, Write};
+
+/// A value that can be rendered as compact JSON into any [`core::fmt::Write`].
+///
+/// Integers and booleans are written as JSON numbers and booleans, floating point values are
+/// written as numbers, or `null` if they are not finite, arrays are written as JSON arrays,
+/// and structures are written as objects keyed by field name, without any whitespace.
+/// Members of bit field structures declared write-only and zero-length values such as `()`
+/// are written as `null`.
+///
+/// This trait can be derived by
+/// [`#[derive(ByteStructDebugJson)]`](https://docs.rs/byte_struct_derive/*/byte_struct_derive/derive.ByteStructDebugJson.html),
+/// and is implemented by structures generated by `bitfields!`.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, ByteStructDebugJson)]
+/// #[byte_struct_le]
+/// struct Frame {
+///     id: u16,
+///     values: [i8; 2],
+/// }
+///
+/// let mut out = String::new();
+/// Frame { id: 7, values: [1, -1] }.write_debug_json(&mut out).unwrap();
+/// assert_eq!(out, r#"{"id":7,"values":[1,-1]}"#);
+/// ```
+pub trait ByteStructDebugJson {
+    /// Writes the value as JSON to `out`
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> Result;
+}
+
+// Values with a usual textual notation, such as addresses, are written as JSON strings
+pub(crate) fn write_json_string<T: core::fmt::Display, W: Write + ?Sized>(
+    value: &T,
+    out: &mut W,
+) -> Result {
+    write!(out, "\"{}\"", value)
+}
+
+// Arbitrary text is escaped so that the output stays valid JSON
+pub(crate) fn write_json_escaped<W: Write + ?Sized>(value: &str, out: &mut W) -> Result {
+    out.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+// Writes bytes that aren't valid UTF-8 as an array of numbers
+pub(crate) fn write_bytes_json<W: Write + ?Sized>(bytes: &[u8], out: &mut W) -> Result {
+    out.write_char('[')?;
+    for (i, byte) in bytes.iter().enumerate() {
+        if i != 0 {
+            out.write_char(',')?;
+        }
+        write!(out, "{}", byte)?;
+    }
+    out.write_char(']')
+}
+
+macro_rules! debug_json_display_impl {
+    ($($t:ty),*) => {$(
+        impl ByteStructDebugJson for $t {
+            fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> Result {
+                write!(out, "{}", self)
+            }
+        }
+    )*}
+}
+
+debug_json_display_impl!(bool, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+macro_rules! debug_json_float_impl {
+    ($($t:ty),*) => {$(
+        impl ByteStructDebugJson for $t {
+            fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> Result {
+                if self.is_finite() {
+                    write!(out, "{}", self)
+                } else {
+                    out.write_str("null")
+                }
+            }
+        }
+    )*}
+}
+
+debug_json_float_impl!(f32, f64);
+
+// Zero-length fields carry no value
+impl ByteStructDebugJson for () {
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> Result {
+        out.write_str("null")
+    }
+}
+
+impl<T: ?Sized> ByteStructDebugJson for core::marker::PhantomData<T> {
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> Result {
+        out.write_str("null")
+    }
+}
+
+// Absent optional fields are written as null
+impl<T: ByteStructDebugJson> ByteStructDebugJson for Option<T> {
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> Result {
+        match self {
+            Some(value) => value.write_debug_json(out),
+            None => out.write_str("null"),
+        }
+    }
+}
+
+impl<T: ByteStructDebugJson> ByteStructDebugJson for core::num::Wrapping<T> {
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> Result {
+        self.0.write_debug_json(out)
+    }
+}
+
+impl<T: ByteStructDebugJson> ByteStructDebugJson for core::num::Saturating<T> {
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> Result {
+        self.0.write_debug_json(out)
+    }
+}
+
+impl<T: ByteStructDebugJson, const N: usize> ByteStructDebugJson for [T; N] {
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> Result {
+        out.write_char('[')?;
+        for (i, element) in self.iter().enumerate() {
+            if i != 0 {
+                out.write_char(',')?;
+            }
+            element.write_debug_json(out)?;
+        }
+        out.write_char(']')
+    }
+}
+
+impl<B: ByteStructDebugJson + Copy> ByteStructDebugJson for ReadOnly<B> {
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> Result {
+        self.get().write_debug_json(out)
+    }
+}
+
+// Write-only members can't be read back
+impl<B> ByteStructDebugJson for WriteOnly<B> {
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> Result {
+        out.write_str("null")
+    }
+}
+
+impl<B: ByteStructDebugJson + Copy + Default> ByteStructDebugJson for WriteOneToClear<B> {
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> Result {
+        self.get().write_debug_json(out)
+    }
+}
+
+impl<B: ByteStructDebugJson + Copy> ByteStructDebugJson for Computed<B> {
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> Result {
+        self.get().write_debug_json(out)
+    }
+}
@@ -0,0 +1,473 @@
+//! Runtime layout metadata and dynamic access to packed records.
+
+use crate::{
+    ByteStructError, ByteStructLen, ByteStructRead, ByteStructUnspecifiedByteOrder, ByteStructWrite,
+};
+use core::fmt;
+use core::marker::PhantomData;
+
+/// The byte order a packed field is encoded with.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ByteOrder {
+    /// Packed as little-endian
+    Little,
+    /// Packed as big-endian
+    Big,
+    /// Packed by the field type's own [`ByteStruct`](trait.ByteStruct.html) implementation,
+    /// which doesn't depend on any externally specified byte order
+    Fixed,
+}
+
+/// Describes where a field is located in a packed record and how it is packed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FieldInfo<'a> {
+    /// The name of the field
+    pub name: &'a str,
+    /// The offset of the first byte of the field, relative to the start of the record
+    pub offset: usize,
+    /// The length of the packed bytes of the field
+    pub len: usize,
+    /// The byte order used to pack the field
+    pub byte_order: ByteOrder,
+}
+
+/// A type whose packed layout is described field by field.
+///
+/// This trait is implemented by
+/// [`#[derive(ByteStruct)]`](https://docs.rs/byte_struct_derive/*/byte_struct_derive/derive.ByteStruct.html).
+///
+/// The byte order recorded for each field is the one resolved from the byte order attributes.
+/// Fields without any byte order attribute are recorded as [`ByteOrder::Fixed`].
+///
+/// [`ByteOrder::Fixed`]: enum.ByteOrder.html#variant.Fixed
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Header {
+///     magic: u32,
+///     #[byte_struct_be]
+///     size: u16,
+/// }
+///
+/// assert_eq!(Header::FIELDS[1], FieldInfo {
+///     name: "size",
+///     offset: 4,
+///     len: 2,
+///     byte_order: ByteOrder::Big,
+/// });
+/// ```
+pub trait ByteStructLayout: ByteStructLen {
+    /// The layout of each field, in declaration order
+    const FIELDS: &'static [FieldInfo<'static>];
+}
+
+/// Checks whether two types have identical packed layouts.
+///
+/// The layouts are identical if both types have the same `BYTE_LEN`
+/// and their fields have the same offsets, lengths and byte orders. Field names are not compared.
+///
+/// Fields packed with [`ByteOrder::Fixed`] are compared by offset and length only,
+/// as the layout of the field types themselves is not recorded.
+/// Use this function on the nested types as well if they are also expected to be compatible.
+///
+/// [`ByteOrder::Fixed`]: enum.ByteOrder.html#variant.Fixed
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct HeaderV1 {
+///     size: u32,
+///     reserved: u32,
+/// }
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct HeaderV2 {
+///     size: u32,
+///     flags: u32,
+/// }
+///
+/// const _: () = assert!(layout_compatible::<HeaderV1, HeaderV2>());
+/// ```
+pub const fn layout_compatible<A: ByteStructLayout, B: ByteStructLayout>() -> bool {
+    if A::BYTE_LEN != B::BYTE_LEN || A::FIELDS.len() != B::FIELDS.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < A::FIELDS.len() {
+        let (a, b) = (&A::FIELDS[i], &B::FIELDS[i]);
+        if a.offset != b.offset || a.len != b.len || !byte_order_eq(a.byte_order, b.byte_order) {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn byte_order_eq(a: ByteOrder, b: ByteOrder) -> bool {
+    matches!(
+        (a, b),
+        (ByteOrder::Little, ByteOrder::Little)
+            | (ByteOrder::Big, ByteOrder::Big)
+            | (ByteOrder::Fixed, ByteOrder::Fixed)
+    )
+}
+
+/// Fails the build if two types don't have identical packed layouts.
+///
+/// See [`layout_compatible`](fn.layout_compatible.html) for what is compared.
+///
+/// # Example
+/// ```compile_fail
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct A {
+///     x: u32,
+/// }
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_be]
+/// struct B {
+///     x: u32,
+/// }
+///
+/// assert_layout_compatible!(A, B);
+/// ```
+#[macro_export]
+macro_rules! assert_layout_compatible {
+    ($a:ty, $b:ty $(,)?) => {
+        const _: () = assert!(
+            $crate::layout_compatible::<$a, $b>(),
+            concat!(
+                "Layouts of ",
+                stringify!($a),
+                " and ",
+                stringify!($b),
+                " are not compatible"
+            )
+        );
+    };
+}
+
+/// The error returned when a record layout given to [`RecordSet`](struct.RecordSet.html) is invalid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RecordLayoutError {
+    /// The record stride is zero
+    ZeroStride,
+    /// The field at this index in the layout extends past the record stride
+    FieldPastStride(usize),
+}
+
+impl fmt::Display for RecordLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordLayoutError::ZeroStride => f.write_str("record stride is zero"),
+            RecordLayoutError::FieldPastStride(index) => {
+                write!(f, "field {} extends past the record stride", index)
+            }
+        }
+    }
+}
+
+impl core::error::Error for RecordLayoutError {}
+
+/// A view over a table of packed records, whose layout is only known at runtime.
+///
+/// Each record occupies `stride` bytes, and the fields in it are located by a slice of [`FieldInfo`].
+/// Fields are addressed by row index and column name.
+///
+/// Fields recorded as [`ByteOrder::Fixed`] have no byte order for [`get`](#method.get)
+/// and [`set`](#method.set) to apply, so they are packed as little-endian.
+/// This is only meaningful for types whose packing doesn't depend on the byte order,
+/// such as byte arrays. Other fixed fields, such as nested structures,
+/// can be accessed as bytes with [`get_raw`](#method.get_raw) and [`set_raw`](#method.set_raw).
+///
+/// [`FieldInfo`]: struct.FieldInfo.html
+/// [`ByteOrder::Fixed`]: enum.ByteOrder.html#variant.Fixed
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// let layout = [
+///     FieldInfo { name: "id", offset: 0, len: 2, byte_order: ByteOrder::Big },
+///     FieldInfo { name: "value", offset: 2, len: 4, byte_order: ByteOrder::Little },
+/// ];
+/// let mut bytes = [0; 12];
+/// let mut table = RecordSet::new(&layout[..], 6, &mut bytes[..]).unwrap();
+/// assert_eq!(table.len(), 2);
+/// table.set(1, "value", &0x12345678u32).unwrap();
+/// assert_eq!(table.get::<u32>(1, "value"), Some(0x12345678));
+/// assert_eq!(table.get_raw(1, "value"), Some(&[0x78, 0x56, 0x34, 0x12][..]));
+/// ```
+pub struct RecordSet<'a, B> {
+    fields: &'a [FieldInfo<'a>],
+    stride: usize,
+    bytes: B,
+}
+
+impl<'a, B> RecordSet<'a, B> {
+    /// Creates a view over `bytes` where each record occupies `stride` bytes.
+    ///
+    /// Returns an error if `stride` is zero or if any field extends past `stride`,
+    /// as layouts given at runtime may come from untrusted configuration.
+    pub fn new(
+        fields: &'a [FieldInfo<'a>],
+        stride: usize,
+        bytes: B,
+    ) -> Result<Self, RecordLayoutError> {
+        if stride == 0 {
+            return Err(RecordLayoutError::ZeroStride);
+        }
+        for (index, field) in fields.iter().enumerate() {
+            match field.offset.checked_add(field.len) {
+                Some(end) if end <= stride => (),
+                _ => return Err(RecordLayoutError::FieldPastStride(index)),
+            }
+        }
+        Ok(RecordSet {
+            fields,
+            stride,
+            bytes,
+        })
+    }
+
+    /// Returns the layout metadata of the column with the given name
+    pub fn field(&self, name: &str) -> Option<&'a FieldInfo<'a>> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// Returns the layout metadata of all columns
+    pub fn fields(&self) -> &'a [FieldInfo<'a>] {
+        self.fields
+    }
+
+    /// Consumes the view and returns the underlying buffer
+    pub fn into_inner(self) -> B {
+        self.bytes
+    }
+
+    fn locate(&self, row: usize, name: &str, len: usize) -> Option<(usize, ByteOrder)> {
+        let field = self.field(name)?;
+        if field.len != len {
+            return None;
+        }
+        Some((row * self.stride + field.offset, field.byte_order))
+    }
+}
+
+impl<'a, B: AsRef<[u8]>> RecordSet<'a, B> {
+    /// Returns the number of complete records in the buffer
+    pub fn len(&self) -> usize {
+        self.bytes.as_ref().len() / self.stride
+    }
+
+    /// Returns `true` if the buffer contains no complete record
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the packed bytes of a field.
+    ///
+    /// Returns `None` if the row or the column doesn't exist.
+    pub fn get_raw(&self, row: usize, name: &str) -> Option<&[u8]> {
+        let field = self.field(name)?;
+        if row >= self.len() {
+            return None;
+        }
+        let start = row * self.stride + field.offset;
+        Some(&self.bytes.as_ref()[start..start + field.len])
+    }
+
+    /// Unpacks a field as type `T` using the byte order recorded in its layout,
+    /// or as little-endian if it is [`ByteOrder::Fixed`](enum.ByteOrder.html#variant.Fixed).
+    ///
+    /// Returns `None` if the row or the column doesn't exist,
+    /// or if the packed length of `T` doesn't match the length of the column.
+    pub fn get<T: ByteStructUnspecifiedByteOrder>(&self, row: usize, name: &str) -> Option<T> {
+        if row >= self.len() {
+            return None;
+        }
+        let (start, byte_order) = self.locate(row, name, T::BYTE_LEN)?;
+        let bytes = &self.bytes.as_ref()[start..start + T::BYTE_LEN];
+        Some(match byte_order {
+            ByteOrder::Big => T::read_bytes_default_be(bytes),
+            ByteOrder::Little | ByteOrder::Fixed => T::read_bytes_default_le(bytes),
+        })
+    }
+}
+
+impl<'a, B: AsRef<[u8]> + AsMut<[u8]>> RecordSet<'a, B> {
+    /// Overwrites the packed bytes of a field.
+    ///
+    /// Returns `None` if the row or the column doesn't exist,
+    /// or if the length of `value` doesn't match the length of the column.
+    pub fn set_raw(&mut self, row: usize, name: &str, value: &[u8]) -> Option<()> {
+        if row >= self.len() {
+            return None;
+        }
+        let (start, _) = self.locate(row, name, value.len())?;
+        self.bytes.as_mut()[start..start + value.len()].copy_from_slice(value);
+        Some(())
+    }
+
+    /// Packs `value` into a field using the byte order recorded in its layout,
+    /// or as little-endian if it is [`ByteOrder::Fixed`](enum.ByteOrder.html#variant.Fixed).
+    ///
+    /// Returns `None` if the row or the column doesn't exist,
+    /// or if the packed length of `T` doesn't match the length of the column.
+    pub fn set<T: ByteStructUnspecifiedByteOrder>(
+        &mut self,
+        row: usize,
+        name: &str,
+        value: &T,
+    ) -> Option<()> {
+        if row >= self.len() {
+            return None;
+        }
+        let (start, byte_order) = self.locate(row, name, T::BYTE_LEN)?;
+        let bytes = &mut self.bytes.as_mut()[start..start + T::BYTE_LEN];
+        match byte_order {
+            ByteOrder::Big => value.write_bytes_default_be(bytes),
+            ByteOrder::Little | ByteOrder::Fixed => value.write_bytes_default_le(bytes),
+        }
+        Some(())
+    }
+}
+
+/// A view over a table of packed records of type `T`, each occupying `stride` bytes.
+///
+/// The bytes between the end of a record and the start of the next one are padding,
+/// which is skipped when unpacking and left untouched when packing.
+/// The last record doesn't need to be followed by its padding.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Vertex {
+///     x: i16,
+///     y: i16,
+///     z: i16,
+/// }
+///
+/// // Vertices padded to 8 bytes, without padding after the last one
+/// let mut bytes = [0xFF; 14];
+/// let mut table = StridedRecords::<Vertex, _>::new(8, &mut bytes[..]);
+/// assert_eq!(table.len(), 2);
+/// table.set(1, &Vertex { x: 1, y: 2, z: 3 }).unwrap();
+/// assert_eq!(table.get(1), Some(Vertex { x: 1, y: 2, z: 3 }));
+/// assert_eq!(&bytes[6..], &[0xFF, 0xFF, 1, 0, 2, 0, 3, 0]);
+/// ```
+pub struct StridedRecords<T, B> {
+    stride: usize,
+    bytes: B,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: ByteStructLen, B> StridedRecords<T, B> {
+    /// Creates a view over `bytes` where each record occupies `stride` bytes.
+    ///
+    /// Panics if `stride` is zero or shorter than `T::BYTE_LEN`.
+    pub fn new(stride: usize, bytes: B) -> Self {
+        assert!(stride != 0, "Record stride must not be zero");
+        assert!(
+            stride >= T::BYTE_LEN,
+            "Record stride is shorter than the record"
+        );
+        StridedRecords {
+            stride,
+            bytes,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the number of bytes each record occupies
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Consumes the view and returns the underlying buffer
+    pub fn into_inner(self) -> B {
+        self.bytes
+    }
+}
+
+impl<T: ByteStructLen, B: AsRef<[u8]>> StridedRecords<T, B> {
+    /// Returns the number of complete records in the buffer
+    pub fn len(&self) -> usize {
+        match self.bytes.as_ref().len().checked_sub(T::BYTE_LEN) {
+            Some(rest) => rest / self.stride + 1,
+            None => 0,
+        }
+    }
+
+    /// Returns `true` if the buffer contains no complete record
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the packed bytes of a record, excluding its padding.
+    ///
+    /// Returns `None` if the row doesn't exist.
+    pub fn get_raw(&self, row: usize) -> Option<&[u8]> {
+        if row >= self.len() {
+            return None;
+        }
+        let start = row * self.stride;
+        Some(&self.bytes.as_ref()[start..start + T::BYTE_LEN])
+    }
+}
+
+impl<T: ByteStructRead, B: AsRef<[u8]>> StridedRecords<T, B> {
+    /// Unpacks a record.
+    ///
+    /// Returns `None` if the row doesn't exist.
+    pub fn get(&self, row: usize) -> Option<T> {
+        self.get_raw(row).map(T::read_bytes)
+    }
+
+    /// Unpacks a record, reporting malformed input as an error.
+    ///
+    /// Returns `None` if the row doesn't exist.
+    /// The error locates the record as an element at its offset in the buffer.
+    pub fn try_get(&self, row: usize) -> Option<Result<T, ByteStructError>> {
+        let bytes = self.get_raw(row)?;
+        Some(T::try_read_bytes(bytes).map_err(|e| e.within_element(row, row * self.stride)))
+    }
+
+    /// Returns an iterator that unpacks all records in order
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        (0..self.len()).map(|row| self.get(row).unwrap())
+    }
+
+    /// Returns an iterator that unpacks all records in order, reporting malformed input as errors
+    pub fn try_iter(&self) -> impl Iterator<Item = Result<T, ByteStructError>> + '_ {
+        (0..self.len()).map(|row| self.try_get(row).unwrap())
+    }
+}
+
+impl<T: ByteStructWrite, B: AsRef<[u8]> + AsMut<[u8]>> StridedRecords<T, B> {
+    /// Packs `value` into a record, leaving its padding untouched.
+    ///
+    /// Returns `None` if the row doesn't exist.
+    pub fn set(&mut self, row: usize, value: &T) -> Option<()> {
+        if row >= self.len() {
+            return None;
+        }
+        let start = row * self.stride;
+        value.write_bytes(&mut self.bytes.as_mut()[start..start + T::BYTE_LEN]);
+        Some(())
+    }
+}
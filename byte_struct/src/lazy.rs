@@ -0,0 +1,182 @@
+//! Structures kept as raw bytes until they are accessed.
+
+use crate::{
+    ByteStruct, ByteStructDebugJson, ByteStructError, ByteStructLen, ByteStructRead,
+    ByteStructWrite,
+};
+use core::cell::OnceCell;
+use core::fmt;
+
+/// A structure `T` that is unpacked only when it is accessed, from the `N` raw bytes it was read from.
+///
+/// Reading a `Lazy` only copies the raw bytes. The structure is unpacked
+/// by the first call to [`get`](#method.get) and kept for later calls.
+/// Packing a `Lazy` copies the raw bytes back unless the structure has been modified
+/// with [`get_mut`](#method.get_mut) or [`set`](#method.set), in which case it is packed again.
+/// This saves the unpacking of records that are mostly passed through untouched.
+///
+/// `N` must be `T::BYTE_LEN`, which is checked when the length of the `Lazy` is used.
+/// As `T` is only unpacked on access, malformed bytes are reported by [`try_get`](#method.try_get)
+/// instead of when reading the `Lazy`.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Details {
+///     size: u32,
+///     flags: u16,
+/// }
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Record {
+///     id: u16,
+///     details: Lazy<Details, { Details::BYTE_LEN }>,
+/// }
+///
+/// let mut record = Record::read_bytes(&[1, 0, 16, 0, 0, 0, 3, 0][..]);
+/// assert!(!record.details.is_decoded());
+/// assert_eq!(record.details.get().size, 16);
+/// record.details.get_mut().flags = 7;
+///
+/// let mut bytes = [0; Record::BYTE_LEN];
+/// record.write_bytes(&mut bytes[..]);
+/// assert_eq!(bytes, [1, 0, 16, 0, 0, 0, 7, 0]);
+/// ```
+pub struct Lazy<T, const N: usize> {
+    raw: [u8; N],
+    value: OnceCell<T>,
+    modified: bool,
+}
+
+impl<T: ByteStruct, const N: usize> Lazy<T, N> {
+    /// Creates a `Lazy` holding an unpacked `value`, which is packed when the `Lazy` is packed
+    pub fn new(value: T) -> Self {
+        Lazy {
+            raw: [0; N],
+            value: OnceCell::from(value),
+            modified: true,
+        }
+    }
+
+    /// Creates a `Lazy` from the raw bytes of `T`, which are unpacked on access
+    pub fn from_raw(raw: [u8; N]) -> Self {
+        Lazy {
+            raw,
+            value: OnceCell::new(),
+            modified: false,
+        }
+    }
+
+    /// Returns the structure, unpacking it on the first call.
+    ///
+    /// Panics if `T::read_bytes` panics on the raw bytes.
+    pub fn get(&self) -> &T {
+        self.value.get_or_init(|| T::read_bytes(&self.raw))
+    }
+
+    /// Returns the structure, unpacking it on the first call, reporting malformed bytes as an error
+    pub fn try_get(&self) -> Result<&T, ByteStructError> {
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+        let value = T::try_read_bytes(&self.raw)?;
+        Ok(self.value.get_or_init(|| value))
+    }
+
+    /// Returns the structure for modification, unpacking it if it hasn't been.
+    ///
+    /// The structure is packed again when the `Lazy` is packed.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.get();
+        self.modified = true;
+        self.value.get_mut().unwrap()
+    }
+
+    /// Replaces the structure with `value`, which is packed when the `Lazy` is packed
+    pub fn set(&mut self, value: T) {
+        self.value = OnceCell::from(value);
+        self.modified = true;
+    }
+
+    /// Returns the structure, unpacking it if it hasn't been
+    pub fn into_inner(self) -> T {
+        self.get();
+        self.value.into_inner().unwrap()
+    }
+
+    /// Returns `true` if the structure has been unpacked
+    pub fn is_decoded(&self) -> bool {
+        self.value.get().is_some()
+    }
+
+    /// Returns `true` if the structure has been modified since it was read
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+}
+
+impl<T: ByteStruct, const N: usize> From<T> for Lazy<T, N> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for Lazy<T, N> {
+    fn clone(&self) -> Self {
+        Lazy {
+            raw: self.raw,
+            value: self.value.clone(),
+            modified: self.modified,
+        }
+    }
+}
+
+impl<T: ByteStruct + PartialEq, const N: usize> PartialEq for Lazy<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for Lazy<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.value.get() {
+            Some(value) => write!(f, "Lazy({:?})", value),
+            None => write!(f, "Lazy({:?})", self.raw),
+        }
+    }
+}
+
+impl<T: ByteStructLen, const N: usize> ByteStructLen for Lazy<T, N> {
+    const BYTE_LEN: usize = {
+        assert!(
+            N == T::BYTE_LEN,
+            "The length of Lazy must be the BYTE_LEN of its structure"
+        );
+        N
+    };
+}
+
+impl<T: ByteStruct, const N: usize> ByteStructWrite for Lazy<T, N> {
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        match self.value.get() {
+            Some(value) if self.modified => value.write_bytes(&mut bytes[..N]),
+            _ => bytes[..N].copy_from_slice(&self.raw),
+        }
+    }
+}
+
+impl<T: ByteStruct, const N: usize> ByteStructRead for Lazy<T, N> {
+    fn read_bytes(bytes: &[u8]) -> Self {
+        Self::from_raw(bytes[..Self::BYTE_LEN].try_into().unwrap())
+    }
+}
+
+impl<T: ByteStruct + ByteStructDebugJson, const N: usize> ByteStructDebugJson for Lazy<T, N> {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        self.get().write_debug_json(out)
+    }
+}
@@ -47,12 +47,334 @@
 //!     assert_eq!(raw_descriptor, raw_another);
 //! }
 //! ```
+//!
+//! ## Optional features
+//!
+//! - `dump`: [`HexDump`](struct.HexDump.html), a hex dump annotated with the field layout.
+//! - `uuid`: packing of `uuid::Uuid` in the RFC 4122 layout, and [`Guid`](struct.Guid.html)
+//!   for the mixed-endian layout.
+//! - `std`: conversions between the Unix timestamp types such as [`UnixTime32`](struct.UnixTime32.html)
+//!   and `std::time::SystemTime`.
+//! - `chrono` / `time`: conversions between the Unix timestamp types and `chrono::DateTime<Utc>`
+//!   / `time::OffsetDateTime`.
+//! - `half`: packing of the 16-bit floating point types `half::f16` and `half::bf16`.
+//! - `bitflags`: [`bitflags_byte_struct!`](macro.bitflags_byte_struct.html), which packs flag types
+//!   defined with `bitflags::bitflags!` as their underlying integer.
+//! - `alloc`: [`Registry`](struct.Registry.html), which unpacks messages by their tag
+//!   into `Box<dyn Message>`, [`FrameCodec`](struct.FrameCodec.html), which splits a byte stream
+//!   into length-prefixed frames, [`StreamParser`](struct.StreamParser.html), which splits
+//!   a byte stream into structures, and [`Migrator`](struct.Migrator.html), which unpacks
+//!   any registered version of a structure and upgrades it to the latest.
+//!   Also packing of `Box<T>` in the same way as `T`,
+//!   where boxed arrays are unpacked directly on the heap. This is also enabled by `std`.
+//! - `embedded-hal`: [`read_register`](fn.read_register.html) and the related functions, which access
+//!   structures mapped to device registers over `embedded_hal` I²C and SPI buses.
+//! - `bytes`: [`ByteStructBufExt`](trait.ByteStructBufExt.html) and
+//!   [`ByteStructBufMutExt`](trait.ByteStructBufMutExt.html), which unpack structures from
+//!   `bytes::Buf` and pack them into `bytes::BufMut`. This also enables `alloc`.
+//! - `heapless` / `arrayvec`: [`Padded`](struct.Padded.html), which packs the bounded strings
+//!   and vectors of these crates at their full capacity.
+//! - `generic-array`: packing of `generic_array::GenericArray` (0.14) in the same way as arrays,
+//!   for typenum-based APIs.
+//! - `bytemuck` / `zerocopy`: [`Bytemuck`](struct.Bytemuck.html) and
+//!   [`Zerocopy`](struct.Zerocopy.html), which pack fields of these crates' plain data types
+//!   as a copy of their memory.
+//! - `scroll`: [`Scroll`](struct.Scroll.html), which reads and writes structures with
+//!   `scroll::Pread` and `scroll::Pwrite`, for use alongside parsers built on `scroll`.
+//! - `nom` / `winnow`: [`nom_parse`](fn.nom_parse.html) and [`winnow_parse`](fn.winnow_parse.html),
+//!   parsers that unpack a structure within these parser combinator libraries.
+//! - `arbitrary`: [`arbitrary_read`](fn.arbitrary_read.html), which generates structures from fuzzer
+//!   input by unpacking it, and `#[byte_struct(arbitrary)]` of the derive,
+//!   which implements `arbitrary::Arbitrary` with it.
+//! - `proptest`: [`byte_struct_roundtrip!`](macro.byte_struct_roundtrip.html) and
+//!   [`assert_roundtrip`](fn.assert_roundtrip.html), property tests that structures survive
+//!   packing and unpacking. This also enables `std`.
+//! - `schema`: [`Schema`](struct.Schema.html), which exports the field layout as a Kaitai Struct
+//!   file, an 010 Editor template or a C header.
+//! - `encoding_rs`: [`EncodedString`](struct.EncodedString.html), which decodes fixed-size strings
+//!   in legacy text encodings such as Shift-JIS. This also enables `alloc`.
 
 #![no_std]
 
-pub use byte_struct_derive::{ByteStruct, ByteStructBE, ByteStructLE};
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub use byte_struct_derive::{
+    ByteStruct, ByteStructBE, ByteStructDebugJson, ByteStructLE, ByteStructVisit,
+};
+
+mod bcd;
+mod bitfield;
+mod borrow;
+#[cfg(feature = "alloc")]
+mod boxed;
+#[cfg(feature = "bytes")]
+mod buf;
+mod checksum;
+#[cfg(feature = "alloc")]
+mod codec;
+#[cfg(any(feature = "nom", feature = "winnow"))]
+mod combinator;
+mod context;
+mod cstr;
+#[cfg(feature = "dump")]
+mod dump;
+#[cfg(feature = "encoding_rs")]
+mod encoding;
+mod enum_field;
+mod error;
+#[cfg(feature = "bitflags")]
+mod flags;
+mod float;
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+#[cfg(feature = "generic-array")]
+mod genarray;
+#[cfg(feature = "uuid")]
+mod guid;
+mod json;
+mod layout;
+mod lazy;
+#[cfg(any(feature = "bytemuck", feature = "zerocopy"))]
+mod memcpy;
+#[cfg(feature = "alloc")]
+mod migrate;
+mod net;
+mod packed_array;
+#[cfg(any(feature = "heapless", feature = "arrayvec"))]
+mod padded;
+mod pool;
+mod pstring;
+mod register;
+mod registry;
+#[cfg(feature = "proptest")]
+mod roundtrip;
+#[cfg(feature = "schema")]
+mod schema;
+#[cfg(feature = "scroll")]
+mod scroll_ctx;
+mod serial;
+mod timestamp;
+mod varint;
+mod vectored;
+mod visit;
+mod word_swap;
+pub use bcd::*;
+pub use bitfield::*;
+pub use borrow::*;
+#[cfg(feature = "bytes")]
+pub use buf::*;
+pub use checksum::*;
+#[cfg(feature = "alloc")]
+pub use codec::*;
+#[cfg(any(feature = "nom", feature = "winnow"))]
+pub use combinator::*;
+pub use context::*;
+pub use cstr::*;
+#[cfg(feature = "dump")]
+pub use dump::*;
+#[cfg(feature = "encoding_rs")]
+pub use encoding::*;
+pub use enum_field::*;
+pub use error::*;
+pub use float::*;
+#[cfg(feature = "arbitrary")]
+pub use fuzz::*;
+#[cfg(feature = "uuid")]
+pub use guid::*;
+pub use json::*;
+pub use layout::*;
+pub use lazy::*;
+#[cfg(any(feature = "bytemuck", feature = "zerocopy"))]
+pub use memcpy::*;
+#[cfg(feature = "alloc")]
+pub use migrate::*;
+pub use net::*;
+pub use packed_array::*;
+#[cfg(any(feature = "heapless", feature = "arrayvec"))]
+pub use padded::*;
+pub use pool::*;
+pub use pstring::*;
+pub use register::*;
+pub use registry::*;
+#[cfg(feature = "proptest")]
+pub use roundtrip::*;
+#[cfg(feature = "schema")]
+pub use schema::*;
+#[cfg(feature = "scroll")]
+pub use scroll_ctx::*;
+pub use serial::*;
+pub use timestamp::*;
+pub use varint::*;
+pub use visit::*;
+pub use word_swap::*;
+
+/// Helpers used by code generated by the derive macro. Not a public API.
+#[doc(hidden)]
+pub mod __private {
+    use crate::{BitFieldStruct, ByteOrder, ByteStruct, ByteStructLen};
+    use core::marker::PhantomData;
+
+    #[cfg(feature = "bitflags")]
+    pub use bitflags::Flags;
+
+    #[cfg(feature = "alloc")]
+    pub use alloc::vec::Vec;
+
+    #[cfg(feature = "arbitrary")]
+    pub use arbitrary;
+
+    /// Returns `true` if `bytes` holds at least `count` consecutive records of type `T`
+    pub fn records_fit<T: ByteStructLen>(bytes: &[u8], count: usize) -> bool {
+        count
+            .checked_mul(T::BYTE_LEN)
+            .is_some_and(|len| len <= bytes.len())
+    }
+
+    /// Returns the packed length of the payload of a single-field enum variant
+    pub const fn variant_len<T: ByteStructLen, E>(_constructor: fn(T) -> E) -> usize {
+        T::BYTE_LEN
+    }
+
+    /// Resolves the byte order actually used for a field of type `T`.
+    ///
+    /// Calling `(&OrderProbe::<T>::new()).resolve(declared)` on a concrete `T` picks
+    /// `ResolveFixed` if `T` implements `ByteStruct`, and `ResolveDeclared` otherwise.
+    pub struct OrderProbe<T>(PhantomData<T>);
+
+    impl<T> OrderProbe<T> {
+        #[allow(clippy::new_without_default)]
+        pub const fn new() -> Self {
+            OrderProbe(PhantomData)
+        }
+    }
+
+    pub trait ResolveFixed {
+        fn resolve(&self, _declared: ByteOrder) -> ByteOrder {
+            ByteOrder::Fixed
+        }
+    }
+
+    impl<T: ByteStruct> ResolveFixed for OrderProbe<T> {}
+
+    pub trait ResolveDeclared {
+        fn resolve(&self, declared: ByteOrder) -> ByteOrder {
+            declared
+        }
+    }
+
+    impl<T> ResolveDeclared for &OrderProbe<T> {}
+
+    /// Panics if `start .. end` is not a valid bit range of an integer with `bits` bits.
+    ///
+    /// This is shared by the bit range functions and by `bitfields!`,
+    /// which checks its members at compile time.
+    pub const fn check_bit_range(start: u32, end: u32, bits: u32) {
+        assert!(start <= end, "Bit range starts after its end");
+        assert!(end <= bits, "Bit range exceeds the width of the integer");
+    }
+
+    /// Panics if the bit lengths of the members of a `bitfields!` structure, adding up to `len`,
+    /// exceed the width `bits` of its base type, or fall short of it unless `partial` is set.
+    pub const fn check_bit_len(len: u32, bits: u32, partial: bool) {
+        assert!(len <= bits, "Bit lengths exceed the width of the base type");
+        assert!(
+            partial || len == bits,
+            "Bit lengths don't add up to the width of the base type; \
+            declare the structure with `=> partial` to leave the high bits unused"
+        );
+    }
+
+    /// Returns the sum of packed lengths, failing the build if it overflows `usize`
+    /// when evaluated for `BYTE_LEN`
+    pub const fn len_sum(lens: &[usize]) -> usize {
+        let mut sum: usize = 0;
+        let mut i = 0;
+        while i < lens.len() {
+            sum = match sum.checked_add(lens[i]) {
+                Some(sum) => sum,
+                None => panic!("Packed length overflows usize"),
+            };
+            i += 1;
+        }
+        sum
+    }
+
+    /// Returns the padding that places a field at the absolute `offset`, after the fields
+    /// that end at `end`, failing the build if they extend past it when evaluated for `BYTE_LEN`
+    pub const fn offset_pad(offset: usize, end: usize) -> usize {
+        match offset.checked_sub(end) {
+            Some(pad) => pad,
+            None => panic!("Field offset overlaps the fields before it"),
+        }
+    }
+
+    /// Returns the length a union view adds to the structure, which is none, failing the build
+    /// if the view of length `len` is longer than its primary field when evaluated for `BYTE_LEN`
+    pub const fn union_span(len: usize, primary_len: usize) -> usize {
+        assert!(len <= primary_len, "Union view is longer than the field it overlaps");
+        0
+    }
+
+    /// Returns the packed length of `count` values of length `len`,
+    /// failing the build if it overflows `usize` when evaluated for `BYTE_LEN`
+    pub const fn len_mul(count: usize, len: usize) -> usize {
+        match count.checked_mul(len) {
+            Some(product) => product,
+            None => panic!("Packed length overflows usize"),
+        }
+    }
+
+    /// Returns the packed length of `count` bit field structures stored as bit planes
+    pub const fn bit_planes_len<T: BitFieldStruct>(count: usize) -> usize {
+        len_mul(T::BITS as usize, count.div_ceil(8))
+    }
+
+    /// Packs bit field structures as bit planes.
+    ///
+    /// Plane `k` holds bit `k` of the raw bits of every element, one bit per element,
+    /// starting from the least significant bit of the first byte of the plane.
+    /// Each plane is padded to whole bytes.
+    pub fn write_bit_planes<T: BitFieldStruct>(values: &[T], bytes: &mut [u8]) {
+        let plane_len = values.len().div_ceil(8);
+        for byte in bytes.iter_mut() {
+            *byte = 0;
+        }
+        for (i, value) in values.iter().enumerate() {
+            let raw = value.to_raw_u128();
+            for bit in 0 .. T::BITS as usize {
+                if (raw >> bit) & 1 != 0 {
+                    bytes[bit * plane_len + i / 8] |= 1 << (i % 8);
+                }
+            }
+        }
+    }
+
+    /// Unpacks bit field structures stored as bit planes. See `write_bit_planes`.
+    pub fn read_bit_planes<T: BitFieldStruct, const N: usize>(bytes: &[u8]) -> [T; N] {
+        let plane_len = N.div_ceil(8);
+        core::array::from_fn(|i| {
+            let mut raw: u128 = 0;
+            for bit in 0 .. T::BITS as usize {
+                raw |= u128::from((bytes[bit * plane_len + i / 8] >> (i % 8)) & 1) << bit;
+            }
+            T::from_raw_u128(raw)
+        })
+    }
+}
 
 /// A type that can be packed into or unpacked from fixed-size bytes, but the method is unknown yet.
+///
+/// The lengths of arrays, tuples and derived structures are computed with overflow checks,
+/// so a length that doesn't fit in `usize` fails the build where it is used.
+///
+/// ```compile_fail
+/// use byte_struct::*;
+///
+/// const LEN: usize = <[[u8; usize::MAX]; 2]>::BYTE_LEN;
+/// ```
 pub trait ByteStructLen {
     /// The length of the packed bytes of this type
     const BYTE_LEN: usize;
@@ -63,20 +385,233 @@ pub trait ByteStructLen {
 /// This trait can be derived by
 /// [`#[derive(ByteStruct)]`](https://docs.rs/byte_struct_derive/*/byte_struct_derive/derive.ByteStruct.html).
 ///
-/// One can implement this trait for custom types in order to pack or unpack an object in a special way.
-pub trait ByteStruct: ByteStructLen {
+/// The packing and unpacking functions are provided by [`ByteStructWrite`] and [`ByteStructRead`],
+/// and this trait is implemented for every type that implements both of them.
+/// Types that can only be sensibly packed or unpacked can implement only one of them,
+/// and can still be nested as fields of structures that are only packed or only unpacked.
+///
+/// One can implement these traits for custom types in order to pack or unpack an object in a special way.
+///
+/// [`ByteStructWrite`]: trait.ByteStructWrite.html
+/// [`ByteStructRead`]: trait.ByteStructRead.html
+#[diagnostic::on_unimplemented(
+    note = "fields of types without their own byte order, such as primitives, \
+            need #[byte_struct_le] or #[byte_struct_be] on the field or on the struct"
+)]
+pub trait ByteStruct: ByteStructRead + ByteStructWrite {}
+
+impl<T: ByteStructRead + ByteStructWrite> ByteStruct for T {}
+
+/// A data structure that can be packed into raw bytes.
+///
+/// See [`ByteStruct`](trait.ByteStruct.html).
+#[diagnostic::on_unimplemented(
+    note = "fields of types without their own byte order, such as primitives, \
+            need #[byte_struct_le] or #[byte_struct_be] on the field or on the struct"
+)]
+pub trait ByteStructWrite: ByteStructLen {
     /// Packs the struct into raw bytes and write to a slice
     fn write_bytes(&self, bytes: &mut [u8]);
 
+    /// Packs the struct into raw bytes and write across a sequence of slices, filling them in order
+    ///
+    /// This allows packing into buffers that are split, such as the two parts of a ring buffer
+    /// that wraps around. Only the first `BYTE_LEN` bytes of the slices are written.
+    ///
+    /// # Limitations
+    /// If the bytes don't fit in the first slice, the struct is packed into a staging buffer
+    /// and then copied into the slices, so split writes cost an extra copy.
+    /// The staging buffer is on the stack for structures up to 256 bytes long,
+    /// and is allocated on the heap for longer ones. Without the `alloc` feature,
+    /// calling this on a structure longer than 256 bytes fails to compile.
+    ///
+    /// Panics if the slices are shorter than `BYTE_LEN` in total.
+    fn write_bytes_vectored(&self, bufs: &mut [&mut [u8]]) {
+        vectored::write_vectored(self, bufs);
+    }
+
+    /// Packs the struct into raw bytes and write to the last `BYTE_LEN` bytes of a slice
+    ///
+    /// This is the packing counterpart of
+    /// [`read_bytes_from_end`](trait.ByteStructRead.html#method.read_bytes_from_end).
+    fn write_bytes_to_end(&self, bytes: &mut [u8]) {
+        let start = bytes.len() - Self::BYTE_LEN;
+        self.write_bytes(&mut bytes[start..]);
+    }
+}
+
+/// A data structure that can be unpacked from raw bytes.
+///
+/// See [`ByteStruct`](trait.ByteStruct.html).
+#[diagnostic::on_unimplemented(
+    note = "fields of types without their own byte order, such as primitives, \
+            need #[byte_struct_le] or #[byte_struct_be] on the field or on the struct"
+)]
+pub trait ByteStructRead: ByteStructLen {
     /// Unpacks raw bytes from a slice into a new struct
     fn read_bytes(bytes: &[u8]) -> Self;
+
+    /// Unpacks raw bytes from a slice into a new struct, reporting malformed input as an error
+    ///
+    /// The default implementation only checks the length of the slice before calling
+    /// [`read_bytes`](#tymethod.read_bytes).
+    /// Types that can reject certain values should override this.
+    fn try_read_bytes(bytes: &[u8]) -> Result<Self, ByteStructError>
+    where
+        Self: Sized,
+    {
+        check_len::<Self>(bytes)?;
+        Ok(Self::read_bytes(bytes))
+    }
+
+    /// Unpacks raw bytes from a slice into an existing struct, replacing its content
+    ///
+    /// This allows decoding into storage that is allocated up front, such as a slot of a
+    /// [`MessagePool`](struct.MessagePool.html).
+    fn read_bytes_into(&mut self, bytes: &[u8])
+    where
+        Self: Sized,
+    {
+        *self = Self::read_bytes(bytes);
+    }
+
+    /// Unpacks raw bytes from a slice into an existing struct, reporting malformed input as an error
+    ///
+    /// The struct is left unchanged if an error is returned.
+    fn try_read_bytes_into(&mut self, bytes: &[u8]) -> Result<(), ByteStructError>
+    where
+        Self: Sized,
+    {
+        *self = Self::try_read_bytes(bytes)?;
+        Ok(())
+    }
+
+    /// Unpacks raw bytes from a sequence of slices, read in order, into a new struct
+    ///
+    /// This allows unpacking from buffers that are split, such as the two parts of a ring buffer
+    /// that wraps around. See
+    /// [`write_bytes_vectored`](trait.ByteStructWrite.html#method.write_bytes_vectored)
+    /// for how the bytes are staged and the limitations that come with it.
+    fn read_bytes_vectored(bufs: &[&[u8]]) -> Self
+    where
+        Self: Sized,
+    {
+        vectored::read_vectored::<Self, _>(bufs, Self::read_bytes)
+    }
+
+    /// Unpacks raw bytes from a sequence of slices, read in order, into a new struct,
+    /// reporting malformed input as an error
+    fn try_read_bytes_vectored(bufs: &[&[u8]]) -> Result<Self, ByteStructError>
+    where
+        Self: Sized,
+    {
+        vectored::read_vectored::<Self, _>(bufs, Self::try_read_bytes)
+    }
+
+    /// Unpacks the last `BYTE_LEN` bytes of a slice into a new struct
+    ///
+    /// This reads trailers that are located from the end of a buffer,
+    /// such as the end of central directory record of a ZIP file.
+    fn read_bytes_from_end(bytes: &[u8]) -> Self
+    where
+        Self: Sized,
+    {
+        Self::read_bytes(&bytes[bytes.len() - Self::BYTE_LEN..])
+    }
+
+    /// Unpacks the last `BYTE_LEN` bytes of a slice into a new struct,
+    /// reporting malformed input as an error
+    ///
+    /// Offsets in the error are relative to the start of the slice.
+    fn try_read_bytes_from_end(bytes: &[u8]) -> Result<Self, ByteStructError>
+    where
+        Self: Sized,
+    {
+        check_len::<Self>(bytes)?;
+        let start = bytes.len() - Self::BYTE_LEN;
+        Self::try_read_bytes(&bytes[start..]).map_err(|e| e.within(None, start))
+    }
+
+    /// Unpacks raw bytes from a slice into a new boxed struct
+    ///
+    /// The struct is unpacked with [`read_bytes`](#tymethod.read_bytes) and then moved into
+    /// the box, so this does not avoid having the whole struct on the stack while it is unpacked.
+    /// Large arrays are kept off the stack by declaring them as fields of type `Box<[T; N]>`,
+    /// which are unpacked directly on the heap, so that only the pointers are on the stack.
+    #[cfg(feature = "alloc")]
+    fn read_bytes_boxed(bytes: &[u8]) -> alloc::boxed::Box<Self>
+    where
+        Self: Sized,
+    {
+        alloc::boxed::Box::new(Self::read_bytes(bytes))
+    }
+}
+
+/// An object-safe counterpart of [`ByteStructWrite`], for packing values behind `dyn` pointers.
+///
+/// This is implemented for every type that implements [`ByteStructWrite`],
+/// so that values of different types can be kept together, such as in a queue of outgoing messages.
+///
+/// [`ByteStructWrite`]: trait.ByteStructWrite.html
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Ping {
+///     seq: u16,
+/// }
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Move {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// let queue: Vec<Box<dyn ByteStructDyn>> = vec![Box::new(Ping { seq: 1 }), Box::new(Move { x: 2, y: 3 })];
+/// let mut bytes = Vec::new();
+/// for message in &queue {
+///     let start = bytes.len();
+///     bytes.resize(start + message.byte_len(), 0);
+///     message.write_bytes_dyn(&mut bytes[start..]);
+/// }
+/// assert_eq!(bytes.len(), 10);
+/// ```
+pub trait ByteStructDyn {
+    /// Returns the length of the packed bytes, which is `BYTE_LEN` of the underlying type
+    fn byte_len(&self) -> usize;
+
+    /// Packs the value into raw bytes and write to a slice
+    fn write_bytes_dyn(&self, bytes: &mut [u8]);
+}
+
+impl<T: ByteStructWrite> ByteStructDyn for T {
+    fn byte_len(&self) -> usize {
+        T::BYTE_LEN
+    }
+
+    fn write_bytes_dyn(&self, bytes: &mut [u8]) {
+        self.write_bytes(bytes);
+    }
+}
+
+fn check_len<T: ByteStructLen>(bytes: &[u8]) -> Result<(), ByteStructError> {
+    if bytes.len() < T::BYTE_LEN {
+        return Err(ByteStructError::new(ErrorKind::ShortBuffer, None, bytes.len()));
+    }
+    Ok(())
 }
 
 /// A type that can be packed into or unpacked from raw bytes under given default byte order.
 ///
 /// This trait is implemented for most numeric primitive types,
 /// except for `bool`, `char`, `isize` and `usize`. This is also implemented for array types
-/// whose element type implements `ByteStructUnspecifiedByteOrder`.
+/// whose element type implements `ByteStructUnspecifiedByteOrder`,
+/// and for tuples of up to 12 such types, which are packed one after another.
+/// `Wrapping<T>` and `Saturating<T>` are packed in the same way as `T`.
 ///
 /// This trait is automatically implemented for all types that implements [`ByteStruct`].
 /// In this case, all members of `ByteStructUnspecifiedByteOrder` are direct wrappers of [`ByteStruct`] members.
@@ -109,28 +644,60 @@ pub trait ByteStructUnspecifiedByteOrder: ByteStructLen {
 
     /// Unpacks raw bytes into a new object with big-endian as the default byte order
     fn read_bytes_default_be(bytes: &[u8]) -> Self;
+
+    /// Fallible version of `read_bytes_default_le`
+    ///
+    /// The default implementation only checks the length of the slice.
+    fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, ByteStructError>
+    where
+        Self: Sized,
+    {
+        check_len::<Self>(bytes)?;
+        Ok(Self::read_bytes_default_le(bytes))
+    }
+
+    /// Fallible version of `read_bytes_default_be`
+    ///
+    /// The default implementation only checks the length of the slice.
+    fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, ByteStructError>
+    where
+        Self: Sized,
+    {
+        check_len::<Self>(bytes)?;
+        Ok(Self::read_bytes_default_be(bytes))
+    }
 }
 
 impl<T: ByteStruct> ByteStructUnspecifiedByteOrder for T {
-    /// A wrapper of [`ByteStruct::write_bytes`](trait.ByteStruct.html#tymethod.write_bytes)
+    /// A wrapper of [`ByteStructWrite::write_bytes`](trait.ByteStructWrite.html#tymethod.write_bytes)
     fn write_bytes_default_le(&self, bytes: &mut [u8]) {
         self.write_bytes(bytes);
     }
 
-    /// A wrapper of [`ByteStruct::read_bytes`](trait.ByteStruct.html#tymethod.read_bytes)
+    /// A wrapper of [`ByteStructRead::read_bytes`](trait.ByteStructRead.html#tymethod.read_bytes)
     fn read_bytes_default_le(bytes: &[u8]) -> Self {
         Self::read_bytes(bytes)
     }
 
-    /// A wrapper of [`ByteStruct::write_bytes`](trait.ByteStruct.html#tymethod.write_bytes)
+    /// A wrapper of [`ByteStructWrite::write_bytes`](trait.ByteStructWrite.html#tymethod.write_bytes)
     fn write_bytes_default_be(&self, bytes: &mut [u8]) {
         self.write_bytes(bytes);
     }
 
-    /// A wrapper of [`ByteStruct::read_bytes`](trait.ByteStruct.html#tymethod.read_bytes)
+    /// A wrapper of [`ByteStructRead::read_bytes`](trait.ByteStructRead.html#tymethod.read_bytes)
     fn read_bytes_default_be(bytes: &[u8]) -> Self {
         Self::read_bytes(bytes)
     }
+
+    /// A wrapper of [`ByteStructRead::try_read_bytes`](trait.ByteStructRead.html#method.try_read_bytes)
+    fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        Self::try_read_bytes(bytes)
+    }
+
+    /// A wrapper of [`ByteStructRead::try_read_bytes`](trait.ByteStructRead.html#method.try_read_bytes)
+    fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        Self::try_read_bytes(bytes)
+    }
 }
 
 impl ByteStructLen for u8 {
@@ -379,14 +946,14 @@ impl ByteStructUnspecifiedByteOrder for f64 {
         bytes.copy_from_slice(&self.to_bits().to_be_bytes()[..]);
     }
     fn read_bytes_default_be(bytes: &[u8]) -> Self {
-        f64::from_bits(u64::from_le_bytes([
+        f64::from_bits(u64::from_be_bytes([
             bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
         ]))
     }
 }
 
 impl<T: ByteStructLen, const N: usize> ByteStructLen for [T; N] {
-    const BYTE_LEN: usize = N * T::BYTE_LEN;
+    const BYTE_LEN: usize = __private::len_mul(N, T::BYTE_LEN);
 }
 
 impl<T: ByteStructUnspecifiedByteOrder, const N: usize> ByteStructUnspecifiedByteOrder for [T; N] {
@@ -414,6 +981,173 @@ impl<T: ByteStructUnspecifiedByteOrder, const N: usize> ByteStructUnspecifiedByt
         let len = T::BYTE_LEN;
         core::array::from_fn(|i| <T>::read_bytes_default_be(&bytes[i * len..(i + 1) * len]))
     }
+    fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        let len = T::BYTE_LEN;
+        try_from_fn(|i| {
+            <T>::try_read_bytes_default_le(&bytes[i * len..(i + 1) * len])
+                .map_err(|e| e.within_element(i, i * len))
+        })
+    }
+    fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        let len = T::BYTE_LEN;
+        try_from_fn(|i| {
+            <T>::try_read_bytes_default_be(&bytes[i * len..(i + 1) * len])
+                .map_err(|e| e.within_element(i, i * len))
+        })
+    }
+}
+
+impl<T: ?Sized> ByteStructLen for core::marker::PhantomData<T> {
+    const BYTE_LEN: usize = 0;
+}
+
+/// Occupies no bytes, so that generic structures can carry marker parameters
+impl<T: ?Sized> ByteStructWrite for core::marker::PhantomData<T> {
+    fn write_bytes(&self, _bytes: &mut [u8]) {}
+}
+
+impl<T: ?Sized> ByteStructRead for core::marker::PhantomData<T> {
+    fn read_bytes(_bytes: &[u8]) -> Self {
+        core::marker::PhantomData
+    }
+}
+
+impl ByteStructLen for () {
+    const BYTE_LEN: usize = 0;
+}
+
+/// Occupies no bytes
+impl ByteStructWrite for () {
+    fn write_bytes(&self, _bytes: &mut [u8]) {}
+}
+
+impl ByteStructRead for () {
+    fn read_bytes(_bytes: &[u8]) -> Self {}
+}
+
+macro_rules! num_wrapper_impl {
+    ($($wrapper:ident),*) => {$(
+        impl<T: ByteStructLen> ByteStructLen for core::num::$wrapper<T> {
+            const BYTE_LEN: usize = T::BYTE_LEN;
+        }
+
+        /// Packed in the same way as the wrapped value
+        impl<T: ByteStructUnspecifiedByteOrder> ByteStructUnspecifiedByteOrder for core::num::$wrapper<T> {
+            fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+                self.0.write_bytes_default_le(bytes);
+            }
+            fn read_bytes_default_le(bytes: &[u8]) -> Self {
+                core::num::$wrapper(T::read_bytes_default_le(bytes))
+            }
+            fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+                self.0.write_bytes_default_be(bytes);
+            }
+            fn read_bytes_default_be(bytes: &[u8]) -> Self {
+                core::num::$wrapper(T::read_bytes_default_be(bytes))
+            }
+            fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, ByteStructError> {
+                T::try_read_bytes_default_le(bytes).map(core::num::$wrapper)
+            }
+            fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, ByteStructError> {
+                T::try_read_bytes_default_be(bytes).map(core::num::$wrapper)
+            }
+        }
+    )*}
+}
+
+num_wrapper_impl!(Wrapping, Saturating);
+
+macro_rules! tuple_impl {
+    ($($t:ident $v:ident),+) => {
+        impl<$($t: ByteStructLen),+> ByteStructLen for ($($t,)+) {
+            const BYTE_LEN: usize = __private::len_sum(&[$($t::BYTE_LEN),+]);
+        }
+
+        #[allow(unused_assignments)]
+        impl<$($t: ByteStructUnspecifiedByteOrder),+> ByteStructUnspecifiedByteOrder for ($($t,)+) {
+            fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+                let ($($v,)+) = self;
+                let mut pos = 0;
+                $(
+                    $v.write_bytes_default_le(&mut bytes[pos..pos + $t::BYTE_LEN]);
+                    pos += $t::BYTE_LEN;
+                )+
+            }
+            fn read_bytes_default_le(bytes: &[u8]) -> Self {
+                let mut pos = 0;
+                ($({
+                    let $v = <$t>::read_bytes_default_le(&bytes[pos..pos + $t::BYTE_LEN]);
+                    pos += $t::BYTE_LEN;
+                    $v
+                },)+)
+            }
+            fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+                let ($($v,)+) = self;
+                let mut pos = 0;
+                $(
+                    $v.write_bytes_default_be(&mut bytes[pos..pos + $t::BYTE_LEN]);
+                    pos += $t::BYTE_LEN;
+                )+
+            }
+            fn read_bytes_default_be(bytes: &[u8]) -> Self {
+                let mut pos = 0;
+                ($({
+                    let $v = <$t>::read_bytes_default_be(&bytes[pos..pos + $t::BYTE_LEN]);
+                    pos += $t::BYTE_LEN;
+                    $v
+                },)+)
+            }
+            fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, ByteStructError> {
+                check_len::<Self>(bytes)?;
+                let mut pos = 0;
+                Ok(($({
+                    let $v = <$t>::try_read_bytes_default_le(&bytes[pos..pos + $t::BYTE_LEN])
+                        .map_err(|e| e.within(None, pos))?;
+                    pos += $t::BYTE_LEN;
+                    $v
+                },)+))
+            }
+            fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, ByteStructError> {
+                check_len::<Self>(bytes)?;
+                let mut pos = 0;
+                Ok(($({
+                    let $v = <$t>::try_read_bytes_default_be(&bytes[pos..pos + $t::BYTE_LEN])
+                        .map_err(|e| e.within(None, pos))?;
+                    pos += $t::BYTE_LEN;
+                    $v
+                },)+))
+            }
+        }
+    };
+}
+
+tuple_impl!(A a);
+tuple_impl!(A a, B b);
+tuple_impl!(A a, B b, C c);
+tuple_impl!(A a, B b, C c, D d);
+tuple_impl!(A a, B b, C c, D d, E e);
+tuple_impl!(A a, B b, C c, D d, E e, F f);
+tuple_impl!(A a, B b, C c, D d, E e, F f, G g);
+tuple_impl!(A a, B b, C c, D d, E e, F f, G g, H h);
+tuple_impl!(A a, B b, C c, D d, E e, F f, G g, H h, I i);
+tuple_impl!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j);
+tuple_impl!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k);
+tuple_impl!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l);
+
+fn try_from_fn<T, const N: usize>(
+    mut f: impl FnMut(usize) -> Result<T, ByteStructError>,
+) -> Result<[T; N], ByteStructError> {
+    let mut error = None;
+    let array = core::array::from_fn(|i| match error {
+        None => f(i).map_err(|e| error = Some(e)).ok(),
+        Some(_) => None,
+    });
+    match error {
+        Some(e) => Err(e),
+        None => Ok(array.map(|element: Option<T>| element.unwrap())),
+    }
 }
 
 /// Generates a structure that implements [`ByteStructUnspecifiedByteOrder`] with bit field semantics.
@@ -439,7 +1173,11 @@ impl<T: ByteStructUnspecifiedByteOrder, const N: usize> ByteStructUnspecifiedByt
 ///     SampleBitField: u16 {
 ///         // Specifies members and bit length from the least significant bit to the most.
 ///         // The bit layout is assumed packed, and paddings must be explicitly specified.
-///         // The sum of bit length of all fields must equal the bit length of the base type.
+///         // The sum of bit length of all fields must equal the bit length of the base type,
+///         // which is checked at compile time, including lengths given as constant expressions.
+///         // See "Partial structures" below for leaving the high bits unused.
+///         // A member can be 0 bits wide, such as a placeholder for a documented field,
+///         // or as wide as the base type.
 ///         // Attributes and visibility specifier can be attached before the field name.
 ///
 ///         // This creates bit field structure in the following layout:
@@ -469,64 +1207,587 @@ impl<T: ByteStructUnspecifiedByteOrder, const N: usize> ByteStructUnspecifiedByt
 ///     ...
 /// }
 /// ```
+///
+/// Bits of a member beyond its width are discarded when packing, so that an oversized value
+/// can't overwrite other members. To reject such values instead, the generated structure
+/// has the function `new(...)`, with the same visibility as the structure, which takes
+/// the members in order and returns `None` if a value doesn't fit in the width of its member.
+///
+/// ```ignore
+/// assert!(SampleBitField::new(1, 2, 0, 3).is_some());
+/// assert!(SampleBitField::new(16, 2, 0, 3).is_none());
+/// ```
+///
+/// Bit lengths that don't add up to the width of the base type fail the build:
+///
+/// ```compile_fail
+/// use byte_struct::*;
+///
+/// const LOW_BITS: u32 = 6;
+///
+/// bitfields!(
+///     LongBitField: u16 {
+///         low: LOW_BITS,
+///         high: 11,
+///     }
+/// );
+/// ```
+///
+/// ```compile_fail
+/// use byte_struct::*;
+///
+/// bitfields!(
+///     ShortBitField: u16 {
+///         low: 4,
+///         high: 11,
+///     }
+/// );
+/// ```
+///
+/// # Partial structures
+///
+/// Appending `=> partial` after the base type allows the bit lengths to add up to less than
+/// the width of the base type, leaving the high bits unused. This is meant for structures that
+/// are packed into fewer bits than their base type, such as the elements of bit-plane fields
+/// and nested structures. The high bits are ignored when unpacking and packed as zero.
+/// Bit lengths that exceed the width of the base type still fail the build.
+///
+/// ```ignore
+/// bitfields!(
+///     Pixel: u8 => partial {
+///         pub color: 2,
+///         pub blink: 1,
+///     }
+/// );
+/// ```
+///
+/// # Access restrictions
+///
+/// Members of hardware registers often can't be both read and written.
+/// An access marker can be appended after the bit length of a member,
+/// which changes the type of the member in the generated structure:
+///
+/// - `=> ro`: [`ReadOnly`]. The member is unpacked as usual, but always packed as zero.
+/// - `=> wo`: [`WriteOnly`]. The member is packed as usual, but always unpacked as zero.
+/// - `=> w1c`: [`WriteOneToClear`]. The member is unpacked as the status,
+///   and is packed as zero unless clearing is requested.
+///
+/// ```ignore
+/// bitfields!(
+///     StatusRegister: u8 {
+///         pub ready: 1 => ro,
+///         pub reset: 1 => wo,
+///         pub overflow: 1 => w1c,
+///         pub mode: 5,
+///     }
+/// );
+/// ```
+///
+/// [`ReadOnly`]: struct.ReadOnly.html
+/// [`WriteOnly`]: struct.WriteOnly.html
+/// [`WriteOneToClear`]: struct.WriteOneToClear.html
+///
+/// # Nested structures
+///
+/// A member declared with `=> nested(Type)`, where `Type` is another structure generated by
+/// `bitfields!`, holds that structure packed into the bits of the member.
+/// The width of the member must be at least the sum of bit lengths of `Type`,
+/// which is checked at compile time. This allows sub-fields shared by several registers
+/// to be declared once.
+///
+/// ```ignore
+/// bitfields!(
+///     #[derive(Clone, Copy)]
+///     Priority: u8 => partial {
+///         pub level: 3,
+///         pub preempt: 1,
+///     }
+/// );
+///
+/// bitfields!(
+///     InterruptConfig: u16 {
+///         pub enable: 1,
+///         pub priority: 4 => nested(Priority),
+///         pub vector: 11,
+///     }
+/// );
+/// ```
+///
+/// # Raw integers
+///
+/// The generated structure has the functions `from_raw(raw)` and `to_raw(&self)`,
+/// with the same visibility as the structure, which convert it from and to the base integer
+/// directly, for values that don't come from bytes, such as a register value read by another
+/// driver. The raw integer is the one before any `reverse_bits`, and `from_raw` doesn't check
+/// fixed or parity bits.
+///
+/// ```ignore
+/// let value = SampleBitField::from_raw(0x1234);
+/// assert_eq!(value.to_raw(), 0x1234);
+/// ```
+///
+/// # Raw bit positions
+///
+/// A member can be followed by `=> [MASK, SHIFT]`, which names the associated constants
+/// generated for it: `MASK`, the bits of the member in the raw integer, and `SHIFT`,
+/// the position of its least significant bit. Two more names can be given as
+/// `=> [MASK, SHIFT, with, try_with]` to also generate the methods `with(self, value)`,
+/// which returns the structure with the member replaced, and `try_with(self, value)`,
+/// which returns `None` instead if the value doesn't fit in the width of the member.
+/// The names come after any access marker, and the items have the same visibility as the member.
+/// The constants describe the raw integer before any `reverse_bits`,
+/// so that code that manipulates the raw value directly, such as a read-modify-write
+/// of a memory-mapped register, can share the declaration.
+///
+/// ```ignore
+/// bitfields!(
+///     SampleBitField: u16 {
+///         pub x: 4,
+///         pub y: 8 => [Y_MASK, Y_SHIFT, with_y, try_with_y],
+///         padding: 1,
+///         pub z: 3 => [Z_MASK, Z_SHIFT, with_z, try_with_z],
+///     }
+/// );
+///
+/// assert_eq!(SampleBitField::Y_MASK, 0x0FF0);
+/// assert_eq!(SampleBitField::Y_SHIFT, 4);
+/// let value = SampleBitField::default().with_y(0x12).with_z(3);
+/// assert!(value.try_with_z(8).is_none());
+/// ```
+///
+/// # Fixed and parity bits
+///
+/// Registers often have bits that must hold a given value, or a bit that holds their parity.
+/// Such members are declared with a marker after the bit length,
+/// and their type in the generated structure is [`Computed`], which keeps the unpacked value
+/// but is ignored when packing:
+///
+/// - `=> fixed(value)`: the member is always packed as `value`.
+/// - `=> parity(even)` / `=> parity(odd)`: the member is 1 bit wide, and is packed so that
+///   the number of set bits in the whole packed integer is even / odd.
+///
+/// A fixed value that doesn't fit in the width of its member, or a parity member
+/// of another width, fails the build:
+///
+/// ```compile_fail
+/// use byte_struct::*;
+///
+/// bitfields!(
+///     Register: u8 {
+///         reserved: 2 => fixed(0b101),
+///         value: 6,
+///     }
+/// );
+/// ```
+///
+/// `read_bytes_default_*` unpacks these members as they are,
+/// while `try_read_bytes_default_*` checks them, returning [`ErrorKind::BadMagic`]
+/// if a fixed member doesn't hold its value, and [`ErrorKind::ValidationFailed`]
+/// if the parity doesn't match.
+///
+/// ```ignore
+/// bitfields!(
+///     ControlRegister: u8 {
+///         pub mode: 3,
+///         reserved: 3 => fixed(0b101),
+///         pub enable: 1,
+///         parity: 1 => parity(odd),
+///     }
+/// );
+///
+/// let control = ControlRegister {
+///     mode: 2,
+///     reserved: Computed::default(),
+///     enable: 1,
+///     parity: Computed::default(),
+/// };
+/// ```
+///
+/// [`Computed`]: struct.Computed.html
+/// [`ErrorKind::BadMagic`]: enum.ErrorKind.html#variant.BadMagic
+/// [`ErrorKind::ValidationFailed`]: enum.ErrorKind.html#variant.ValidationFailed
+///
+/// # Bit order
+///
+/// Some links transmit the least significant bit first, and their datasheets number the bits
+/// in the order they are sent. Appending `=> reverse_bits` after the base type reverses the order
+/// of the bits of the base integer when packing and unpacking, so that members are declared
+/// starting from the most significant bit of the packed integer instead.
+/// The byte order still applies to the reversed integer as usual.
+/// Only packing is affected; the raw bits of [`BitFieldStruct`] are in the declared order.
+/// It can be combined with `partial` as `=> reverse_bits => partial`.
+///
+/// ```ignore
+/// bitfields!(
+///     // `start` is the most significant bit of the packed byte
+///     Command: u8 => reverse_bits {
+///         pub start: 1,
+///         pub opcode: 3,
+///         pub address: 4,
+///     }
+/// );
+/// ```
+///
+/// [`BitFieldStruct`]: trait.BitFieldStruct.html
+///
+/// # Reserved registers
+///
+/// A structure can be declared without any members, for registers that are documented but unused.
+/// It ignores all bits when unpacking and packs as zero.
+///
+/// ```ignore
+/// bitfields!(
+///     ReservedRegister: u32 {}
+/// );
+/// ```
+///
+/// # Round trip test
+///
+/// Under `cfg(test)`, the generated structure has an associated function `assert_round_trip()`,
+/// which asserts that every raw value of the base type packs back to itself after being unpacked.
+/// Partial structures never pass, as their high bits are dropped.
+/// Calling it from a test is opt-in:
+///
+/// ```ignore
+/// #[test]
+/// fn sample_bit_field_round_trip() {
+///     SampleBitField::assert_round_trip();
+/// }
+/// ```
 #[macro_export]
 macro_rules! bitfields{
+    (@field_ty $base:ty) => { $base };
+    (@field_ty $base:ty, ro) => { $crate::ReadOnly<$base> };
+    (@field_ty $base:ty, wo) => { $crate::WriteOnly<$base> };
+    (@field_ty $base:ty, w1c) => { $crate::WriteOneToClear<$base> };
+    (@field_ty $base:ty, fixed($($value:tt)*)) => { $crate::Computed<$base> };
+    (@field_ty $base:ty, parity($parity:ident)) => { $crate::Computed<$base> };
+    (@field_ty $base:ty, nested($nested:ty)) => { $nested };
+    (@pack_fixed $raw:ident, $mask:ident, $pos:ident, fixed($value:expr)) => {
+        $raw |= (($value) & $mask).checked_shl($pos).unwrap_or(0);
+    };
+    (@pack_fixed $raw:ident, $mask:ident, $pos:ident $($marker:tt)*) => {};
+    (@pack_parity $base:ty, $raw:ident, $pos:ident, parity($parity:ident)) => {
+        let odd = $raw.count_ones() % 2 == 1;
+        let bit: $base = if odd == $crate::bitfields!(@parity_odd $parity) { 0 } else { 1 };
+        $raw |= bit.checked_shl($pos).unwrap_or(0);
+    };
+    (@pack_parity $base:ty, $raw:ident, $pos:ident $($marker:tt)*) => {};
+    (@parity_odd even) => { false };
+    (@parity_odd odd) => { true };
+    (@check_fixed $raw:ident, $mask:ident, $pos:ident, fixed($value:expr)) => {
+        if $raw.checked_shr($pos).unwrap_or(0) & $mask != ($value) & $mask {
+            return Err($crate::ErrorKind::BadMagic);
+        }
+    };
+    (@check_fixed $raw:ident, $mask:ident, $pos:ident $($marker:tt)*) => {};
+    (@check_parity $raw:ident, parity($parity:ident)) => {
+        if ($raw.count_ones() % 2 == 1) != $crate::bitfields!(@parity_odd $parity) {
+            return Err($crate::ErrorKind::ValidationFailed);
+        }
+    };
+    (@check_parity $raw:ident $($marker:tt)*) => {};
+    (@check_width $base:ty, $field_len:expr, fixed($value:expr)) => {
+        // The value is checked before it is cast to the base type, which could truncate it
+        const _: () = {
+            let width = if $field_len < <$base>::BITS { $field_len } else { <$base>::BITS };
+            let fits = match (($value) as u128).checked_shr(width) {
+                Some(rest) => rest == 0,
+                None => true,
+            };
+            assert!(fits, "Fixed value doesn't fit in the width of the member");
+        };
+    };
+    (@check_width $base:ty, $field_len:expr, parity($parity:ident)) => {
+        const _: () = assert!($field_len == 1, "Parity members must be 1 bit wide");
+    };
+    (@check_width $base:ty, $field_len:expr, nested($nested:ty)) => {
+        const _: () = assert!(
+            <$nested as $crate::BitFieldStruct>::BITS <= $field_len,
+            "Nested bit field structure doesn't fit in the width of the member"
+        );
+    };
+    (@check_width $base:ty, $field_len:expr $(, $($marker:tt)*)?) => {};
+    (@consts $name:ident, $base:ty, $pos:expr;) => {};
+    (
+        @consts $name:ident, $base:ty, $pos:expr;
+        ($field_vis:vis, $field_name:ident, $field_len:expr, $field_ty:ty, [$($names:ident),*]) $($rest:tt)*
+    ) => {
+        $crate::bitfields!(@items $name, $base, $pos, $field_vis, $field_name, $field_len, $field_ty; $($names),*);
+        $crate::bitfields!(@consts $name, $base, $pos + $field_len; $($rest)*);
+    };
+    (@items $name:ident, $base:ty, $pos:expr, $field_vis:vis, $field_name:ident, $field_len:expr, $field_ty:ty;) => {};
+    (
+        @items $name:ident, $base:ty, $pos:expr, $field_vis:vis, $field_name:ident, $field_len:expr, $field_ty:ty;
+        $mask:ident, $shift:ident $(, $with:ident, $try_with:ident)?
+    ) => {
+        impl $name {
+            #[doc = concat!("The bits of `", stringify!($field_name), "` in the raw integer")]
+            #[allow(dead_code)]
+            $field_vis const $mask: $base =
+                match <$base>::MAX.checked_shr(<$base>::BITS - $field_len) {
+                    Some(mask) => match mask.checked_shl($pos) {
+                        Some(mask) => mask,
+                        None => 0,
+                    },
+                    None => 0,
+                };
+            #[doc = concat!("The position of the least significant bit of `",
+                stringify!($field_name), "` in the raw integer")]
+            #[allow(dead_code)]
+            $field_vis const $shift: u32 = $pos;
+            $(
+                #[doc = concat!("Returns the structure with `", stringify!($field_name),
+                    "` replaced by `value`")]
+                #[allow(dead_code)]
+                $field_vis fn $with(mut self, value: $field_ty) -> Self {
+                    self.$field_name = value;
+                    self
+                }
+
+                #[doc = concat!("Returns the structure with `", stringify!($field_name),
+                    "` replaced by `value`, or `None` if `value` doesn't fit in the width of `",
+                    stringify!($field_name), "`")]
+                #[allow(dead_code)]
+                $field_vis fn $try_with(mut self, value: $field_ty) -> Option<Self> {
+                    let mask = Self::$mask.checked_shr($pos).unwrap_or(0);
+                    if $crate::BitFieldValue::<$base>::to_bits(&value, mask) & !mask != 0 {
+                        return None;
+                    }
+                    self.$field_name = value;
+                    Some(self)
+                }
+            )?
+        }
+    };
+    (@bit_order $raw:expr;) => { $raw };
+    (@bit_order $raw:expr; reverse_bits $($option:ident)*) => { $raw.reverse_bits() };
+    (@bit_order $raw:expr; partial $($option:ident)*) => {
+        $crate::bitfields!(@bit_order $raw; $($option)*)
+    };
+    (@partial) => { false };
+    (@partial partial $($option:ident)*) => { true };
+    (@partial reverse_bits $($option:ident)*) => { $crate::bitfields!(@partial $($option)*) };
     (
         $(#[$outer:meta])*
-        $visibility:vis $name:ident : $base:ty {
+        $visibility:vis $name:ident : $base:ty $(=> $option:ident)* {
             $(
                 $(#[$inner:ident $($args:tt)*])*
                 $field_vis:vis $field_name:ident : $field_len:expr
-            ),+ $(,)?
+                    $(=> $access:ident $(($($access_arg:tt)*))?)?
+                    $(=> [$($names:ident),* $(,)?])?
+            ),* $(,)?
         }
     ) => {
         $(#[$outer])*
         $visibility struct $name {
             $(
                 $(#[$inner $($args)*])*
-                $field_vis $field_name: $base
+                $field_vis $field_name: $crate::bitfields!(@field_ty $base $(, $access $(($($access_arg)*))?)?)
             ),*
         }
 
         impl $name {
-            #[allow(unused_assignments)]
-            fn from_raw(raw: $base) -> $name {
+            /// Creates the structure from its members,
+            /// or returns `None` if a value doesn't fit in the width of its member
+            #[allow(clippy::too_many_arguments)]
+            $visibility fn new(
+                $($field_name: $crate::bitfields!(@field_ty $base $(, $access $(($($access_arg)*))?)?)),*
+            ) -> Option<$name> {
+                $(
+                    let mask: $base = <$base>::MAX.checked_shr(<$base>::BITS - $field_len).unwrap_or(0);
+                    if $crate::BitFieldValue::<$base>::to_bits(&$field_name, mask) & !mask != 0 {
+                        return None;
+                    }
+                )*
+                Some($name{$($field_name),*})
+            }
+            /// Unpacks the members from the raw integer, without checking fixed or parity bits
+            // Shifts are checked so that members may be zero bits wide or as wide as the base type
+            #[allow(unused_assignments, unused_mut, unused_variables)]
+            $visibility fn from_raw(raw: $base) -> $name {
                 let mut raw_v = raw;
                 $(
-                    let mask: $base = (1 << $field_len) - 1;
-                    let $field_name = raw_v & mask;
-                    raw_v >>= $field_len;
+                    let mask: $base = <$base>::MAX.checked_shr(<$base>::BITS - $field_len).unwrap_or(0);
+                    let $field_name = $crate::BitFieldValue::<$base>::from_bits(raw_v & mask);
+                    raw_v = raw_v.checked_shr($field_len).unwrap_or(0);
                 )*
                 $name{$($field_name),*}
             }
-            #[allow(unused_assignments)]
-            fn to_raw(&self) -> $base {
+            /// Packs the members into the raw integer.
+            ///
+            /// Bits of a member beyond its width are discarded.
+            #[allow(unused_assignments, unused_mut)]
+            $visibility fn to_raw(&self) -> $base {
                 let mut raw: $base = 0;
-                let mut pos = 0;
+                let mut pos: u32 = 0;
+                $(
+                    let mask: $base = <$base>::MAX.checked_shr(<$base>::BITS - $field_len).unwrap_or(0);
+                    let bits = $crate::BitFieldValue::<$base>::to_bits(&self.$field_name, mask);
+                    raw |= (bits & mask).checked_shl(pos).unwrap_or(0);
+                    pos += $field_len;
+                )*
+                // Parity covers the fixed bits, so they are packed first
+                pos = 0;
                 $(
-                    raw |= self.$field_name << pos;
+                    let mask: $base = <$base>::MAX.checked_shr(<$base>::BITS - $field_len).unwrap_or(0);
+                    $crate::bitfields!(@pack_fixed raw, mask, pos $(, $access $(($($access_arg)*))?)?);
+                    pos += $field_len;
+                )*
+                pos = 0;
+                $(
+                    $crate::bitfields!(@pack_parity $base, raw, pos $(, $access $(($($access_arg)*))?)?);
                     pos += $field_len;
                 )*
                 raw
             }
+            // Checks the bits of fixed and parity members
+            #[allow(unused_assignments, unused_mut, unused_variables)]
+            fn check_raw(raw: $base) -> Result<(), $crate::ErrorKind> {
+                let mut pos: u32 = 0;
+                $(
+                    let mask: $base = <$base>::MAX.checked_shr(<$base>::BITS - $field_len).unwrap_or(0);
+                    $crate::bitfields!(@check_fixed raw, mask, pos $(, $access $(($($access_arg)*))?)?);
+                    $crate::bitfields!(@check_parity raw $(, $access $(($($access_arg)*))?)?);
+                    pos += $field_len;
+                )*
+                Ok(())
+            }
+
+            /// Asserts that packing an unpacked raw value gives back the same raw value.
+            ///
+            /// Bits of members with access restrictions or computed by the structure
+            /// are not expected to round trip, nor are any bits of a structure declared without members.
+            /// All raw values are checked for base types up to 16 bits,
+            /// and a fixed set of samples is checked for wider base types.
+            #[cfg(test)]
+            #[allow(dead_code, unused_assignments, unused_mut)]
+            $visibility fn assert_round_trip() {
+                let mut keep: $base = if <$name as $crate::BitFieldStruct>::BITS == 0 { 0 } else { !0 };
+                let mut pos: u32 = 0;
+                $(
+                    let mask: $base = <$base>::MAX.checked_shr(<$base>::BITS - $field_len).unwrap_or(0);
+                    let all_ones = <$crate::bitfields!(@field_ty $base $(, $access $(($($access_arg)*))?)?)
+                        as $crate::BitFieldValue<$base>>::from_bits(mask);
+                    if $crate::BitFieldValue::<$base>::to_bits(&all_ones, mask) != mask {
+                        keep &= !mask.checked_shl(pos).unwrap_or(0);
+                    }
+                    pos += $field_len;
+                )*
+                let check = |raw: $base| {
+                    assert_eq!(
+                        <$name>::from_raw(raw).to_raw() & keep,
+                        raw & keep,
+                        concat!("Raw value {:#x} doesn't round trip through ", stringify!($name)),
+                        raw
+                    );
+                };
+                if <$base>::BITS <= 16 {
+                    for raw in 0 ..= <$base>::MAX {
+                        check(raw);
+                    }
+                } else {
+                    check(<$base>::MAX);
+                    for bit in 0 .. <$base>::BITS {
+                        check(1 << bit);
+                    }
+                    let mut state: u128 = 0x9e3779b97f4a7c15f39cc0605cedc834;
+                    for _ in 0 .. 0x10000 {
+                        state ^= state << 13;
+                        state ^= state >> 7;
+                        state ^= state << 17;
+                        check(state as $base);
+                    }
+                }
+            }
+        }
+
+        impl $crate::ByteStructDebugJson for $name {
+            fn write_debug_json<W: ::core::fmt::Write + ?Sized>(&self, out: &mut W) -> ::core::fmt::Result {
+                out.write_char('{')?;
+                let mut first = true;
+                $(
+                    if !first {
+                        out.write_char(',')?;
+                    }
+                    out.write_str(concat!("\"", stringify!($field_name), "\":"))?;
+                    $crate::ByteStructDebugJson::write_debug_json(&self.$field_name, out)?;
+                    first = false;
+                )*
+                let _ = first;
+                out.write_char('}')
+            }
+        }
+
+        $crate::bitfields!(@consts $name, $base, 0; $((
+            $field_vis, $field_name, $field_len, $crate::bitfields!(@field_ty $base $(, $access $(($($access_arg)*))?)?),
+            [$($($names),*)?]
+        ))*);
+
+        // Structures without members are reserved registers, which leave all bits unused
+        const _: () = $crate::__private::check_bit_len(
+            0 $(+ $field_len)*,
+            <$base>::BITS,
+            $crate::bitfields!(@partial $($option)*) || {
+                let members: &[&str] = &[$(stringify!($field_name)),*];
+                members.is_empty()
+            },
+        );
+        $(
+            $crate::bitfields!(@check_width $base, $field_len $(, $access $(($($access_arg)*))?)?);
+        )*
+
+        impl<B: $crate::RawBits> $crate::BitFieldValue<B> for $name {
+            fn from_bits(bits: B) -> Self {
+                <$name as $crate::BitFieldStruct>::from_raw_u128(bits.to_u128())
+            }
+            fn to_bits(&self, _mask: B) -> B {
+                B::from_u128(<$name as $crate::BitFieldStruct>::to_raw_u128(self))
+            }
+        }
+
+        impl $crate::BitFieldStruct for $name {
+            const BITS: u32 = 0 $(+ $field_len)*;
+            fn to_raw_u128(&self) -> u128 {
+                self.to_raw() as u128
+            }
+            fn from_raw_u128(raw: u128) -> Self {
+                <$name>::from_raw(raw as $base)
+            }
         }
 
-        impl ByteStructLen for $name {
-            const BYTE_LEN: usize = <$base>::BYTE_LEN;
+        impl $crate::ByteStructLen for $name {
+            const BYTE_LEN: usize = <$base as $crate::ByteStructLen>::BYTE_LEN;
         }
 
-        impl ByteStructUnspecifiedByteOrder for $name {
+        impl $crate::ByteStructUnspecifiedByteOrder for $name {
             fn write_bytes_default_le(&self, bytes: &mut [u8]) {
-                self.to_raw().write_bytes_default_le(bytes);
+                let raw = $crate::bitfields!(@bit_order self.to_raw() ; $($option)*);
+                $crate::ByteStructUnspecifiedByteOrder::write_bytes_default_le(&raw, bytes);
             }
             fn read_bytes_default_le(bytes: &[u8]) -> Self {
-                <$name>::from_raw(<$base>::read_bytes_default_le(bytes))
+                let raw = <$base as $crate::ByteStructUnspecifiedByteOrder>::read_bytes_default_le(bytes);
+                <$name>::from_raw($crate::bitfields!(@bit_order raw ; $($option)*))
             }
             fn write_bytes_default_be(&self, bytes: &mut [u8]) {
-                self.to_raw().write_bytes_default_be(bytes);
+                let raw = $crate::bitfields!(@bit_order self.to_raw() ; $($option)*);
+                $crate::ByteStructUnspecifiedByteOrder::write_bytes_default_be(&raw, bytes);
             }
             fn read_bytes_default_be(bytes: &[u8]) -> Self {
-                <$name>::from_raw(<$base>::read_bytes_default_be(bytes))
+                let raw = <$base as $crate::ByteStructUnspecifiedByteOrder>::read_bytes_default_be(bytes);
+                <$name>::from_raw($crate::bitfields!(@bit_order raw ; $($option)*))
+            }
+            fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, $crate::ByteStructError> {
+                let raw = <$base as $crate::ByteStructUnspecifiedByteOrder>::try_read_bytes_default_le(bytes)?;
+                let raw = $crate::bitfields!(@bit_order raw ; $($option)*);
+                <$name>::check_raw(raw).map_err(|kind| $crate::ByteStructError::new(kind, None, 0))?;
+                Ok(<$name>::from_raw(raw))
+            }
+            fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, $crate::ByteStructError> {
+                let raw = <$base as $crate::ByteStructUnspecifiedByteOrder>::try_read_bytes_default_be(bytes)?;
+                let raw = $crate::bitfields!(@bit_order raw ; $($option)*);
+                <$name>::check_raw(raw).map_err(|kind| $crate::ByteStructError::new(kind, None, 0))?;
+                Ok(<$name>::from_raw(raw))
             }
         }
     }
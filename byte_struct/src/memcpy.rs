@@ -0,0 +1,148 @@
+//! Fields packed by copying their memory, for types from `bytemuck` and `zerocopy`.
+
+use crate::{ByteStructDebugJson, ByteStructLen, ByteStructRead, ByteStructWrite};
+use core::fmt::{Result as FmtResult, Write};
+
+fn write_json_bytes<W: Write + ?Sized>(bytes: &[u8], out: &mut W) -> FmtResult {
+    out.write_char('[')?;
+    for (i, byte) in bytes.iter().enumerate() {
+        if i != 0 {
+            out.write_char(',')?;
+        }
+        write!(out, "{}", byte)?;
+    }
+    out.write_char(']')
+}
+
+/// A field of a `bytemuck::Pod` type, packed as a copy of its memory.
+///
+/// The packed bytes are exactly the bytes of the value in memory, so this only matches
+/// the wire format if the type is laid out as the format is, including its byte order.
+/// For example, plain integers are packed in the byte order of the target,
+/// while a `#[repr(C)]` struct of byte arrays is packed the same on every target.
+/// Byte order attributes have no effect on this field.
+///
+/// This skips unpacking field by field for performance-critical blobs inside a larger structure.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Packet {
+///     kind: u16,
+///     payload: Bytemuck<[[u8; 4]; 2]>,
+/// }
+///
+/// let packet = Packet::read_bytes(&[1, 0, 1, 2, 3, 4, 5, 6, 7, 8]);
+/// assert_eq!(packet.payload.0, [[1, 2, 3, 4], [5, 6, 7, 8]]);
+/// assert_eq!(packet.payload.as_wire_bytes(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+/// ```
+#[cfg(feature = "bytemuck")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Bytemuck<T>(pub T);
+
+#[cfg(feature = "bytemuck")]
+impl<T: bytemuck::Pod> Bytemuck<T> {
+    /// Returns the packed bytes of the value, which are its bytes in memory
+    pub fn as_wire_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(&self.0)
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+impl<T: bytemuck::Pod> ByteStructLen for Bytemuck<T> {
+    const BYTE_LEN: usize = core::mem::size_of::<T>();
+}
+
+#[cfg(feature = "bytemuck")]
+impl<T: bytemuck::Pod> ByteStructWrite for Bytemuck<T> {
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        bytes[..Self::BYTE_LEN].copy_from_slice(self.as_wire_bytes());
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+impl<T: bytemuck::Pod> ByteStructRead for Bytemuck<T> {
+    fn read_bytes(bytes: &[u8]) -> Self {
+        Bytemuck(bytemuck::pod_read_unaligned(&bytes[..Self::BYTE_LEN]))
+    }
+}
+
+/// Written as an array of the packed bytes
+#[cfg(feature = "bytemuck")]
+impl<T: bytemuck::Pod> ByteStructDebugJson for Bytemuck<T> {
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> FmtResult {
+        write_json_bytes(self.as_wire_bytes(), out)
+    }
+}
+
+/// A field of a type implementing `zerocopy::FromBytes` and `zerocopy::IntoBytes`,
+/// packed as a copy of its memory.
+///
+/// See [`Bytemuck`](struct.Bytemuck.html) for how the packed bytes relate to the wire format.
+/// The byte order aware integers of `zerocopy::byteorder`, such as `U32<BigEndian>`,
+/// are packed the same on every target.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+/// use zerocopy::byteorder::{BigEndian, U32};
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Packet {
+///     kind: u16,
+///     values: Zerocopy<[U32<BigEndian>; 2]>,
+/// }
+///
+/// let packet = Packet::read_bytes(&[1, 0, 0, 0, 0, 1, 0, 0, 0, 2]);
+/// assert_eq!(packet.values.0[1].get(), 2);
+/// ```
+#[cfg(feature = "zerocopy")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Zerocopy<T>(pub T);
+
+#[cfg(feature = "zerocopy")]
+impl<T: zerocopy::IntoBytes + zerocopy::Immutable> Zerocopy<T> {
+    /// Returns the packed bytes of the value, which are its bytes in memory
+    pub fn as_wire_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+#[cfg(feature = "zerocopy")]
+impl<T: zerocopy::FromBytes + zerocopy::IntoBytes + zerocopy::Immutable> ByteStructLen
+    for Zerocopy<T>
+{
+    const BYTE_LEN: usize = core::mem::size_of::<T>();
+}
+
+#[cfg(feature = "zerocopy")]
+impl<T: zerocopy::FromBytes + zerocopy::IntoBytes + zerocopy::Immutable> ByteStructWrite
+    for Zerocopy<T>
+{
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        bytes[..Self::BYTE_LEN].copy_from_slice(self.as_wire_bytes());
+    }
+}
+
+#[cfg(feature = "zerocopy")]
+impl<T: zerocopy::FromBytes + zerocopy::IntoBytes + zerocopy::Immutable> ByteStructRead
+    for Zerocopy<T>
+{
+    fn read_bytes(bytes: &[u8]) -> Self {
+        Zerocopy(T::read_from_bytes(&bytes[..Self::BYTE_LEN]).ok().unwrap())
+    }
+}
+
+/// Written as an array of the packed bytes
+#[cfg(feature = "zerocopy")]
+impl<T: zerocopy::FromBytes + zerocopy::IntoBytes + zerocopy::Immutable> ByteStructDebugJson
+    for Zerocopy<T>
+{
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> FmtResult {
+        write_json_bytes(self.as_wire_bytes(), out)
+    }
+}
@@ -0,0 +1,126 @@
+//! Unpacking of whichever version of a structure is stored, upgraded to the latest version.
+
+use crate::{ByteStructError, ByteStructRead, ErrorKind};
+use alloc::collections::BTreeMap;
+
+type ReadFn<L> = fn(&[u8]) -> Result<L, ByteStructError>;
+
+/// A table of the versions of a structure, which unpacks the version given by a header
+/// and upgrades it to the latest version `L`.
+///
+/// Each version is a structure that starts with the header `H`,
+/// from which the version number is taken by the function given to [`new`](#method.new).
+/// Versions are upgraded with `From` implementations supplied by the user, either directly to `L`
+/// with [`register`](#method.register), or through the next version
+/// with [`register_via`](#method.register_via), so that each upgrade only covers one step.
+///
+/// Unpacking a version that isn't registered fails with `ErrorKind::InvalidValue`.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Header {
+///     version: u16,
+/// }
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct ConfigV1 {
+///     header: Header,
+///     volume: u8,
+/// }
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct ConfigV2 {
+///     header: Header,
+///     volume: u8,
+///     balance: i8,
+/// }
+///
+/// impl From<ConfigV1> for ConfigV2 {
+///     fn from(v1: ConfigV1) -> Self {
+///         ConfigV2 { header: Header { version: 2 }, volume: v1.volume, balance: 0 }
+///     }
+/// }
+///
+/// let mut migrator: Migrator<Header, ConfigV2> = Migrator::new(|header| header.version.into());
+/// migrator.register::<ConfigV1>(1).register::<ConfigV2>(2);
+///
+/// let config = migrator.read_any(&[1, 0, 80]).unwrap();
+/// assert_eq!(config, ConfigV2 { header: Header { version: 2 }, volume: 80, balance: 0 });
+/// assert!(migrator.read_any(&[3, 0, 80, 0]).is_err());
+/// ```
+pub struct Migrator<H, L> {
+    version: fn(&H) -> u32,
+    entries: BTreeMap<u32, (usize, ReadFn<L>)>,
+}
+
+impl<H: ByteStructRead, L> Migrator<H, L> {
+    /// Creates a migrator with no versions registered,
+    /// which gets the version number from the header with `version`
+    pub fn new(version: fn(&H) -> u32) -> Self {
+        Migrator {
+            version,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Registers type `V` as `version`, upgraded to the latest version with `From`.
+    ///
+    /// Panics if another type is already registered as the same version.
+    pub fn register<V: ByteStructRead + Into<L>>(&mut self, version: u32) -> &mut Self {
+        fn read<V: ByteStructRead + Into<L>, L>(bytes: &[u8]) -> Result<L, ByteStructError> {
+            Ok(V::try_read_bytes(bytes)?.into())
+        }
+        self.insert(version, V::BYTE_LEN, read::<V, L>)
+    }
+
+    /// Registers type `V` as `version`, upgraded to `N` with `From`,
+    /// and then from `N` to the latest version with `From`.
+    ///
+    /// Panics if another type is already registered as the same version.
+    pub fn register_via<V, N>(&mut self, version: u32) -> &mut Self
+    where
+        V: ByteStructRead + Into<N>,
+        N: Into<L>,
+    {
+        fn read<V: ByteStructRead + Into<N>, N: Into<L>, L>(
+            bytes: &[u8],
+        ) -> Result<L, ByteStructError> {
+            Ok(V::try_read_bytes(bytes)?.into().into())
+        }
+        self.insert(version, V::BYTE_LEN, read::<V, N, L>)
+    }
+
+    /// Returns the packed length of the type registered as `version`
+    pub fn byte_len(&self, version: u32) -> Option<usize> {
+        self.entries.get(&version).map(|(len, _)| *len)
+    }
+
+    /// Returns the version number given by the header at the start of `bytes`
+    pub fn version_of(&self, bytes: &[u8]) -> Result<u32, ByteStructError> {
+        Ok((self.version)(&H::try_read_bytes(bytes)?))
+    }
+
+    /// Unpacks the version given by the header at the start of `bytes`,
+    /// and upgrades it to the latest version
+    pub fn read_any(&self, bytes: &[u8]) -> Result<L, ByteStructError> {
+        match self.entries.get(&self.version_of(bytes)?) {
+            Some((_, read)) => read(bytes),
+            None => Err(ByteStructError::new(ErrorKind::InvalidValue, None, 0)),
+        }
+    }
+
+    fn insert(&mut self, version: u32, len: usize, read: ReadFn<L>) -> &mut Self {
+        assert!(
+            self.entries.insert(version, (len, read)).is_none(),
+            "Version {} is already registered",
+            version
+        );
+        self
+    }
+}
@@ -0,0 +1,210 @@
+//! Packing of network addresses.
+//!
+//! Addresses are packed in network byte order, the same way they appear in protocol headers,
+//! regardless of the byte order attributes of the containing structure.
+
+use crate::json::write_json_string;
+use crate::{ByteStructDebugJson, ByteStructLen, ByteStructRead, ByteStructWrite};
+use core::fmt;
+use core::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4};
+use core::str::FromStr;
+
+/// A MAC address (EUI-48), packed as its 6 octets in transmission order.
+///
+/// It is displayed and parsed in the usual notation of six hexadecimal octets,
+/// such as `01:23:45:67:89:ab`. Parsing also accepts `-` as the separator.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// let addr: MacAddr = "01:00:5e:00:00:fb".parse().unwrap();
+/// assert!(addr.is_multicast());
+/// assert_eq!(addr.to_string(), "01:00:5e:00:00:fb");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct MacAddr(pub [u8; 6]);
+
+impl MacAddr {
+    /// The broadcast address `ff:ff:ff:ff:ff:ff`
+    pub const BROADCAST: MacAddr = MacAddr([0xff; 6]);
+
+    /// Creates an address from its octets
+    pub const fn new(octets: [u8; 6]) -> Self {
+        MacAddr(octets)
+    }
+
+    /// Returns the octets of the address
+    pub const fn octets(&self) -> [u8; 6] {
+        self.0
+    }
+
+    /// Returns `true` if this is the broadcast address
+    pub fn is_broadcast(&self) -> bool {
+        *self == MacAddr::BROADCAST
+    }
+
+    /// Returns `true` if the group bit is set, which includes the broadcast address
+    pub fn is_multicast(&self) -> bool {
+        self.0[0] & 0x01 != 0
+    }
+
+    /// Returns `true` if the group bit is clear
+    pub fn is_unicast(&self) -> bool {
+        !self.is_multicast()
+    }
+
+    /// Returns `true` if the locally administered bit is set
+    pub fn is_local(&self) -> bool {
+        self.0[0] & 0x02 != 0
+    }
+}
+
+impl From<[u8; 6]> for MacAddr {
+    fn from(octets: [u8; 6]) -> Self {
+        MacAddr(octets)
+    }
+}
+
+impl From<MacAddr> for [u8; 6] {
+    fn from(addr: MacAddr) -> Self {
+        addr.0
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(f, "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}", a, b, c, d, e, g)
+    }
+}
+
+/// The error returned when parsing a [`MacAddr`](struct.MacAddr.html) fails.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ParseMacAddrError;
+
+impl fmt::Display for ParseMacAddrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid MAC address syntax")
+    }
+}
+
+impl core::error::Error for ParseMacAddrError {}
+
+impl FromStr for MacAddr {
+    type Err = ParseMacAddrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let separator = match s.as_bytes().get(2) {
+            Some(b':') => ':',
+            Some(b'-') => '-',
+            _ => return Err(ParseMacAddrError),
+        };
+        let mut octets = [0; 6];
+        let mut parts = s.split(separator);
+        for octet in &mut octets {
+            let part = parts.next().ok_or(ParseMacAddrError)?;
+            if part.len() != 2 {
+                return Err(ParseMacAddrError);
+            }
+            *octet = u8::from_str_radix(part, 16).map_err(|_| ParseMacAddrError)?;
+        }
+        if parts.next().is_some() {
+            return Err(ParseMacAddrError);
+        }
+        Ok(MacAddr(octets))
+    }
+}
+
+impl ByteStructLen for MacAddr {
+    const BYTE_LEN: usize = 6;
+}
+
+impl ByteStructWrite for MacAddr {
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        bytes[..6].copy_from_slice(&self.0);
+    }
+}
+
+impl ByteStructRead for MacAddr {
+    fn read_bytes(bytes: &[u8]) -> Self {
+        MacAddr(<[u8; 6]>::try_from(&bytes[..6]).unwrap())
+    }
+}
+
+impl ByteStructLen for Ipv4Addr {
+    const BYTE_LEN: usize = 4;
+}
+
+impl ByteStructWrite for Ipv4Addr {
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        bytes[..4].copy_from_slice(&self.octets());
+    }
+}
+
+impl ByteStructRead for Ipv4Addr {
+    fn read_bytes(bytes: &[u8]) -> Self {
+        Ipv4Addr::from(<[u8; 4]>::try_from(&bytes[..4]).unwrap())
+    }
+}
+
+impl ByteStructLen for Ipv6Addr {
+    const BYTE_LEN: usize = 16;
+}
+
+impl ByteStructWrite for Ipv6Addr {
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        bytes[..16].copy_from_slice(&self.octets());
+    }
+}
+
+impl ByteStructRead for Ipv6Addr {
+    fn read_bytes(bytes: &[u8]) -> Self {
+        Ipv6Addr::from(<[u8; 16]>::try_from(&bytes[..16]).unwrap())
+    }
+}
+
+impl ByteStructLen for SocketAddrV4 {
+    const BYTE_LEN: usize = 6;
+}
+
+/// Packed as the address followed by the port
+impl ByteStructWrite for SocketAddrV4 {
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        self.ip().write_bytes(&mut bytes[..4]);
+        bytes[4..6].copy_from_slice(&self.port().to_be_bytes());
+    }
+}
+
+impl ByteStructRead for SocketAddrV4 {
+    fn read_bytes(bytes: &[u8]) -> Self {
+        SocketAddrV4::new(
+            Ipv4Addr::read_bytes(&bytes[..4]),
+            u16::from_be_bytes([bytes[4], bytes[5]]),
+        )
+    }
+}
+
+impl ByteStructDebugJson for Ipv4Addr {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        write_json_string(self, out)
+    }
+}
+
+impl ByteStructDebugJson for Ipv6Addr {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        write_json_string(self, out)
+    }
+}
+
+impl ByteStructDebugJson for MacAddr {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        write_json_string(self, out)
+    }
+}
+
+impl ByteStructDebugJson for SocketAddrV4 {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        write_json_string(self, out)
+    }
+}
@@ -0,0 +1,220 @@
+//! Arrays of values narrower than a byte, packed without padding between them,
+//! including arrays of booleans packed as bits.
+
+use crate::{ByteStructDebugJson, ByteStructLen, ByteStructUnspecifiedByteOrder, RawBits};
+use core::fmt;
+
+/// An array of `N` unsigned integers, each packed as `BITS` bits.
+///
+/// The values are packed one after another as a stream of bits, in which the byte order selects
+/// the bit order:
+/// - with little-endian byte order, the stream starts from the least significant bit of the
+///   first byte, and each value starts from its least significant bit, as in FAT12 tables
+///   and IMA ADPCM samples
+/// - with big-endian byte order, the stream starts from the most significant bit of the
+///   first byte, and each value starts from its most significant bit, as in the pixels of
+///   4-bit images
+///
+/// The packed length is `BITS * N` bits rounded up to whole bytes.
+/// The unused bits of the last byte are packed as zero and ignored when unpacking.
+/// Bits of a value beyond `BITS` are discarded when packing.
+/// `BITS` must be from 1 up to the width of `T`.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Fat12Sector {
+///     entries: PackedArray<u16, 12, 4>,
+/// }
+///
+/// let sector = Fat12Sector::read_bytes(&[0xF8, 0xFF, 0xFF, 0x03, 0x40, 0x00][..]);
+/// assert_eq!(sector.entries.0, [0xFF8, 0xFFF, 0x003, 0x004]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PackedArray<T, const BITS: u32, const N: usize>(pub [T; N]);
+
+impl<T: RawBits, const BITS: u32, const N: usize> Default for PackedArray<T, BITS, N> {
+    fn default() -> Self {
+        PackedArray([T::from_u128(0); N])
+    }
+}
+
+impl<T, const BITS: u32, const N: usize> From<[T; N]> for PackedArray<T, BITS, N> {
+    fn from(values: [T; N]) -> Self {
+        PackedArray(values)
+    }
+}
+
+impl<T: RawBits, const BITS: u32, const N: usize> PackedArray<T, BITS, N> {
+    /// Creates the array, or returns `None` if a value doesn't fit in `BITS` bits
+    pub fn new(values: [T; N]) -> Option<Self> {
+        if values.iter().any(|value| value.to_u128() >> BITS != 0) {
+            return None;
+        }
+        Some(PackedArray(values))
+    }
+
+    fn write_stream(&self, bytes: &mut [u8], msb_first: bool) {
+        let bytes = &mut bytes[..Self::BYTE_LEN];
+        bytes.fill(0);
+        for (i, value) in self.0.iter().enumerate() {
+            let value = value.to_u128();
+            for bit in 0..BITS {
+                if (value >> bit) & 1 == 0 {
+                    continue;
+                }
+                let pos = i * BITS as usize;
+                if msb_first {
+                    let pos = pos + (BITS - 1 - bit) as usize;
+                    bytes[pos / 8] |= 0x80 >> (pos % 8);
+                } else {
+                    let pos = pos + bit as usize;
+                    bytes[pos / 8] |= 1 << (pos % 8);
+                }
+            }
+        }
+    }
+
+    fn read_stream(bytes: &[u8], msb_first: bool) -> Self {
+        let bytes = &bytes[..Self::BYTE_LEN];
+        PackedArray(core::array::from_fn(|i| {
+            let mut value: u128 = 0;
+            for bit in 0..BITS {
+                let pos = i * BITS as usize;
+                let set = if msb_first {
+                    let pos = pos + (BITS - 1 - bit) as usize;
+                    bytes[pos / 8] & (0x80 >> (pos % 8)) != 0
+                } else {
+                    let pos = pos + bit as usize;
+                    bytes[pos / 8] & (1 << (pos % 8)) != 0
+                };
+                value |= u128::from(set) << bit;
+            }
+            T::from_u128(value)
+        }))
+    }
+}
+
+impl<T: RawBits, const BITS: u32, const N: usize> ByteStructLen for PackedArray<T, BITS, N> {
+    const BYTE_LEN: usize = {
+        assert!(
+            BITS > 0 && BITS <= T::BITS,
+            "PackedArray supports from 1 bit up to the width of the element type"
+        );
+        (BITS as usize * N).div_ceil(8)
+    };
+}
+
+impl<T: RawBits, const BITS: u32, const N: usize> ByteStructUnspecifiedByteOrder
+    for PackedArray<T, BITS, N>
+{
+    fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+        self.write_stream(bytes, false);
+    }
+    fn read_bytes_default_le(bytes: &[u8]) -> Self {
+        Self::read_stream(bytes, false)
+    }
+    fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+        self.write_stream(bytes, true);
+    }
+    fn read_bytes_default_be(bytes: &[u8]) -> Self {
+        Self::read_stream(bytes, true)
+    }
+}
+
+impl<T: ByteStructDebugJson, const BITS: u32, const N: usize> ByteStructDebugJson
+    for PackedArray<T, BITS, N>
+{
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        self.0.write_debug_json(out)
+    }
+}
+
+/// An array of `N` booleans, each packed as one bit.
+///
+/// This is packed in the same way as `PackedArray<u8, 1, N>`, with `true` as a set bit.
+/// With little-endian byte order, the first boolean is the least significant bit
+/// of the first byte, and with big-endian byte order, the most significant bit.
+/// The packed length is `N` bits rounded up to whole bytes.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Presence {
+///     present: BitArray<10>,
+/// }
+///
+/// let presence = Presence::read_bytes(&[0b0000_0101, 0b10][..]);
+/// assert!(presence.present.get(0) && presence.present.get(2) && presence.present.get(9));
+/// assert_eq!(presence.present.count_ones(), 3);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BitArray<const N: usize>(pub [bool; N]);
+
+impl<const N: usize> Default for BitArray<N> {
+    fn default() -> Self {
+        BitArray([false; N])
+    }
+}
+
+impl<const N: usize> From<[bool; N]> for BitArray<N> {
+    fn from(bits: [bool; N]) -> Self {
+        BitArray(bits)
+    }
+}
+
+impl<const N: usize> BitArray<N> {
+    /// Returns the boolean at `index`. Panics if `index` is out of bounds
+    pub fn get(&self, index: usize) -> bool {
+        self.0[index]
+    }
+
+    /// Sets the boolean at `index`. Panics if `index` is out of bounds
+    pub fn set(&mut self, index: usize, value: bool) {
+        self.0[index] = value;
+    }
+
+    /// Returns the number of booleans that are `true`
+    pub fn count_ones(&self) -> usize {
+        self.0.iter().filter(|&&bit| bit).count()
+    }
+
+    fn to_packed(self) -> PackedArray<u8, 1, N> {
+        PackedArray(self.0.map(u8::from))
+    }
+
+    fn from_packed(packed: PackedArray<u8, 1, N>) -> Self {
+        BitArray(packed.0.map(|bit| bit != 0))
+    }
+}
+
+impl<const N: usize> ByteStructLen for BitArray<N> {
+    const BYTE_LEN: usize = PackedArray::<u8, 1, N>::BYTE_LEN;
+}
+
+impl<const N: usize> ByteStructUnspecifiedByteOrder for BitArray<N> {
+    fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+        self.to_packed().write_bytes_default_le(bytes);
+    }
+    fn read_bytes_default_le(bytes: &[u8]) -> Self {
+        Self::from_packed(PackedArray::read_bytes_default_le(bytes))
+    }
+    fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+        self.to_packed().write_bytes_default_be(bytes);
+    }
+    fn read_bytes_default_be(bytes: &[u8]) -> Self {
+        Self::from_packed(PackedArray::read_bytes_default_be(bytes))
+    }
+}
+
+impl<const N: usize> ByteStructDebugJson for BitArray<N> {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        self.0.write_debug_json(out)
+    }
+}
@@ -0,0 +1,207 @@
+//! Bounded containers from `heapless` and `arrayvec`, packed at their full capacity.
+
+use crate::json::write_json_escaped;
+use crate::{
+    check_len, ByteStructDebugJson, ByteStructError, ByteStructLen, ByteStructRead,
+    ByteStructUnspecifiedByteOrder, ByteStructWrite, ErrorKind,
+};
+use core::fmt::{Result as FmtResult, Write};
+
+/// A bounded container packed at its full capacity, with the unused space filled with the byte `FILL`.
+///
+/// The container itself doesn't record its length in the packed bytes,
+/// so the fill byte is how the length is recovered when unpacking:
+///
+/// - Strings (`heapless::String<N>` and `arrayvec::ArrayString<N>`) are packed as `N` bytes of UTF-8.
+///   When unpacking, trailing `FILL` bytes are removed. With the default `FILL` of zero,
+///   this is a NUL-padded string, and with `b' '`, a space-padded string.
+///   Unpacking bytes that are not valid UTF-8 panics, or fails with `ErrorKind::InvalidValue`.
+/// - Vectors (`heapless::Vec<T, N>` and `arrayvec::ArrayVec<T, N>`) are packed as `N` elements.
+///   When unpacking, trailing elements whose packed bytes are all `FILL` are removed.
+///
+/// Content that itself ends with what looks like the fill is therefore shortened when unpacked.
+/// Vectors are packed in the same way as arrays, so they need a byte order attribute
+/// if their elements do.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Device {
+///     name: Padded<heapless::String<8>>,
+///     model: Padded<arrayvec::ArrayString<4>, b' '>,
+///     channels: Padded<heapless::Vec<u16, 3>>,
+/// }
+///
+/// let device = Device {
+///     name: Padded(heapless::String::try_from("probe").unwrap()),
+///     model: Padded(arrayvec::ArrayString::from("X1").unwrap()),
+///     channels: Padded(heapless::Vec::from_slice(&[1, 2]).unwrap()),
+/// };
+/// let mut bytes = [0; Device::BYTE_LEN];
+/// device.write_bytes(&mut bytes[..]);
+/// assert_eq!(&bytes[..], b"probe\0\0\0X1  \x01\0\x02\0\0\0");
+/// assert_eq!(Device::read_bytes(&bytes[..]), device);
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Padded<C, const FILL: u8 = 0>(pub C);
+
+impl<C, const FILL: u8> From<C> for Padded<C, FILL> {
+    fn from(value: C) -> Self {
+        Padded(value)
+    }
+}
+
+// Returns the string in `bytes` with trailing fill bytes removed
+fn read_padded_str(bytes: &[u8], fill: u8) -> Result<&str, ByteStructError> {
+    let len = bytes.iter().rposition(|b| *b != fill).map_or(0, |i| i + 1);
+    core::str::from_utf8(&bytes[..len])
+        .map_err(|_| ByteStructError::new(ErrorKind::InvalidValue, None, 0))
+}
+
+fn write_padded_str(value: &str, bytes: &mut [u8], fill: u8) {
+    bytes[..value.len()].copy_from_slice(value.as_bytes());
+    bytes[value.len()..].fill(fill);
+}
+
+// Returns the number of elements before the trailing slots that are entirely fill bytes
+fn filled_slots(bytes: &[u8], slot_len: usize, slots: usize, fill: u8) -> usize {
+    let slot_is_fill = |i: usize| {
+        bytes[i * slot_len..(i + 1) * slot_len]
+            .iter()
+            .all(|b| *b == fill)
+    };
+    (0..slots)
+        .rev()
+        .find(|i| !slot_is_fill(*i))
+        .map_or(0, |i| i + 1)
+}
+
+macro_rules! padded_string_impl {
+    ($feature:literal, $t:ty, $push:ident) => {
+        #[cfg(feature = $feature)]
+        impl<const N: usize, const FILL: u8> ByteStructLen for Padded<$t, FILL> {
+            const BYTE_LEN: usize = N;
+        }
+
+        #[cfg(feature = $feature)]
+        impl<const N: usize, const FILL: u8> ByteStructWrite for Padded<$t, FILL> {
+            fn write_bytes(&self, bytes: &mut [u8]) {
+                write_padded_str(self.0.as_str(), &mut bytes[..N], FILL);
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl<const N: usize, const FILL: u8> ByteStructRead for Padded<$t, FILL> {
+            fn read_bytes(bytes: &[u8]) -> Self {
+                Self::try_read_bytes(bytes).expect("Invalid UTF-8 in padded string")
+            }
+            fn try_read_bytes(bytes: &[u8]) -> Result<Self, ByteStructError> {
+                check_len::<Self>(bytes)?;
+                let mut value = <$t>::new();
+                value.$push(read_padded_str(&bytes[..N], FILL)?).unwrap();
+                Ok(Padded(value))
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl<const N: usize, const FILL: u8> ByteStructDebugJson for Padded<$t, FILL> {
+            fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> FmtResult {
+                write_json_escaped(self.0.as_str(), out)
+            }
+        }
+    };
+}
+
+padded_string_impl!("heapless", heapless::String<N>, push_str);
+padded_string_impl!("arrayvec", arrayvec::ArrayString<N>, try_push_str);
+
+macro_rules! padded_vec_impl {
+    ($feature:literal, $t:ty, $push:ident) => {
+        #[cfg(feature = $feature)]
+        impl<T: ByteStructLen, const N: usize, const FILL: u8> ByteStructLen for Padded<$t, FILL> {
+            const BYTE_LEN: usize = crate::__private::len_mul(N, T::BYTE_LEN);
+        }
+
+        #[cfg(feature = $feature)]
+        impl<T: ByteStructUnspecifiedByteOrder, const N: usize, const FILL: u8>
+            ByteStructUnspecifiedByteOrder for Padded<$t, FILL>
+        {
+            fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+                let len = T::BYTE_LEN;
+                for (i, element) in self.0.iter().enumerate() {
+                    element.write_bytes_default_le(&mut bytes[i * len..(i + 1) * len]);
+                }
+                bytes[self.0.len() * len..N * len].fill(FILL);
+            }
+            fn read_bytes_default_le(bytes: &[u8]) -> Self {
+                let len = T::BYTE_LEN;
+                let mut value = <$t>::new();
+                for i in 0..filled_slots(bytes, len, N, FILL) {
+                    let element = T::read_bytes_default_le(&bytes[i * len..(i + 1) * len]);
+                    value.$push(element).ok().unwrap();
+                }
+                Padded(value)
+            }
+            fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+                let len = T::BYTE_LEN;
+                for (i, element) in self.0.iter().enumerate() {
+                    element.write_bytes_default_be(&mut bytes[i * len..(i + 1) * len]);
+                }
+                bytes[self.0.len() * len..N * len].fill(FILL);
+            }
+            fn read_bytes_default_be(bytes: &[u8]) -> Self {
+                let len = T::BYTE_LEN;
+                let mut value = <$t>::new();
+                for i in 0..filled_slots(bytes, len, N, FILL) {
+                    let element = T::read_bytes_default_be(&bytes[i * len..(i + 1) * len]);
+                    value.$push(element).ok().unwrap();
+                }
+                Padded(value)
+            }
+            fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, ByteStructError> {
+                check_len::<Self>(bytes)?;
+                let len = T::BYTE_LEN;
+                let mut value = <$t>::new();
+                for i in 0..filled_slots(bytes, len, N, FILL) {
+                    let element = T::try_read_bytes_default_le(&bytes[i * len..(i + 1) * len])
+                        .map_err(|e| e.within_element(i, i * len))?;
+                    value.$push(element).ok().unwrap();
+                }
+                Ok(Padded(value))
+            }
+            fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, ByteStructError> {
+                check_len::<Self>(bytes)?;
+                let len = T::BYTE_LEN;
+                let mut value = <$t>::new();
+                for i in 0..filled_slots(bytes, len, N, FILL) {
+                    let element = T::try_read_bytes_default_be(&bytes[i * len..(i + 1) * len])
+                        .map_err(|e| e.within_element(i, i * len))?;
+                    value.$push(element).ok().unwrap();
+                }
+                Ok(Padded(value))
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl<T: ByteStructDebugJson, const N: usize, const FILL: u8> ByteStructDebugJson
+            for Padded<$t, FILL>
+        {
+            fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> FmtResult {
+                out.write_char('[')?;
+                for (i, element) in self.0.iter().enumerate() {
+                    if i != 0 {
+                        out.write_char(',')?;
+                    }
+                    element.write_debug_json(out)?;
+                }
+                out.write_char(']')
+            }
+        }
+    };
+}
+
+padded_vec_impl!("heapless", heapless::Vec<T, N>, push);
+padded_vec_impl!("arrayvec", arrayvec::ArrayVec<T, N>, try_push);
@@ -0,0 +1,143 @@
+//! Statically allocated storage for decoded messages.
+
+use crate::{ByteStructError, ByteStructRead};
+
+/// A handle to an occupied slot of a [`MessagePool`].
+///
+/// The handle is returned when a message is decoded into the pool,
+/// and is consumed when the slot is released.
+/// It must only be used with the pool that returned it.
+///
+/// [`MessagePool`]: struct.MessagePool.html
+#[derive(PartialEq, Eq, Debug)]
+pub struct PoolHandle(usize);
+
+impl PoolHandle {
+    /// Returns the index of the slot in the pool
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// A pool of `N` preallocated slots of `T`, into which incoming messages are decoded in place.
+///
+/// No allocation happens after the pool is created. Decoding a message takes a free slot,
+/// unpacks the bytes into it with [`ByteStructRead::read_bytes_into`], and returns a [`PoolHandle`]
+/// to access the message. The slot is returned to the pool with [`release`](#method.release).
+///
+/// [`ByteStructRead::read_bytes_into`]: trait.ByteStructRead.html#method.read_bytes_into
+/// [`PoolHandle`]: struct.PoolHandle.html
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, Default)]
+/// #[byte_struct_le]
+/// struct Sample {
+///     channel: u8,
+///     value: u16,
+/// }
+///
+/// let mut pool = MessagePool::<Sample, 4>::new();
+/// let handle = pool.decode(&[1, 0x34, 0x12][..]).unwrap();
+/// assert_eq!(pool.get(&handle).value, 0x1234);
+/// assert_eq!(pool.len(), 1);
+/// pool.release(handle);
+/// assert!(pool.is_empty());
+/// ```
+pub struct MessagePool<T, const N: usize> {
+    slots: [T; N],
+    occupied: [bool; N],
+}
+
+impl<T: Default, const N: usize> MessagePool<T, N> {
+    /// Creates a pool whose slots are initialized with `T::default()`
+    pub fn new() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl<T: Default, const N: usize> Default for MessagePool<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> MessagePool<T, N> {
+    /// Creates a pool whose slots are initialized by calling `f` with the index of each slot
+    pub fn from_fn<F: FnMut(usize) -> T>(f: F) -> Self {
+        MessagePool {
+            slots: core::array::from_fn(f),
+            occupied: [false; N],
+        }
+    }
+
+    /// Returns the number of slots in the pool
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the number of occupied slots
+    pub fn len(&self) -> usize {
+        self.occupied.iter().filter(|occupied| **occupied).count()
+    }
+
+    /// Returns `true` if no slot is occupied
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if all slots are occupied
+    pub fn is_full(&self) -> bool {
+        self.len() == N
+    }
+
+    /// Returns the message in an occupied slot
+    pub fn get(&self, handle: &PoolHandle) -> &T {
+        &self.slots[handle.0]
+    }
+
+    /// Returns the message in an occupied slot for modification
+    pub fn get_mut(&mut self, handle: &PoolHandle) -> &mut T {
+        &mut self.slots[handle.0]
+    }
+
+    /// Frees a slot so that it can be reused.
+    ///
+    /// The message is left in the slot until it is overwritten by another message.
+    pub fn release(&mut self, handle: PoolHandle) {
+        self.occupied[handle.0] = false;
+    }
+
+    fn free_slot(&self) -> Option<usize> {
+        self.occupied.iter().position(|occupied| !occupied)
+    }
+}
+
+impl<T: ByteStructRead, const N: usize> MessagePool<T, N> {
+    /// Decodes a message into a free slot.
+    ///
+    /// Returns `None` if all slots are occupied.
+    /// Panics if the slice is too short, in the same way as [`ByteStructRead::read_bytes`].
+    ///
+    /// [`ByteStructRead::read_bytes`]: trait.ByteStructRead.html#tymethod.read_bytes
+    pub fn decode(&mut self, bytes: &[u8]) -> Option<PoolHandle> {
+        let index = self.free_slot()?;
+        self.slots[index].read_bytes_into(bytes);
+        self.occupied[index] = true;
+        Some(PoolHandle(index))
+    }
+
+    /// Decodes a message into a free slot, reporting malformed input as an error.
+    ///
+    /// Returns `Ok(None)` if all slots are occupied. The slot stays free if decoding fails.
+    pub fn try_decode(&mut self, bytes: &[u8]) -> Result<Option<PoolHandle>, ByteStructError> {
+        let Some(index) = self.free_slot() else {
+            return Ok(None);
+        };
+        self.slots[index].try_read_bytes_into(bytes)?;
+        self.occupied[index] = true;
+        Ok(Some(PoolHandle(index)))
+    }
+}
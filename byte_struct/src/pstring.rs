@@ -0,0 +1,154 @@
+//! Length-prefixed strings, stored in a fixed capacity.
+
+use crate::json::{write_bytes_json, write_json_escaped};
+use crate::{
+    check_len, ByteStructDebugJson, ByteStructError, ByteStructLen, ByteStructUnspecifiedByteOrder,
+    ErrorKind, RawBits,
+};
+use core::fmt;
+
+/// A string prefixed with its length of type `L`, followed by `CAP` bytes of storage,
+/// as the `string[CAP]` type of Turbo Pascal.
+///
+/// The packed length is always that of `L` plus `CAP` bytes, whatever the length of the string.
+/// The bytes after the string are packed as zero and ignored when unpacking.
+/// A length larger than `CAP` panics when unpacking, or fails with `ErrorKind::InvalidValue`.
+///
+/// The content is kept as bytes, as Pascal strings are often in a legacy encoding,
+/// and can be viewed as UTF-8 with [`as_str`](#method.as_str).
+/// The length is packed in the byte order of the containing structure.
+/// `CAP` must fit in `L`.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Save {
+///     player: PString8<7>,
+///     level: u8,
+/// }
+///
+/// let save = Save { player: PString8::new(b"Zelda").unwrap(), level: 3 };
+/// let mut bytes = [0; Save::BYTE_LEN];
+/// save.write_bytes(&mut bytes[..]);
+/// assert_eq!(&bytes[..], b"\x05Zelda\0\0\x03");
+/// assert_eq!(Save::read_bytes(&bytes[..]), save);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PString<L, const CAP: usize> {
+    bytes: [u8; CAP],
+    len: L,
+}
+
+/// A string prefixed with its length as one byte. See [`PString`](struct.PString.html).
+pub type PString8<const CAP: usize> = PString<u8, CAP>;
+
+/// A string prefixed with its length as a 16-bit word. See [`PString`](struct.PString.html).
+pub type PString16<const CAP: usize> = PString<u16, CAP>;
+
+impl<L: RawBits, const CAP: usize> PString<L, CAP> {
+    /// Creates a string holding `bytes`, or returns `None` if it is longer than `CAP`
+    pub fn new(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() > CAP {
+            return None;
+        }
+        let mut value = Self::default();
+        value.bytes[..bytes.len()].copy_from_slice(bytes);
+        value.len = L::from_u128(bytes.len() as u128);
+        Some(value)
+    }
+
+    /// Returns the length of the string in bytes
+    pub fn len(&self) -> usize {
+        self.len.to_u128() as usize
+    }
+
+    /// Returns `true` if the string is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bytes of the string
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len()]
+    }
+
+    /// Returns the string as UTF-8, or `None` if it isn't valid UTF-8
+    pub fn as_str(&self) -> Option<&str> {
+        core::str::from_utf8(self.as_bytes()).ok()
+    }
+
+    fn from_parts(len: L, bytes: &[u8]) -> Result<Self, ByteStructError> {
+        let len = len.to_u128();
+        if len > CAP as u128 {
+            return Err(ByteStructError::new(ErrorKind::InvalidValue, None, 0));
+        }
+        Ok(Self::new(&bytes[..len as usize]).unwrap())
+    }
+}
+
+impl<L: RawBits, const CAP: usize> Default for PString<L, CAP> {
+    fn default() -> Self {
+        PString {
+            bytes: [0; CAP],
+            len: L::from_u128(0),
+        }
+    }
+}
+
+impl<L: RawBits, const CAP: usize> fmt::Debug for PString<L, CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.as_str() {
+            Some(s) => write!(f, "PString({:?})", s),
+            None => write!(f, "PString({:?})", self.as_bytes()),
+        }
+    }
+}
+
+impl<L: RawBits + ByteStructLen, const CAP: usize> ByteStructLen for PString<L, CAP> {
+    const BYTE_LEN: usize = {
+        assert!(
+            (CAP as u128) >> L::BITS == 0,
+            "The capacity of PString must fit in its length type"
+        );
+        L::BYTE_LEN + CAP
+    };
+}
+
+impl<L: RawBits + ByteStructUnspecifiedByteOrder, const CAP: usize> ByteStructUnspecifiedByteOrder
+    for PString<L, CAP>
+{
+    fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+        self.len.write_bytes_default_le(&mut bytes[..L::BYTE_LEN]);
+        bytes[L::BYTE_LEN..Self::BYTE_LEN].copy_from_slice(&self.bytes);
+    }
+    fn read_bytes_default_le(bytes: &[u8]) -> Self {
+        Self::try_read_bytes_default_le(bytes).expect("Pascal string longer than its capacity")
+    }
+    fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+        self.len.write_bytes_default_be(&mut bytes[..L::BYTE_LEN]);
+        bytes[L::BYTE_LEN..Self::BYTE_LEN].copy_from_slice(&self.bytes);
+    }
+    fn read_bytes_default_be(bytes: &[u8]) -> Self {
+        Self::try_read_bytes_default_be(bytes).expect("Pascal string longer than its capacity")
+    }
+    fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        Self::from_parts(L::read_bytes_default_le(bytes), &bytes[L::BYTE_LEN..])
+    }
+    fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        Self::from_parts(L::read_bytes_default_be(bytes), &bytes[L::BYTE_LEN..])
+    }
+}
+
+impl<L: RawBits, const CAP: usize> ByteStructDebugJson for PString<L, CAP> {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        match self.as_str() {
+            Some(s) => write_json_escaped(s, out),
+            None => write_bytes_json(self.as_bytes(), out),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Register maps of devices, and access to registers over embedded-hal buses.
+
+#[cfg(feature = "embedded-hal")]
+use crate::{ByteStructError, ByteStructRead, ByteStructWrite};
+#[cfg(feature = "embedded-hal")]
+use core::fmt;
+#[cfg(feature = "embedded-hal")]
+use embedded_hal::{i2c::I2c, spi, spi::SpiDevice};
+
+/// A structure mapped to consecutive registers of a device, starting at register `ADDR`.
+///
+/// This trait can be implemented by
+/// [`#[derive(ByteStruct)]`](https://docs.rs/byte_struct_derive/*/byte_struct_derive/derive.ByteStruct.html)
+/// with `#[byte_struct_register(addr = N)]` on the struct.
+pub trait ByteStructRegister {
+    /// The address of the first register
+    const ADDR: u8;
+}
+
+/// The maximum packed length of a structure accessed with the register functions.
+///
+/// The packed bytes are staged in a buffer on the stack, so longer structures fail the build.
+#[cfg(feature = "embedded-hal")]
+pub const MAX_REGISTER_LEN: usize = 64;
+
+/// An error from accessing registers of a device.
+#[cfg(feature = "embedded-hal")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RegisterError<E> {
+    /// The bus reported an error
+    Bus(E),
+    /// The bytes read from the device failed to unpack
+    Unpack(ByteStructError),
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<E: fmt::Debug> fmt::Display for RegisterError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegisterError::Bus(error) => write!(f, "bus error: {:?}", error),
+            RegisterError::Unpack(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<E: fmt::Debug> core::error::Error for RegisterError<E> {}
+
+#[cfg(feature = "embedded-hal")]
+const fn check_register_len<T: crate::ByteStructLen>() -> usize {
+    assert!(
+        T::BYTE_LEN <= MAX_REGISTER_LEN,
+        "Register structure is longer than MAX_REGISTER_LEN"
+    );
+    T::BYTE_LEN
+}
+
+/// Reads a structure from the registers of the I²C device at `dev_addr`.
+///
+/// The register address is written first, then `BYTE_LEN` bytes are read in the same transaction,
+/// relying on the device incrementing the register address as it is read.
+///
+/// # Example
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct_register(addr = 0x3B, endian = "be")]
+/// struct Accel {
+///     x: i16,
+///     y: i16,
+///     z: i16,
+/// }
+///
+/// let accel: Accel = read_register(&mut i2c, 0x68)?;
+/// ```
+#[cfg(feature = "embedded-hal")]
+pub fn read_register<T, I>(i2c: &mut I, dev_addr: u8) -> Result<T, RegisterError<I::Error>>
+where
+    T: ByteStructRegister + ByteStructRead,
+    I: I2c,
+{
+    let len = const { check_register_len::<T>() };
+    let mut buf = [0; MAX_REGISTER_LEN];
+    i2c.write_read(dev_addr, &[T::ADDR], &mut buf[..len])
+        .map_err(RegisterError::Bus)?;
+    T::try_read_bytes(&buf[..len]).map_err(RegisterError::Unpack)
+}
+
+/// Writes a structure to the registers of the I²C device at `dev_addr`.
+///
+/// The register address and the packed bytes are written in a single write.
+#[cfg(feature = "embedded-hal")]
+pub fn write_register<T, I>(i2c: &mut I, dev_addr: u8, value: &T) -> Result<(), I::Error>
+where
+    T: ByteStructRegister + ByteStructWrite,
+    I: I2c,
+{
+    let len = const { check_register_len::<T>() };
+    let mut buf = [0; MAX_REGISTER_LEN + 1];
+    buf[0] = T::ADDR;
+    value.write_bytes(&mut buf[1..len + 1]);
+    i2c.write(dev_addr, &buf[..len + 1])
+}
+
+/// Reads a structure from the registers of an SPI device.
+///
+/// The register address is sent with the most significant bit set to indicate a read,
+/// which is the convention of most SPI sensors, followed by `BYTE_LEN` bytes read.
+#[cfg(feature = "embedded-hal")]
+pub fn read_register_spi<T, S>(spi: &mut S) -> Result<T, RegisterError<S::Error>>
+where
+    T: ByteStructRegister + ByteStructRead,
+    S: SpiDevice,
+{
+    let len = const { check_register_len::<T>() };
+    let mut buf = [0; MAX_REGISTER_LEN];
+    spi.transaction(&mut [
+        spi::Operation::Write(&[T::ADDR | 0x80]),
+        spi::Operation::Read(&mut buf[..len]),
+    ])
+    .map_err(RegisterError::Bus)?;
+    T::try_read_bytes(&buf[..len]).map_err(RegisterError::Unpack)
+}
+
+/// Writes a structure to the registers of an SPI device.
+///
+/// The register address is sent with the most significant bit cleared to indicate a write,
+/// followed by the packed bytes.
+#[cfg(feature = "embedded-hal")]
+pub fn write_register_spi<T, S>(spi: &mut S, value: &T) -> Result<(), S::Error>
+where
+    T: ByteStructRegister + ByteStructWrite,
+    S: SpiDevice,
+{
+    let len = const { check_register_len::<T>() };
+    let mut buf = [0; MAX_REGISTER_LEN + 1];
+    buf[0] = T::ADDR & 0x7F;
+    value.write_bytes(&mut buf[1..len + 1]);
+    spi.write(&buf[..len + 1])
+}
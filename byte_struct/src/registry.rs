@@ -0,0 +1,174 @@
+//! Numeric tags identifying message structures, and dispatch of messages by tag.
+
+#[cfg(feature = "alloc")]
+use crate::{ByteStructDyn, ByteStructError, ByteStructRead, ByteStructWrite, ErrorKind};
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, collections::BTreeMap, vec, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::any::Any;
+
+/// A structure identified by a numeric tag when sent as a message.
+///
+/// This trait can be implemented by
+/// [`#[derive(ByteStruct)]`](https://docs.rs/byte_struct_derive/*/byte_struct_derive/derive.ByteStruct.html)
+/// with `#[byte_struct(tag = N)]` on the struct.
+/// How the tag is carried alongside the packed bytes is up to the protocol.
+pub trait ByteStructTag {
+    /// The tag of the message
+    const TAG: u32;
+}
+
+/// A message whose type is only known at runtime, as returned by [`Registry::decode`].
+///
+/// This is implemented for every type that implements [`ByteStructTag`] and [`ByteStructWrite`].
+/// The concrete type can be recovered with [`downcast_ref`](#method.downcast_ref)
+/// or [`downcast`](#method.downcast).
+///
+/// [`Registry::decode`]: struct.Registry.html#method.decode
+/// [`ByteStructTag`]: trait.ByteStructTag.html
+/// [`ByteStructWrite`]: trait.ByteStructWrite.html
+#[cfg(feature = "alloc")]
+pub trait Message: ByteStructDyn + Any {
+    /// Returns the tag of the message
+    fn tag(&self) -> u32;
+}
+
+#[cfg(feature = "alloc")]
+impl<T: ByteStructTag + ByteStructWrite + Any> Message for T {
+    fn tag(&self) -> u32 {
+        T::TAG
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl dyn Message {
+    /// Returns a reference to the message if it is of type `T`
+    pub fn downcast_ref<T: Message>(&self) -> Option<&T> {
+        (self as &dyn Any).downcast_ref()
+    }
+
+    /// Returns the message if it is of type `T`, or gives it back otherwise
+    pub fn downcast<T: Message>(self: Box<Self>) -> Result<Box<T>, Box<Self>> {
+        if (*self).downcast_ref::<T>().is_some() {
+            let any: Box<dyn Any> = self;
+            Ok(any.downcast().unwrap())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::fmt::Debug for dyn Message {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Message")
+            .field("tag", &self.tag())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "alloc")]
+type DecodeFn = fn(&[u8]) -> Result<Box<dyn Message>, ByteStructError>;
+
+#[cfg(feature = "alloc")]
+struct Entry {
+    len: usize,
+    decode: DecodeFn,
+}
+
+/// A table of message types keyed by their tags, for unpacking messages whose type is given by a tag.
+///
+/// Decoding a tag that isn't registered fails with [`ErrorKind::InvalidValue`].
+///
+/// [`ErrorKind::InvalidValue`]: enum.ErrorKind.html#variant.InvalidValue
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct(endian = "le", tag = 1)]
+/// struct Ping {
+///     seq: u16,
+/// }
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct(endian = "le", tag = 2)]
+/// struct Move {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// let mut registry = Registry::new();
+/// registry.register::<Ping>().register::<Move>();
+///
+/// let bytes = registry.encode(&Move { x: 2, y: 3 }).unwrap();
+/// let message = registry.decode(Move::TAG, &bytes).unwrap();
+/// assert_eq!(message.tag(), 2);
+/// assert_eq!(message.downcast_ref::<Move>(), Some(&Move { x: 2, y: 3 }));
+/// assert!(registry.decode(3, &bytes).is_err());
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+pub struct Registry {
+    entries: BTreeMap<u32, Entry>,
+}
+
+#[cfg(feature = "alloc")]
+impl Registry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Registry {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Registers type `T` under its tag.
+    ///
+    /// Panics if another type is already registered under the same tag.
+    pub fn register<T: Message + ByteStructTag + ByteStructRead>(&mut self) -> &mut Self {
+        fn decode<T: Message + ByteStructRead>(
+            bytes: &[u8],
+        ) -> Result<Box<dyn Message>, ByteStructError> {
+            Ok(Box::new(T::try_read_bytes(bytes)?))
+        }
+        let entry = Entry {
+            len: T::BYTE_LEN,
+            decode: decode::<T>,
+        };
+        assert!(
+            self.entries.insert(T::TAG, entry).is_none(),
+            "Tag {} is already registered",
+            T::TAG
+        );
+        self
+    }
+
+    /// Returns `true` if a type is registered under `tag`
+    pub fn contains(&self, tag: u32) -> bool {
+        self.entries.contains_key(&tag)
+    }
+
+    /// Returns the packed length of the type registered under `tag`
+    pub fn byte_len(&self, tag: u32) -> Option<usize> {
+        self.entries.get(&tag).map(|entry| entry.len)
+    }
+
+    /// Unpacks a message of the type registered under `tag`
+    pub fn decode(&self, tag: u32, bytes: &[u8]) -> Result<Box<dyn Message>, ByteStructError> {
+        match self.entries.get(&tag) {
+            Some(entry) => (entry.decode)(bytes),
+            None => Err(ByteStructError::new(ErrorKind::InvalidValue, None, 0)),
+        }
+    }
+
+    /// Packs a message into a new buffer, after checking that its tag is registered
+    pub fn encode(&self, message: &dyn Message) -> Result<Vec<u8>, ByteStructError> {
+        if !self.contains(message.tag()) {
+            return Err(ByteStructError::new(ErrorKind::InvalidValue, None, 0));
+        }
+        let mut bytes = vec![0; message.byte_len()];
+        message.write_bytes_dyn(&mut bytes);
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,101 @@
+//! Property tests of packing and unpacking with `proptest`.
+
+use crate::{ByteStructRead, ByteStructWrite};
+use alloc::vec;
+use core::fmt::Debug;
+use proptest::prelude::*;
+use proptest::test_runner::TestRunner;
+
+/// Returns a strategy that generates structures by unpacking random bytes.
+///
+/// Bytes that fail to unpack, such as those rejected by validation, are filtered out.
+/// Structures whose validation rejects most bytes make the test runner give up,
+/// and need a hand-written strategy instead.
+pub fn byte_struct_strategy<T: ByteStructRead + Debug>() -> impl Strategy<Value = T> {
+    proptest::collection::vec(any::<u8>(), T::BYTE_LEN)
+        .prop_filter_map("bytes fail to unpack", |bytes| {
+            T::try_read_bytes(&bytes).ok()
+        })
+}
+
+/// Checks that a structure survives packing and unpacking.
+///
+/// This fails if unpacking the packed bytes doesn't give back an equal structure,
+/// or if packing that structure again doesn't give the same bytes.
+pub fn check_roundtrip<T>(value: &T) -> Result<(), TestCaseError>
+where
+    T: ByteStructRead + ByteStructWrite + PartialEq + Debug,
+{
+    let mut bytes = vec![0; T::BYTE_LEN];
+    value.write_bytes(&mut bytes);
+    let unpacked = T::try_read_bytes(&bytes)
+        .map_err(|e| TestCaseError::fail(alloc::format!("packed bytes fail to unpack: {}", e)))?;
+    prop_assert_eq!(&unpacked, value);
+    let mut repacked = vec![0; T::BYTE_LEN];
+    unpacked.write_bytes(&mut repacked);
+    prop_assert_eq!(repacked, bytes);
+    Ok(())
+}
+
+/// Runs [`check_roundtrip`](fn.check_roundtrip.html) on structures generated
+/// by [`byte_struct_strategy`](fn.byte_struct_strategy.html).
+///
+/// Panics with the minimal failing structure if any of them fails.
+pub fn assert_roundtrip<T>()
+where
+    T: ByteStructRead + ByteStructWrite + PartialEq + Debug,
+{
+    assert_roundtrip_with(byte_struct_strategy::<T>());
+}
+
+/// Runs [`check_roundtrip`](fn.check_roundtrip.html) on structures generated by `strategy`.
+///
+/// Panics with the minimal failing structure if any of them fails.
+pub fn assert_roundtrip_with<T, S>(strategy: S)
+where
+    T: ByteStructRead + ByteStructWrite + PartialEq + Debug,
+    S: Strategy<Value = T>,
+{
+    if let Err(e) = TestRunner::default().run(&strategy, |value| check_roundtrip(&value)) {
+        panic!("{}", e);
+    }
+}
+
+/// Generates property tests that structures survive packing and unpacking.
+///
+/// `byte_struct_roundtrip!(name: Type, ...)` defines a `#[test]` function named `name`
+/// for each type, while `byte_struct_roundtrip!(Type)` runs the test in place,
+/// for use inside an existing test function.
+/// Both run [`assert_roundtrip`](fn.assert_roundtrip.html).
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Header {
+///     kind: u8,
+///     size: u16,
+/// }
+///
+/// byte_struct_roundtrip!(header_roundtrip: Header);
+///
+/// fn main() {
+///     byte_struct_roundtrip!(Header);
+/// }
+/// ```
+#[macro_export]
+macro_rules! byte_struct_roundtrip {
+    ($($name:ident: $ty:ty),+ $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                $crate::assert_roundtrip::<$ty>();
+            }
+        )+
+    };
+    ($ty:ty) => {
+        $crate::assert_roundtrip::<$ty>()
+    };
+}
@@ -0,0 +1,216 @@
+//! Export of packed layouts to the description languages of external tools.
+
+use crate::{ByteOrder, ByteStructLayout, FieldInfo};
+use core::fmt;
+use core::marker::PhantomData;
+
+/// A description language that [`Schema`](struct.Schema.html) can export a layout to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SchemaFormat {
+    /// A Kaitai Struct `.ksy` file
+    Kaitai,
+    /// An 010 Editor binary template
+    Editor010,
+    /// A C header declaring a packed struct
+    CHeader,
+}
+
+/// Formats the field layout of `T` as a schema for external tools, such as hex editors.
+///
+/// The schema is generated from [`ByteStructLayout::FIELDS`], which records where each field is
+/// and how it is packed, but not its type. Fields of 1, 2, 4 or 8 bytes with a byte order
+/// are exported as unsigned integers of that size, and all other fields as arrays of bytes.
+/// This includes fields that aren't integers, such as `f32` or `[u8; 4]`,
+/// whose exported type can be refined by hand.
+/// Bytes not covered by any field are exported as padding arrays named after their offset.
+///
+/// Available with the `schema` feature.
+///
+/// [`ByteStructLayout::FIELDS`]: trait.ByteStructLayout.html#associatedconstant.FIELDS
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Header {
+///     magic: u32,
+///     #[byte_struct_be]
+///     size: u16,
+///     #[byte_struct(pad_before = 2)]
+///     name: [u8; 6],
+/// }
+///
+/// assert_eq!(
+///     Schema::<Header>::new("header", SchemaFormat::Kaitai).to_string(),
+///     "meta:\n  \
+///        id: header\n\
+///      seq:\n  \
+///        - id: magic\n    \
+///          type: u4le\n  \
+///        - id: size\n    \
+///          type: u2be\n  \
+///        - id: padding_6\n    \
+///          size: 2\n  \
+///        - id: name\n    \
+///          size: 6\n"
+/// );
+/// ```
+pub struct Schema<'a, T> {
+    name: &'a str,
+    format: SchemaFormat,
+    phantom: PhantomData<T>,
+}
+
+impl<'a, T: ByteStructLayout> Schema<'a, T> {
+    /// Creates a schema of `T` in `format`, declaring the structure as `name`
+    pub fn new(name: &'a str, format: SchemaFormat) -> Self {
+        Schema {
+            name,
+            format,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ByteStructLayout> fmt::Display for Schema<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.format {
+            SchemaFormat::Kaitai => write_kaitai::<T>(f, self.name),
+            SchemaFormat::Editor010 => write_editor_010::<T>(f, self.name),
+            SchemaFormat::CHeader => write_c_header::<T>(f, self.name),
+        }
+    }
+}
+
+// A run of bytes in the packed layout, either a field or padding between fields
+enum Segment<'a> {
+    Field(&'a FieldInfo<'a>),
+    Padding { offset: usize, len: usize },
+}
+
+fn for_each_segment<T: ByteStructLayout>(mut f: impl FnMut(Segment) -> fmt::Result) -> fmt::Result {
+    let mut pos = 0;
+    for field in T::FIELDS {
+        if field.offset > pos {
+            f(Segment::Padding {
+                offset: pos,
+                len: field.offset - pos,
+            })?;
+        }
+        f(Segment::Field(field))?;
+        pos = pos.max(field.offset + field.len);
+    }
+    if T::BYTE_LEN > pos {
+        f(Segment::Padding {
+            offset: pos,
+            len: T::BYTE_LEN - pos,
+        })?;
+    }
+    Ok(())
+}
+
+// The integer byte order of a field, or `None` if it is exported as bytes
+fn int_order(field: &FieldInfo) -> Option<ByteOrder> {
+    match (field.len, field.byte_order) {
+        (1, _) => Some(ByteOrder::Fixed),
+        (2 | 4 | 8, ByteOrder::Little | ByteOrder::Big) => Some(field.byte_order),
+        _ => None,
+    }
+}
+
+// A field name without the prefix of raw identifiers
+struct Ident<'a>(&'a str);
+
+impl fmt::Display for Ident<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0.trim_start_matches("r#"))
+    }
+}
+
+fn write_kaitai<T: ByteStructLayout>(f: &mut fmt::Formatter, name: &str) -> fmt::Result {
+    writeln!(f, "meta:\n  id: {}\nseq:", name)?;
+    for_each_segment::<T>(|segment| match segment {
+        Segment::Field(field) => {
+            writeln!(f, "  - id: {}", Ident(field.name))?;
+            match int_order(field) {
+                Some(order) => {
+                    let suffix = match order {
+                        ByteOrder::Little => "le",
+                        ByteOrder::Big => "be",
+                        ByteOrder::Fixed => "",
+                    };
+                    writeln!(f, "    type: u{}{}", field.len, suffix)
+                }
+                None => writeln!(f, "    size: {}", field.len),
+            }
+        }
+        Segment::Padding { offset, len } => {
+            writeln!(f, "  - id: padding_{}\n    size: {}", offset, len)
+        }
+    })
+}
+
+fn write_editor_010<T: ByteStructLayout>(f: &mut fmt::Formatter, name: &str) -> fmt::Result {
+    writeln!(f, "typedef struct {{")?;
+    let mut current = ByteOrder::Fixed;
+    for_each_segment::<T>(|segment| match segment {
+        Segment::Field(field) => {
+            let order = int_order(field);
+            match order {
+                Some(ByteOrder::Little) if current != ByteOrder::Little => {
+                    writeln!(f, "    LittleEndian();")?;
+                    current = ByteOrder::Little;
+                }
+                Some(ByteOrder::Big) if current != ByteOrder::Big => {
+                    writeln!(f, "    BigEndian();")?;
+                    current = ByteOrder::Big;
+                }
+                _ => (),
+            }
+            match order {
+                Some(ByteOrder::Fixed) => writeln!(f, "    ubyte {};", Ident(field.name)),
+                Some(_) => writeln!(f, "    uint{} {};", field.len * 8, Ident(field.name)),
+                None => writeln!(f, "    ubyte {}[{}];", Ident(field.name), field.len),
+            }
+        }
+        Segment::Padding { offset, len } => {
+            writeln!(f, "    ubyte padding_{}[{}];", offset, len)
+        }
+    })?;
+    writeln!(f, "}} {};", name)
+}
+
+fn write_c_header<T: ByteStructLayout>(f: &mut fmt::Formatter, name: &str) -> fmt::Result {
+    writeln!(
+        f,
+        "#include <stdint.h>\n\n#pragma pack(push, 1)\nstruct {} {{",
+        name
+    )?;
+    for_each_segment::<T>(|segment| match segment {
+        Segment::Field(field) => match int_order(field) {
+            Some(order) => {
+                let comment = match order {
+                    ByteOrder::Little => " /* little-endian */",
+                    ByteOrder::Big => " /* big-endian */",
+                    ByteOrder::Fixed => "",
+                };
+                let bits = field.len * 8;
+                writeln!(f, "    uint{}_t {};{}", bits, Ident(field.name), comment)
+            }
+            None => writeln!(f, "    uint8_t {}[{}];", Ident(field.name), field.len),
+        },
+        Segment::Padding { offset, len } => {
+            writeln!(f, "    uint8_t padding_{}[{}];", offset, len)
+        }
+    })?;
+    writeln!(f, "}};\n#pragma pack(pop)\n")?;
+    writeln!(
+        f,
+        "_Static_assert(sizeof(struct {}) == {}, \"unexpected size of struct {}\");",
+        name,
+        T::BYTE_LEN,
+        name
+    )
+}
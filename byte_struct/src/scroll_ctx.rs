@@ -0,0 +1,131 @@
+//! Reading and writing packed structures with `scroll`.
+
+use crate::{ByteStructDebugJson, ByteStructLen, ByteStructUnspecifiedByteOrder, ErrorKind};
+use core::fmt::{Result as FmtResult, Write};
+use scroll::ctx::{SizeWith, TryFromCtx, TryIntoCtx};
+use scroll::Endian;
+
+/// A value read and written with `scroll` through its byte_struct packing.
+///
+/// This implements `TryFromCtx`, `TryIntoCtx` and `SizeWith` with `scroll::Endian` as the context,
+/// so the value can be used with `pread`, `gread`, `pwrite` and parsers built on them.
+/// The context gives the default byte order of types without their own,
+/// such as primitives and arrays, and has no effect on types implementing `ByteStruct`.
+///
+/// Errors are reported as `scroll::Error`: `TooBig` if the input is too short,
+/// and `BadInput` with a short description if the bytes fail to unpack.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+/// use scroll::{Pread, Pwrite, BE};
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Section {
+///     offset: u32,
+///     size: u16,
+/// }
+///
+/// let bytes = [0xAA, 1, 0, 0, 0, 2, 0];
+/// let section: Scroll<Section> = bytes.pread(1).unwrap();
+/// assert_eq!(section.0, Section { offset: 1, size: 2 });
+///
+/// let mut out = [0; 4];
+/// out.pwrite_with(Scroll([1u16, 2]), 0, BE).unwrap();
+/// assert_eq!(out, [0, 1, 0, 2]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Scroll<T>(pub T);
+
+impl<T: ByteStructLen> ByteStructLen for Scroll<T> {
+    const BYTE_LEN: usize = T::BYTE_LEN;
+}
+
+/// Packed in the same way as the wrapped value
+impl<T: ByteStructUnspecifiedByteOrder> ByteStructUnspecifiedByteOrder for Scroll<T> {
+    fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+        self.0.write_bytes_default_le(bytes);
+    }
+    fn read_bytes_default_le(bytes: &[u8]) -> Self {
+        Scroll(T::read_bytes_default_le(bytes))
+    }
+    fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+        self.0.write_bytes_default_be(bytes);
+    }
+    fn read_bytes_default_be(bytes: &[u8]) -> Self {
+        Scroll(T::read_bytes_default_be(bytes))
+    }
+    fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, crate::ByteStructError> {
+        T::try_read_bytes_default_le(bytes).map(Scroll)
+    }
+    fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, crate::ByteStructError> {
+        T::try_read_bytes_default_be(bytes).map(Scroll)
+    }
+}
+
+impl<T: ByteStructDebugJson> ByteStructDebugJson for Scroll<T> {
+    fn write_debug_json<W: Write + ?Sized>(&self, out: &mut W) -> FmtResult {
+        self.0.write_debug_json(out)
+    }
+}
+
+fn too_big<T: ByteStructLen>(len: usize) -> scroll::Error {
+    scroll::Error::TooBig {
+        size: T::BYTE_LEN,
+        len,
+    }
+}
+
+impl<'a, T: ByteStructUnspecifiedByteOrder + 'a> TryFromCtx<'a, Endian> for Scroll<T> {
+    type Error = scroll::Error;
+
+    fn try_from_ctx(from: &'a [u8], ctx: Endian) -> Result<(Self, usize), Self::Error> {
+        if from.len() < T::BYTE_LEN {
+            return Err(too_big::<T>(from.len()));
+        }
+        let value = if ctx.is_little() {
+            T::try_read_bytes_default_le(from)
+        } else {
+            T::try_read_bytes_default_be(from)
+        };
+        match value {
+            Ok(value) => Ok((Scroll(value), T::BYTE_LEN)),
+            Err(error) if error.kind == ErrorKind::ShortBuffer => Err(too_big::<T>(from.len())),
+            Err(error) => Err(scroll::Error::BadInput {
+                size: T::BYTE_LEN,
+                msg: error.kind.reason(),
+            }),
+        }
+    }
+}
+
+impl<T: ByteStructUnspecifiedByteOrder> TryIntoCtx<Endian> for &Scroll<T> {
+    type Error = scroll::Error;
+
+    fn try_into_ctx(self, into: &mut [u8], ctx: Endian) -> Result<usize, Self::Error> {
+        if into.len() < T::BYTE_LEN {
+            return Err(too_big::<T>(into.len()));
+        }
+        if ctx.is_little() {
+            self.0.write_bytes_default_le(into);
+        } else {
+            self.0.write_bytes_default_be(into);
+        }
+        Ok(T::BYTE_LEN)
+    }
+}
+
+impl<T: ByteStructUnspecifiedByteOrder> TryIntoCtx<Endian> for Scroll<T> {
+    type Error = scroll::Error;
+
+    fn try_into_ctx(self, into: &mut [u8], ctx: Endian) -> Result<usize, Self::Error> {
+        (&self).try_into_ctx(into, ctx)
+    }
+}
+
+impl<T: ByteStructLen> SizeWith<Endian> for Scroll<T> {
+    fn size_with(_ctx: &Endian) -> usize {
+        T::BYTE_LEN
+    }
+}
@@ -0,0 +1,269 @@
+//! COBS and SLIP framing of packed structures for serial links.
+//!
+//! Both encodings make the end of a frame recognizable in a byte stream.
+//! COBS (Consistent Overhead Byte Stuffing) removes all zero bytes from the frame
+//! and terminates it with a zero byte, with an overhead of at most one byte every 254 bytes.
+//! SLIP (RFC 1055) terminates the frame with `0xC0`, and escapes `0xC0` and `0xDB` in the frame
+//! as two bytes each.
+
+use crate::{ByteStructError, ByteStructRead, ByteStructWrite};
+use core::fmt;
+
+/// An error from encoding or decoding a COBS or SLIP frame.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FrameError {
+    /// The output slice is too short to hold the encoded frame
+    OutputTooShort,
+    /// The frame is not validly encoded, or has bytes after the packed structure
+    Malformed,
+    /// The decoded bytes failed to unpack
+    Unpack(ByteStructError),
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FrameError::OutputTooShort => f.write_str("output too short for the encoded frame"),
+            FrameError::Malformed => f.write_str("malformed frame"),
+            FrameError::Unpack(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl core::error::Error for FrameError {}
+
+impl From<ByteStructError> for FrameError {
+    fn from(error: ByteStructError) -> Self {
+        FrameError::Unpack(error)
+    }
+}
+
+/// Returns the maximum length of `len` bytes encoded with COBS, excluding the delimiter
+pub const fn cobs_max_encoded_len(len: usize) -> usize {
+    len + len / 254 + 1
+}
+
+// Encodes buf[src .. src + len] into the start of buf.
+// The output never overtakes the input as long as src is at least the maximum overhead.
+fn cobs_encode_within(buf: &mut [u8], src: usize, len: usize) -> usize {
+    let mut code_index = 0;
+    let mut index = 1;
+    let mut code = 1u8;
+    for i in src..src + len {
+        let byte = buf[i];
+        if byte != 0 {
+            buf[index] = byte;
+            index += 1;
+            code += 1;
+        }
+        if byte == 0 || code == 0xFF {
+            buf[code_index] = code;
+            code_index = index;
+            index += 1;
+            code = 1;
+        }
+    }
+    buf[code_index] = code;
+    index
+}
+
+/// Encodes `data` with COBS into `out`, and returns the length of the encoded bytes.
+///
+/// The delimiter is not appended.
+pub fn cobs_encode(data: &[u8], out: &mut [u8]) -> Result<usize, FrameError> {
+    let encoded_len = cobs_max_encoded_len(data.len());
+    if out.len() < encoded_len {
+        return Err(FrameError::OutputTooShort);
+    }
+    let src = encoded_len - data.len();
+    out[src..encoded_len].copy_from_slice(data);
+    Ok(cobs_encode_within(out, src, data.len()))
+}
+
+/// Decodes COBS encoded bytes in place, and returns the length of the decoded bytes.
+///
+/// `buf` must not include the delimiter.
+pub fn cobs_decode(buf: &mut [u8]) -> Result<usize, FrameError> {
+    if buf.is_empty() {
+        return Err(FrameError::Malformed);
+    }
+    let mut read = 0;
+    let mut write = 0;
+    while read < buf.len() {
+        let code = buf[read] as usize;
+        read += 1;
+        if code == 0 || read + code - 1 > buf.len() {
+            return Err(FrameError::Malformed);
+        }
+        for i in read..read + code - 1 {
+            if buf[i] == 0 {
+                return Err(FrameError::Malformed);
+            }
+            buf[write] = buf[i];
+            write += 1;
+        }
+        read += code - 1;
+        if code != 0xFF && read < buf.len() {
+            buf[write] = 0;
+            write += 1;
+        }
+    }
+    Ok(write)
+}
+
+/// Packs `value` and encodes it as a COBS frame terminated by a zero byte.
+///
+/// Returns the length of the frame including the delimiter.
+/// `out` needs to hold `cobs_max_encoded_len(T::BYTE_LEN) + 1` bytes.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct, PartialEq, Debug)]
+/// #[byte_struct_le]
+/// struct Reading {
+///     channel: u8,
+///     value: u16,
+/// }
+///
+/// let mut frame = [0; cobs_max_encoded_len(Reading::BYTE_LEN) + 1];
+/// let len = cobs_encode_frame(&Reading { channel: 0, value: 0x1234 }, &mut frame).unwrap();
+/// assert_eq!(&frame[..len], &[0x01, 0x03, 0x34, 0x12, 0x00]);
+/// assert_eq!(cobs_decode_frame::<Reading>(&mut frame[..len]), Ok(Reading { channel: 0, value: 0x1234 }));
+/// ```
+pub fn cobs_encode_frame<T: ByteStructWrite>(
+    value: &T,
+    out: &mut [u8],
+) -> Result<usize, FrameError> {
+    let encoded_len = cobs_max_encoded_len(T::BYTE_LEN);
+    if out.len() < encoded_len + 1 {
+        return Err(FrameError::OutputTooShort);
+    }
+    let src = encoded_len - T::BYTE_LEN;
+    value.write_bytes(&mut out[src..encoded_len]);
+    let len = cobs_encode_within(out, src, T::BYTE_LEN);
+    out[len] = 0;
+    Ok(len + 1)
+}
+
+/// Decodes a COBS frame in place and unpacks it.
+///
+/// A trailing delimiter in `frame` is ignored.
+pub fn cobs_decode_frame<T: ByteStructRead>(frame: &mut [u8]) -> Result<T, FrameError> {
+    let frame = match frame.split_last_mut() {
+        Some((0, rest)) => rest,
+        _ => frame,
+    };
+    let len = cobs_decode(frame)?;
+    unpack_exact(&frame[..len])
+}
+
+const SLIP_END: u8 = 0xC0;
+const SLIP_ESC: u8 = 0xDB;
+const SLIP_ESC_END: u8 = 0xDC;
+const SLIP_ESC_ESC: u8 = 0xDD;
+
+// Escapes buf[.. len] in place from the end, and appends the END byte
+fn slip_escape_within(buf: &mut [u8], len: usize) -> Result<usize, FrameError> {
+    let escapes = buf[..len]
+        .iter()
+        .filter(|byte| **byte == SLIP_END || **byte == SLIP_ESC)
+        .count();
+    let encoded_len = len + escapes + 1;
+    if buf.len() < encoded_len {
+        return Err(FrameError::OutputTooShort);
+    }
+    buf[encoded_len - 1] = SLIP_END;
+    let mut write = encoded_len - 1;
+    for read in (0..len).rev() {
+        let byte = buf[read];
+        let escaped = match byte {
+            SLIP_END => Some(SLIP_ESC_END),
+            SLIP_ESC => Some(SLIP_ESC_ESC),
+            _ => None,
+        };
+        match escaped {
+            Some(escaped) => {
+                buf[write - 1] = escaped;
+                buf[write - 2] = SLIP_ESC;
+                write -= 2;
+            }
+            None => {
+                buf[write - 1] = byte;
+                write -= 1;
+            }
+        }
+    }
+    Ok(encoded_len)
+}
+
+/// Encodes `data` with SLIP into `out`, terminated by the END byte `0xC0`,
+/// and returns the length of the encoded bytes.
+pub fn slip_encode(data: &[u8], out: &mut [u8]) -> Result<usize, FrameError> {
+    if out.len() < data.len() {
+        return Err(FrameError::OutputTooShort);
+    }
+    out[..data.len()].copy_from_slice(data);
+    slip_escape_within(out, data.len())
+}
+
+/// Decodes SLIP encoded bytes in place, and returns the length of the decoded bytes.
+///
+/// A trailing END byte in `buf` is ignored.
+pub fn slip_decode(buf: &mut [u8]) -> Result<usize, FrameError> {
+    let len = match buf.last() {
+        Some(&SLIP_END) => buf.len() - 1,
+        _ => buf.len(),
+    };
+    let mut read = 0;
+    let mut write = 0;
+    while read < len {
+        let byte = match buf[read] {
+            SLIP_END => return Err(FrameError::Malformed),
+            SLIP_ESC => {
+                read += 1;
+                match buf[..len].get(read) {
+                    Some(&SLIP_ESC_END) => SLIP_END,
+                    Some(&SLIP_ESC_ESC) => SLIP_ESC,
+                    _ => return Err(FrameError::Malformed),
+                }
+            }
+            byte => byte,
+        };
+        buf[write] = byte;
+        write += 1;
+        read += 1;
+    }
+    Ok(write)
+}
+
+/// Packs `value` and encodes it as a SLIP frame terminated by the END byte `0xC0`.
+///
+/// Returns the length of the frame including the END byte.
+/// `out` needs to hold `2 * T::BYTE_LEN + 1` bytes in the worst case.
+pub fn slip_encode_frame<T: ByteStructWrite>(
+    value: &T,
+    out: &mut [u8],
+) -> Result<usize, FrameError> {
+    if out.len() < T::BYTE_LEN {
+        return Err(FrameError::OutputTooShort);
+    }
+    value.write_bytes(&mut out[..T::BYTE_LEN]);
+    slip_escape_within(out, T::BYTE_LEN)
+}
+
+/// Decodes a SLIP frame in place and unpacks it.
+///
+/// A trailing END byte in `frame` is ignored.
+pub fn slip_decode_frame<T: ByteStructRead>(frame: &mut [u8]) -> Result<T, FrameError> {
+    let len = slip_decode(frame)?;
+    unpack_exact(&frame[..len])
+}
+
+fn unpack_exact<T: ByteStructRead>(bytes: &[u8]) -> Result<T, FrameError> {
+    if bytes.len() > T::BYTE_LEN {
+        return Err(FrameError::Malformed);
+    }
+    Ok(T::try_read_bytes(bytes)?)
+}
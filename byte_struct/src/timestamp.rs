@@ -0,0 +1,376 @@
+//! Timestamps packed as plain integers, and the MS-DOS packed date and time.
+
+use crate::json::write_json_string;
+use crate::{
+    extract_bits, ByteStructDebugJson, ByteStructError, ByteStructLen,
+    ByteStructUnspecifiedByteOrder,
+};
+use core::fmt;
+use core::time::Duration;
+
+/// The error returned when converting a time that a timestamp type can't represent,
+/// such as a time before the Unix epoch or past the range of the packed integer.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TimestampRangeError;
+
+impl fmt::Display for TimestampRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("time out of the range of the timestamp")
+    }
+}
+
+impl core::error::Error for TimestampRangeError {}
+
+const fn units_to_duration(units: u64, units_per_sec: u64) -> Duration {
+    let nanos_per_unit = 1_000_000_000 / units_per_sec;
+    Duration::new(units / units_per_sec, (units % units_per_sec * nanos_per_unit) as u32)
+}
+
+const fn duration_to_units(duration: Duration, units_per_sec: u64) -> u128 {
+    duration.as_nanos() / (1_000_000_000 / units_per_sec) as u128
+}
+
+macro_rules! unix_time_impl {
+    ($(#[$doc:meta])* $name:ident($t:ty), $units_per_sec:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+        pub struct $name(pub $t);
+
+        impl $name {
+            /// The Unix epoch, 1970-01-01 00:00:00 UTC
+            pub const EPOCH: $name = $name(0);
+
+            /// Returns the time elapsed since the Unix epoch
+            pub const fn to_duration(self) -> Duration {
+                units_to_duration(self.0 as u64, $units_per_sec)
+            }
+
+            /// Creates a timestamp from the time elapsed since the Unix epoch,
+            /// truncated to the unit of the timestamp.
+            ///
+            /// Returns `None` if the time is past the range of the packed integer.
+            pub fn from_duration(duration: Duration) -> Option<Self> {
+                <$t>::try_from(duration_to_units(duration, $units_per_sec)).ok().map($name)
+            }
+        }
+
+        impl From<$name> for Duration {
+            fn from(value: $name) -> Self {
+                value.to_duration()
+            }
+        }
+
+        impl TryFrom<Duration> for $name {
+            type Error = TimestampRangeError;
+            fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+                $name::from_duration(duration).ok_or(TimestampRangeError)
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl From<$name> for std::time::SystemTime {
+            fn from(value: $name) -> Self {
+                std::time::UNIX_EPOCH + value.to_duration()
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl TryFrom<std::time::SystemTime> for $name {
+            type Error = TimestampRangeError;
+            fn try_from(value: std::time::SystemTime) -> Result<Self, Self::Error> {
+                let duration = value
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_err(|_| TimestampRangeError)?;
+                $name::try_from(duration)
+            }
+        }
+
+        #[cfg(feature = "chrono")]
+        impl TryFrom<$name> for chrono::DateTime<chrono::Utc> {
+            type Error = TimestampRangeError;
+            fn try_from(value: $name) -> Result<Self, Self::Error> {
+                let duration = value.to_duration();
+                let secs = i64::try_from(duration.as_secs()).map_err(|_| TimestampRangeError)?;
+                chrono::DateTime::from_timestamp(secs, duration.subsec_nanos()).ok_or(TimestampRangeError)
+            }
+        }
+
+        #[cfg(feature = "chrono")]
+        impl TryFrom<chrono::DateTime<chrono::Utc>> for $name {
+            type Error = TimestampRangeError;
+            fn try_from(value: chrono::DateTime<chrono::Utc>) -> Result<Self, Self::Error> {
+                let secs = u64::try_from(value.timestamp()).map_err(|_| TimestampRangeError)?;
+                $name::try_from(Duration::new(secs, value.timestamp_subsec_nanos()))
+            }
+        }
+
+        #[cfg(feature = "time")]
+        impl TryFrom<$name> for time::OffsetDateTime {
+            type Error = TimestampRangeError;
+            fn try_from(value: $name) -> Result<Self, Self::Error> {
+                let nanos = value.to_duration().as_nanos() as i128;
+                time::OffsetDateTime::from_unix_timestamp_nanos(nanos).map_err(|_| TimestampRangeError)
+            }
+        }
+
+        #[cfg(feature = "time")]
+        impl TryFrom<time::OffsetDateTime> for $name {
+            type Error = TimestampRangeError;
+            fn try_from(value: time::OffsetDateTime) -> Result<Self, Self::Error> {
+                let nanos = u128::try_from(value.unix_timestamp_nanos()).map_err(|_| TimestampRangeError)?;
+                let duration = Duration::new(
+                    u64::try_from(nanos / 1_000_000_000).map_err(|_| TimestampRangeError)?,
+                    (nanos % 1_000_000_000) as u32,
+                );
+                $name::try_from(duration)
+            }
+        }
+
+        impl ByteStructLen for $name {
+            const BYTE_LEN: usize = <$t>::BYTE_LEN;
+        }
+
+        /// Packed in the same way as the integer count
+        impl ByteStructUnspecifiedByteOrder for $name {
+            fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+                self.0.write_bytes_default_le(bytes);
+            }
+            fn read_bytes_default_le(bytes: &[u8]) -> Self {
+                $name(<$t>::read_bytes_default_le(bytes))
+            }
+            fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+                self.0.write_bytes_default_be(bytes);
+            }
+            fn read_bytes_default_be(bytes: &[u8]) -> Self {
+                $name(<$t>::read_bytes_default_be(bytes))
+            }
+            fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, ByteStructError> {
+                <$t>::try_read_bytes_default_le(bytes).map($name)
+            }
+            fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, ByteStructError> {
+                <$t>::try_read_bytes_default_be(bytes).map($name)
+            }
+        }
+
+        impl ByteStructDebugJson for $name {
+            fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+                self.0.write_debug_json(out)
+            }
+        }
+    };
+}
+
+unix_time_impl!(
+    /// Seconds since the Unix epoch, packed as a `u32`.
+    ///
+    /// Like the integer it wraps, it follows the byte order attributes of the containing structure.
+    /// It converts to and from `Duration` since the epoch, and with the `std`, `chrono` or `time`
+    /// feature, to and from `SystemTime`, `chrono::DateTime<Utc>` or `time::OffsetDateTime`.
+    /// Times before the epoch can't be represented.
+    ///
+    /// # Example
+    /// ```
+    /// use byte_struct::*;
+    /// use core::time::Duration;
+    ///
+    /// #[derive(ByteStruct)]
+    /// #[byte_struct_le]
+    /// struct Entry {
+    ///     modified: UnixTime32,
+    ///     size: u32,
+    /// }
+    ///
+    /// let entry = Entry::read_bytes(&[0x00, 0xe1, 0xf5, 0x05, 0, 0, 0, 0][..]);
+    /// assert_eq!(entry.modified.to_duration(), Duration::from_secs(100_000_000));
+    /// ```
+    UnixTime32(u32),
+    1
+);
+
+unix_time_impl!(
+    /// Seconds since the Unix epoch, packed as a `u64`.
+    ///
+    /// See [`UnixTime32`](struct.UnixTime32.html) for the conversions.
+    UnixTime64(u64),
+    1
+);
+
+unix_time_impl!(
+    /// Milliseconds since the Unix epoch, packed as a `u64`.
+    ///
+    /// See [`UnixTime32`](struct.UnixTime32.html) for the conversions.
+    /// Converting a more precise time truncates it to whole milliseconds.
+    UnixTimeMillis64(u64),
+    1000
+);
+
+/// A date and time in the MS-DOS format used by FAT directory entries and ZIP headers,
+/// packed as a 16-bit time followed by a 16-bit date.
+///
+/// Both halves are integers that follow the byte order attributes of the containing structure,
+/// which is little-endian in FAT and ZIP. The time is in units of 2 seconds,
+/// and the year is counted from 1980, so the representable range is from 1980 to 2107.
+///
+/// The fields are not checked when unpacking, so invalid dates such as month 0 are kept as they are.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct ZipLocalHeaderStart {
+///     signature: u32,
+///     version: u16,
+///     flags: u16,
+///     compression: u16,
+///     modified: DosDateTime,
+/// }
+///
+/// let header = ZipLocalHeaderStart::read_bytes(&[
+///     0x50, 0x4b, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00, 0x7d, 0x6b, 0x4f, 0x5a,
+/// ][..]);
+/// assert_eq!(header.modified.to_string(), "2025-02-15T13:27:58");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DosDateTime {
+    time: u16,
+    date: u16,
+}
+
+impl DosDateTime {
+    /// The earliest representable time, 1980-01-01 00:00:00
+    pub const MIN: DosDateTime = DosDateTime {
+        time: 0,
+        date: (1 << 5) | 1,
+    };
+
+    /// Creates a date and time from its components.
+    ///
+    /// Odd seconds are rounded down, as the time is stored in units of 2 seconds.
+    /// Returns `None` if a component is out of range, including days past the end of the month.
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Option<Self> {
+        if !(1980..=2107).contains(&year)
+            || !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return None;
+        }
+        Some(DosDateTime {
+            time: (u16::from(hour) << 11) | (u16::from(minute) << 5) | u16::from(second / 2),
+            date: ((year - 1980) << 9) | (u16::from(month) << 5) | u16::from(day),
+        })
+    }
+
+    /// Creates a date and time from the raw 16-bit time and date
+    pub const fn from_raw(time: u16, date: u16) -> Self {
+        DosDateTime { time, date }
+    }
+
+    /// Returns the raw 16-bit time
+    pub const fn raw_time(&self) -> u16 {
+        self.time
+    }
+
+    /// Returns the raw 16-bit date
+    pub const fn raw_date(&self) -> u16 {
+        self.date
+    }
+
+    /// Returns the year, from 1980 to 2107
+    pub fn year(&self) -> u16 {
+        1980 + extract_bits(self.date, 9..16)
+    }
+
+    /// Returns the month, from 1 to 12 for valid dates
+    pub fn month(&self) -> u8 {
+        extract_bits(self.date, 5..9) as u8
+    }
+
+    /// Returns the day of the month, from 1 to 31 for valid dates
+    pub fn day(&self) -> u8 {
+        extract_bits(self.date, 0..5) as u8
+    }
+
+    /// Returns the hour, from 0 to 23 for valid times
+    pub fn hour(&self) -> u8 {
+        extract_bits(self.time, 11..16) as u8
+    }
+
+    /// Returns the minute, from 0 to 59 for valid times
+    pub fn minute(&self) -> u8 {
+        extract_bits(self.time, 5..11) as u8
+    }
+
+    /// Returns the second, which is always even
+    pub fn second(&self) -> u8 {
+        extract_bits(self.time, 0..5) as u8 * 2
+    }
+}
+
+impl Default for DosDateTime {
+    fn default() -> Self {
+        DosDateTime::MIN
+    }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl fmt::Display for DosDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year(),
+            self.month(),
+            self.day(),
+            self.hour(),
+            self.minute(),
+            self.second()
+        )
+    }
+}
+
+impl ByteStructLen for DosDateTime {
+    const BYTE_LEN: usize = 4;
+}
+
+impl ByteStructUnspecifiedByteOrder for DosDateTime {
+    fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+        self.time.write_bytes_default_le(&mut bytes[0..2]);
+        self.date.write_bytes_default_le(&mut bytes[2..4]);
+    }
+    fn read_bytes_default_le(bytes: &[u8]) -> Self {
+        DosDateTime {
+            time: u16::read_bytes_default_le(&bytes[0..2]),
+            date: u16::read_bytes_default_le(&bytes[2..4]),
+        }
+    }
+    fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+        self.time.write_bytes_default_be(&mut bytes[0..2]);
+        self.date.write_bytes_default_be(&mut bytes[2..4]);
+    }
+    fn read_bytes_default_be(bytes: &[u8]) -> Self {
+        DosDateTime {
+            time: u16::read_bytes_default_be(&bytes[0..2]),
+            date: u16::read_bytes_default_be(&bytes[2..4]),
+        }
+    }
+}
+
+impl ByteStructDebugJson for DosDateTime {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        write_json_string(self, out)
+    }
+}
@@ -0,0 +1,218 @@
+//! Variable-length integers in the LEB128 encoding of protobuf, DWARF and WebAssembly.
+
+use crate::{ByteStructError, ErrorKind};
+
+/// An unsigned integer encoded as unsigned LEB128, the varint of protobuf.
+///
+/// The value is stored 7 bits per byte, starting from the least significant bits,
+/// with the most significant bit of each byte set if more bytes follow.
+///
+/// The encoded length depends on the value, so this isn't a fixed-size type
+/// and can't be a field of a structure deriving `ByteStruct`.
+/// It is encoded and decoded next to packed structures instead,
+/// such as for the variable-length parts following a fixed header.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// let mut bytes = [0; VarUint::MAX_LEN];
+/// let len = VarUint(300).encode(&mut bytes);
+/// assert_eq!(bytes[..len], [0xAC, 0x02]);
+/// assert_eq!(VarUint::decode(&bytes[..len]), Ok((VarUint(300), 2)));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct VarUint(pub u64);
+
+/// A signed integer encoded with zigzag encoding as [`VarUint`](struct.VarUint.html),
+/// the `sint64` of protobuf.
+///
+/// Zigzag encoding maps 0, -1, 1, -2, 2... to 0, 1, 2, 3, 4...,
+/// so that values of small magnitude have short encodings regardless of their sign.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// let mut bytes = [0; VarInt::MAX_LEN];
+/// let len = VarInt(-2).encode(&mut bytes);
+/// assert_eq!(bytes[..len], [0x03]);
+/// assert_eq!(VarInt::decode(&bytes[..len]), Ok((VarInt(-2), 1)));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct VarInt(pub i64);
+
+/// A signed integer encoded as signed LEB128, the variable-length integer of DWARF and WebAssembly.
+///
+/// The two's complement value is stored 7 bits per byte as in [`VarUint`](struct.VarUint.html),
+/// and the encoding ends once the remaining bits are all copies of the sign bit,
+/// which is bit 6 of the last byte. Unlike [`VarInt`](struct.VarInt.html), negative values
+/// are not zigzag encoded, so the two have different encodings of the same value.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// let mut bytes = [0; Sleb128::MAX_LEN];
+/// let len = Sleb128(-129).encode(&mut bytes);
+/// assert_eq!(bytes[..len], [0xFF, 0x7E]);
+/// assert_eq!(Sleb128::decode(&bytes[..len]), Ok((Sleb128(-129), 2)));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct Sleb128(pub i64);
+
+impl VarUint {
+    /// The longest encoding of a 64-bit value in bytes
+    pub const MAX_LEN: usize = 10;
+
+    /// Returns the length of the encoding of the value in bytes
+    pub const fn encoded_len(self) -> usize {
+        let bits = 64 - self.0.leading_zeros() as usize;
+        if bits == 0 {
+            1
+        } else {
+            bits.div_ceil(7)
+        }
+    }
+
+    /// Encodes the value at the start of `bytes` and returns the length of the encoding.
+    ///
+    /// Panics if `bytes` is shorter than the encoding.
+    pub fn encode(self, bytes: &mut [u8]) -> usize {
+        let len = self.encoded_len();
+        let mut value = self.0;
+        for byte in &mut bytes[..len - 1] {
+            *byte = value as u8 | 0x80;
+            value >>= 7;
+        }
+        bytes[len - 1] = value as u8;
+        len
+    }
+
+    /// Decodes a value from the start of `bytes`, and returns it with the length of its encoding.
+    ///
+    /// Fails with `ErrorKind::ShortBuffer` if `bytes` ends before the last byte of the encoding,
+    /// and with `ErrorKind::InvalidValue` if the encoding doesn't fit in 64 bits.
+    pub fn decode(bytes: &[u8]) -> Result<(Self, usize), ByteStructError> {
+        let mut value = 0;
+        for (i, &byte) in bytes.iter().enumerate() {
+            // The 10th byte only holds the most significant bit
+            if i == Self::MAX_LEN - 1 && byte > 1 {
+                return Err(ByteStructError::new(ErrorKind::InvalidValue, None, i));
+            }
+            value |= u64::from(byte & 0x7F) << (7 * i);
+            if byte & 0x80 == 0 {
+                return Ok((VarUint(value), i + 1));
+            }
+        }
+        Err(ByteStructError::new(
+            ErrorKind::ShortBuffer,
+            None,
+            bytes.len(),
+        ))
+    }
+}
+
+impl VarInt {
+    /// The longest encoding of a 64-bit value in bytes
+    pub const MAX_LEN: usize = VarUint::MAX_LEN;
+
+    const fn zigzag(self) -> VarUint {
+        VarUint(((self.0 << 1) ^ (self.0 >> 63)) as u64)
+    }
+
+    /// Returns the length of the encoding of the value in bytes
+    pub const fn encoded_len(self) -> usize {
+        self.zigzag().encoded_len()
+    }
+
+    /// Encodes the value at the start of `bytes` and returns the length of the encoding.
+    ///
+    /// Panics if `bytes` is shorter than the encoding.
+    pub fn encode(self, bytes: &mut [u8]) -> usize {
+        self.zigzag().encode(bytes)
+    }
+
+    /// Decodes a value from the start of `bytes`, and returns it with the length of its encoding.
+    ///
+    /// Fails in the same way as [`VarUint::decode`](struct.VarUint.html#method.decode).
+    pub fn decode(bytes: &[u8]) -> Result<(Self, usize), ByteStructError> {
+        let (VarUint(value), len) = VarUint::decode(bytes)?;
+        Ok((VarInt((value >> 1) as i64 ^ -((value & 1) as i64)), len))
+    }
+}
+
+impl Sleb128 {
+    /// The longest encoding of a 64-bit value in bytes
+    pub const MAX_LEN: usize = 10;
+
+    /// Returns the length of the encoding of the value in bytes
+    pub const fn encoded_len(self) -> usize {
+        // The bits that differ from the sign, plus the sign bit itself
+        let bits = if self.0 < 0 {
+            65 - self.0.leading_ones() as usize
+        } else {
+            65 - self.0.leading_zeros() as usize
+        };
+        bits.div_ceil(7)
+    }
+
+    /// Encodes the value at the start of `bytes` and returns the length of the encoding.
+    ///
+    /// Panics if `bytes` is shorter than the encoding.
+    pub fn encode(self, bytes: &mut [u8]) -> usize {
+        let len = self.encoded_len();
+        let mut value = self.0;
+        for byte in &mut bytes[..len - 1] {
+            *byte = value as u8 | 0x80;
+            value >>= 7;
+        }
+        bytes[len - 1] = value as u8 & 0x7F;
+        len
+    }
+
+    /// Decodes a value from the start of `bytes`, and returns it with the length of its encoding.
+    ///
+    /// Fails with `ErrorKind::ShortBuffer` if `bytes` ends before the last byte of the encoding,
+    /// and with `ErrorKind::InvalidValue` if the encoding doesn't fit in 64 bits.
+    pub fn decode(bytes: &[u8]) -> Result<(Self, usize), ByteStructError> {
+        let mut value = 0;
+        for (i, &byte) in bytes.iter().enumerate() {
+            // The 10th byte only holds the most significant bit, and copies of it
+            if i == Self::MAX_LEN - 1 && byte != 0x00 && byte != 0x7F {
+                return Err(ByteStructError::new(ErrorKind::InvalidValue, None, i));
+            }
+            value |= i64::from(byte & 0x7F) << (7 * i);
+            if byte & 0x80 == 0 {
+                let shift = 7 * (i + 1);
+                if shift < 64 && byte & 0x40 != 0 {
+                    value |= -1 << shift;
+                }
+                return Ok((Sleb128(value), i + 1));
+            }
+        }
+        Err(ByteStructError::new(
+            ErrorKind::ShortBuffer,
+            None,
+            bytes.len(),
+        ))
+    }
+}
+
+impl From<u64> for VarUint {
+    fn from(value: u64) -> Self {
+        VarUint(value)
+    }
+}
+
+impl From<i64> for VarInt {
+    fn from(value: i64) -> Self {
+        VarInt(value)
+    }
+}
+
+impl From<i64> for Sleb128 {
+    fn from(value: i64) -> Self {
+        Sleb128(value)
+    }
+}
@@ -0,0 +1,85 @@
+//! Packing into and unpacking from a sequence of non-contiguous slices.
+
+use crate::{ByteStructLen, ByteStructWrite};
+use core::marker::PhantomData;
+
+// Structures up to this length are staged on the stack, and longer ones on the heap
+const STAGING_LEN: usize = 256;
+
+#[cfg(feature = "alloc")]
+pub(crate) fn with_staging<R>(len: usize, f: impl FnOnce(&mut [u8]) -> R) -> R {
+    if len <= STAGING_LEN {
+        f(&mut [0; STAGING_LEN][..len])
+    } else {
+        f(&mut alloc::vec![0; len])
+    }
+}
+
+#[cfg(not(feature = "alloc"))]
+pub(crate) fn with_staging<R>(len: usize, f: impl FnOnce(&mut [u8]) -> R) -> R {
+    assert!(
+        len <= STAGING_LEN,
+        "Structures longer than 256 bytes need the alloc feature to be staged"
+    );
+    f(&mut [0; STAGING_LEN][..len])
+}
+
+// The staging length of `T`, which rejects structures that can't be staged at compile time
+struct Staging<T: ?Sized>(PhantomData<T>);
+
+impl<T: ByteStructLen + ?Sized> Staging<T> {
+    #[cfg(feature = "alloc")]
+    const LEN: usize = T::BYTE_LEN;
+
+    #[cfg(not(feature = "alloc"))]
+    const LEN: usize = {
+        assert!(
+            T::BYTE_LEN <= STAGING_LEN,
+            "Structures longer than 256 bytes need the alloc feature to be packed or unpacked vectored"
+        );
+        T::BYTE_LEN
+    };
+}
+
+pub(crate) fn write_vectored<T: ByteStructWrite + ?Sized>(value: &T, bufs: &mut [&mut [u8]]) {
+    let len = Staging::<T>::LEN;
+    if let Some(first) = bufs.first_mut() {
+        if first.len() >= len {
+            value.write_bytes(&mut first[..len]);
+            return;
+        }
+    }
+    with_staging(len, |staging| {
+        value.write_bytes(staging);
+        let mut rest = &staging[..];
+        for buf in bufs.iter_mut() {
+            let len = rest.len().min(buf.len());
+            buf[..len].copy_from_slice(&rest[..len]);
+            rest = &rest[len..];
+        }
+        assert!(rest.is_empty(), "Slices too short for the structure");
+    })
+}
+
+// Passes the first `T::BYTE_LEN` bytes of the slices to `f` as one slice,
+// or all of the bytes if there are fewer than `T::BYTE_LEN`
+pub(crate) fn read_vectored<T: ByteStructLen + ?Sized, R>(
+    bufs: &[&[u8]],
+    f: impl FnOnce(&[u8]) -> R,
+) -> R {
+    let len = Staging::<T>::LEN;
+    if let Some(first) = bufs.first() {
+        if first.len() >= len {
+            return f(&first[..len]);
+        }
+    }
+    with_staging(len, |staging| {
+        let mut filled = 0;
+        for buf in bufs {
+            let copy_len = (len - filled).min(buf.len());
+            staging[filled..filled + copy_len].copy_from_slice(&buf[..copy_len]);
+            filled += copy_len;
+        }
+        f(&staging[..filled])
+    })
+}
@@ -0,0 +1,54 @@
+//! Generic traversal of the fields of a packed structure.
+
+use crate::{ByteStructLayout, FieldInfo};
+use core::any::Any;
+
+/// Receives the fields of a [`ByteStructVisit`] value one by one.
+///
+/// [`ByteStructVisit`]: trait.ByteStructVisit.html
+pub trait FieldVisitor {
+    /// Called once for each field in declaration order.
+    ///
+    /// `value` is the unpacked value of the field, which can be inspected by downcasting it to
+    /// the field type.
+    fn visit_field(&mut self, info: &FieldInfo<'static>, value: &dyn Any);
+}
+
+/// A structure whose fields can be walked by a [`FieldVisitor`].
+///
+/// This trait can be derived by
+/// [`#[derive(ByteStructVisit)]`](https://docs.rs/byte_struct_derive/*/byte_struct_derive/derive.ByteStructVisit.html)
+/// on structures that also derive `ByteStruct`.
+///
+/// [`FieldVisitor`]: trait.FieldVisitor.html
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+/// use core::any::Any;
+///
+/// #[derive(ByteStruct, ByteStructVisit)]
+/// #[byte_struct_le]
+/// struct Point {
+///     x: u16,
+///     y: u16,
+/// }
+///
+/// struct Sum(u32);
+///
+/// impl FieldVisitor for Sum {
+///     fn visit_field(&mut self, _info: &FieldInfo<'static>, value: &dyn Any) {
+///         if let Some(value) = value.downcast_ref::<u16>() {
+///             self.0 += u32::from(*value);
+///         }
+///     }
+/// }
+///
+/// let mut sum = Sum(0);
+/// Point { x: 3, y: 4 }.visit_fields(&mut sum);
+/// assert_eq!(sum.0, 7);
+/// ```
+pub trait ByteStructVisit: ByteStructLayout {
+    /// Calls `visitor` for each field of the structure in declaration order
+    fn visit_fields<V: FieldVisitor + ?Sized>(&self, visitor: &mut V);
+}
@@ -0,0 +1,109 @@
+//! Values packed with their 16-bit words in swapped order.
+
+use crate::{
+    check_len, ByteStructDebugJson, ByteStructError, ByteStructLen, ByteStructUnspecifiedByteOrder,
+};
+use core::fmt;
+
+// The largest value that can be packed, as that of u128
+const MAX_WORD_SWAPPED_LEN: usize = 16;
+
+/// A value packed as 16-bit words, in the reverse of the word order of its byte order.
+///
+/// `T` is usually `u32`, `i32` or `f32`, or their 64-bit counterparts.
+/// The byte order attributes of the containing structure apply to the bytes within each word,
+/// while the words are in the opposite order, which gives the mixed orderings common to
+/// Modbus register maps and to the PDP-11. For the 32-bit value `0x0A0B0C0D`:
+/// - with big-endian byte order, the bytes are `0C 0D 0A 0B`,
+///   which is the word-swapped "CDAB" order of Modbus devices
+/// - with little-endian byte order, the bytes are `0B 0A 0D 0C`,
+///   which is the "BADC" order of the PDP-11, also known as middle-endian
+///
+/// The length of `T` must be a multiple of 2, up to 16 bytes.
+///
+/// # Example
+/// ```
+/// use byte_struct::*;
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_be]
+/// struct Meter {
+///     voltage: WordSwapped<f32>,
+///     energy: WordSwapped<u32>,
+/// }
+///
+/// let meter = Meter::read_bytes(&[0x00, 0x00, 0x43, 0x66, 0x86, 0xa0, 0x00, 0x01][..]);
+/// assert_eq!(meter.voltage.0, 230.0);
+/// assert_eq!(meter.energy.0, 100000);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct WordSwapped<T>(pub T);
+
+impl<T> From<T> for WordSwapped<T> {
+    fn from(value: T) -> Self {
+        WordSwapped(value)
+    }
+}
+
+// Reverses the order of the 16-bit words of `bytes`
+fn swap_words(bytes: &mut [u8]) {
+    let words = bytes.len() / 2;
+    for i in 0..words / 2 {
+        let j = words - 1 - i;
+        bytes.swap(2 * i, 2 * j);
+        bytes.swap(2 * i + 1, 2 * j + 1);
+    }
+}
+
+impl<T: ByteStructUnspecifiedByteOrder> WordSwapped<T> {
+    // Copies the packed bytes of the value into a buffer with the words in the order of `T`
+    fn unswapped(bytes: &[u8]) -> [u8; MAX_WORD_SWAPPED_LEN] {
+        let mut buf = [0; MAX_WORD_SWAPPED_LEN];
+        buf[..Self::BYTE_LEN].copy_from_slice(&bytes[..Self::BYTE_LEN]);
+        swap_words(&mut buf[..Self::BYTE_LEN]);
+        buf
+    }
+}
+
+impl<T: ByteStructLen> ByteStructLen for WordSwapped<T> {
+    const BYTE_LEN: usize = {
+        assert!(
+            T::BYTE_LEN % 2 == 0 && T::BYTE_LEN <= MAX_WORD_SWAPPED_LEN,
+            "WordSwapped supports values of whole words up to 16 bytes"
+        );
+        T::BYTE_LEN
+    };
+}
+
+impl<T: ByteStructUnspecifiedByteOrder> ByteStructUnspecifiedByteOrder for WordSwapped<T> {
+    fn write_bytes_default_le(&self, bytes: &mut [u8]) {
+        let bytes = &mut bytes[..Self::BYTE_LEN];
+        self.0.write_bytes_default_le(bytes);
+        swap_words(bytes);
+    }
+    fn read_bytes_default_le(bytes: &[u8]) -> Self {
+        WordSwapped(T::read_bytes_default_le(&Self::unswapped(bytes)))
+    }
+    fn write_bytes_default_be(&self, bytes: &mut [u8]) {
+        let bytes = &mut bytes[..Self::BYTE_LEN];
+        self.0.write_bytes_default_be(bytes);
+        swap_words(bytes);
+    }
+    fn read_bytes_default_be(bytes: &[u8]) -> Self {
+        WordSwapped(T::read_bytes_default_be(&Self::unswapped(bytes)))
+    }
+    fn try_read_bytes_default_le(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        T::try_read_bytes_default_le(&Self::unswapped(bytes)).map(WordSwapped)
+    }
+    fn try_read_bytes_default_be(bytes: &[u8]) -> Result<Self, ByteStructError> {
+        check_len::<Self>(bytes)?;
+        T::try_read_bytes_default_be(&Self::unswapped(bytes)).map(WordSwapped)
+    }
+}
+
+impl<T: ByteStructDebugJson> ByteStructDebugJson for WordSwapped<T> {
+    fn write_debug_json<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        self.0.write_debug_json(out)
+    }
+}
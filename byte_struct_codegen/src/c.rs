@@ -0,0 +1,754 @@
+//! Generation from the struct declarations of C headers.
+
+use crate::{rust_ident, write_structs, Abi, Error, FieldDef, StructDef};
+use std::collections::HashMap;
+
+/// Generates a structure deriving `ByteStruct` for each struct defined in a C header.
+///
+/// The fields are laid out as the C compiler would for `abi`, with the padding between them
+/// declared with `pad_before` and the padding at the end as a `_padding` byte array.
+/// Each structure asserts its length with `assert_len`, and is named after its `typedef`
+/// if it has one, or after its tag otherwise. Anonymous nested structs are named
+/// after the enclosing struct and their position in it, such as `outer_1`.
+///
+/// Supported member types are the fixed-width integers of `<stdint.h>`, the builtin integer
+/// and floating point types, `_Bool` and `bool` (as `u8`), enums (as `i32`), other structs
+/// of the header, and `typedef`s of these, in arrays of any dimension.
+/// Array lengths can be integer literals or object-like macros defined as one.
+/// `#pragma pack` is honoured, while other preprocessor directives are ignored,
+/// so conditional parts of the header should be resolved beforehand.
+///
+/// Declarations other than structs and `typedef`s, such as function prototypes, are skipped.
+/// Structs that can't be represented, such as those with pointer, union or bit-field members,
+/// and structs containing them are skipped as well, with a comment at the end of the output
+/// explaining why.
+///
+/// Returns an error if a struct definition isn't terminated.
+///
+/// # Example
+/// ```
+/// use byte_struct_codegen::{structs_from_c, Abi};
+///
+/// let header = "
+///     #define NAME_LEN 3
+///     typedef struct {
+///         uint8_t kind;
+///         uint32_t size;
+///         char name[NAME_LEN];
+///     } entry_t;
+/// ";
+/// assert_eq!(
+///     structs_from_c(header, &Abi::X86_64_SYSV).unwrap(),
+///     "#[derive(Clone, PartialEq, Debug, byte_struct::ByteStruct)]\n\
+///      #[byte_struct(endian = \"le\", assert_len = 12)]\n\
+///      #[allow(non_camel_case_types, non_snake_case)]\n\
+///      pub struct entry_t {\n    \
+///          pub kind: u8,\n    \
+///          #[byte_struct(pad_before = 3)]\n    \
+///          pub size: u32,\n    \
+///          pub name: [i8; 3],\n    \
+///          pub _padding: [u8; 1],\n\
+///      }\n"
+/// );
+/// ```
+pub fn structs_from_c(header: &str, abi: &Abi) -> Result<String, Error> {
+    let (tokens, defines) = lex(header);
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        abi,
+        defines,
+        pack: None,
+        pack_stack: Vec::new(),
+        types: HashMap::new(),
+        structs: Vec::new(),
+        skipped: Vec::new(),
+    };
+    parser.header()?;
+    let mut rust = write_structs(&parser.structs);
+    if !rust.is_empty() && !parser.skipped.is_empty() {
+        rust.push('\n');
+    }
+    for reason in &parser.skipped {
+        rust.push_str(&format!("// {}\n", reason));
+    }
+    Ok(rust)
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Ident(String),
+    Number(u64),
+    Str,
+    Punct(char),
+    // A literal that isn't an integer, such as `1.5f`
+    Other,
+    // A `#pragma pack` directive
+    Pack(Pack),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Pack {
+    Set(Option<usize>),
+    Push(Option<usize>),
+    Pop,
+}
+
+// Replaces comments with spaces, keeping line breaks so that lines can still be counted
+fn strip_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                    }
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+                out.push(' ');
+            }
+            ('"' | '\'', _) => {
+                out.push(c);
+                while let Some(d) = chars.next() {
+                    out.push(d);
+                    if d == '\\' {
+                        out.extend(chars.next());
+                    } else if d == c || d == '\n' {
+                        break;
+                    }
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+type Tokens = Vec<(Token, usize)>;
+
+fn lex_line(text: &str, line: usize, tokens: &mut Tokens) {
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&c) = chars
+                .peek()
+                .filter(|c| c.is_ascii_alphanumeric() || **c == '_')
+            {
+                ident.push(c);
+                chars.next();
+            }
+            tokens.push((Token::Ident(ident), line));
+        } else if c.is_ascii_digit() {
+            let mut literal = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphanumeric()) {
+                literal.push(c);
+                chars.next();
+            }
+            let digits = literal.trim_end_matches(['u', 'U', 'l', 'L']);
+            let value = match digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None if digits.len() > 1 && digits.starts_with('0') => {
+                    u64::from_str_radix(&digits[1..], 8)
+                }
+                None => digits.parse(),
+            };
+            let token = value.map_or(Token::Other, Token::Number);
+            tokens.push((token, line));
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            while let Some(d) = chars.next() {
+                if d == '\\' {
+                    chars.next();
+                } else if d == c {
+                    break;
+                }
+            }
+            tokens.push((Token::Str, line));
+        } else {
+            tokens.push((Token::Punct(c), line));
+            chars.next();
+        }
+    }
+}
+
+// Splits the header into tokens, collecting macros defined as an integer literal
+// and turning `#pragma pack` into tokens
+fn lex(header: &str) -> (Tokens, HashMap<String, u64>) {
+    let source = strip_comments(header);
+    let mut tokens = Vec::new();
+    let mut defines = HashMap::new();
+    let mut lines = source.lines().enumerate();
+    while let Some((index, text)) = lines.next() {
+        let line = index + 1;
+        if !text.trim_start().starts_with('#') {
+            lex_line(text, line, &mut tokens);
+            continue;
+        }
+        let mut directive = text.trim_start()[1..].to_string();
+        while directive.ends_with('\\') {
+            directive.pop();
+            directive.push_str(lines.next().map_or("", |(_, text)| text));
+        }
+        let mut words = Vec::new();
+        lex_line(&directive, line, &mut words);
+        let words: Vec<Token> = words.into_iter().map(|(token, _)| token).collect();
+        let ident = |i: usize| match words.get(i) {
+            Some(Token::Ident(ident)) => ident.as_str(),
+            _ => "",
+        };
+        if ident(0) == "define" {
+            let value = match words.get(2..).unwrap_or_default() {
+                [Token::Number(value)] => Some(*value),
+                [Token::Punct('('), Token::Number(value), Token::Punct(')')] => Some(*value),
+                _ => None,
+            };
+            if let (Some(value), Some(Token::Ident(name))) = (value, words.get(1)) {
+                defines.insert(name.clone(), value);
+            }
+        } else if ident(0) == "pragma" && ident(1) == "pack" {
+            let number = words.iter().find_map(|word| match word {
+                Token::Number(value) => Some(*value as usize),
+                _ => None,
+            });
+            let pack = match ident(3) {
+                "push" => Pack::Push(number),
+                "pop" => Pack::Pop,
+                _ => Pack::Set(number),
+            };
+            tokens.push((Token::Pack(pack), line));
+        }
+    }
+    (tokens, defines)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::I64 | Scalar::U64 | Scalar::F64 => 8,
+        }
+    }
+
+    fn rust_name(self) -> &'static str {
+        match self {
+            Scalar::I8 => "i8",
+            Scalar::U8 => "u8",
+            Scalar::I16 => "i16",
+            Scalar::U16 => "u16",
+            Scalar::I32 => "i32",
+            Scalar::U32 => "u32",
+            Scalar::I64 => "i64",
+            Scalar::U64 => "u64",
+            Scalar::F32 => "f32",
+            Scalar::F64 => "f64",
+        }
+    }
+
+    fn from_std_name(name: &str) -> Option<Scalar> {
+        Some(match name {
+            "int8_t" => Scalar::I8,
+            "uint8_t" | "bool" => Scalar::U8,
+            "int16_t" => Scalar::I16,
+            "uint16_t" => Scalar::U16,
+            "int32_t" => Scalar::I32,
+            "uint32_t" => Scalar::U32,
+            "int64_t" => Scalar::I64,
+            "uint64_t" => Scalar::U64,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Type {
+    Scalar(Scalar),
+    Struct {
+        name: String,
+        len: usize,
+        align: usize,
+    },
+    Array(Box<Type>, usize),
+    // A struct that can't be generated
+    Skipped,
+}
+
+const BUILTIN_WORDS: &[&str] = &[
+    "signed", "unsigned", "char", "short", "int", "long", "float", "double", "_Bool",
+];
+
+struct Parser<'a> {
+    tokens: Tokens,
+    pos: usize,
+    abi: &'a Abi,
+    defines: HashMap<String, u64>,
+    pack: Option<usize>,
+    pack_stack: Vec<Option<usize>>,
+    // Types by their name, with struct tags prefixed by `struct `
+    types: HashMap<String, Type>,
+    structs: Vec<StructDef>,
+    // Why each skipped struct can't be generated
+    skipped: Vec<String>,
+}
+
+impl Parser<'_> {
+    // Returns the next token, applying any `#pragma pack` before it
+    fn peek(&mut self) -> Option<&Token> {
+        while let Some((Token::Pack(pack), _)) = self.tokens.get(self.pos) {
+            match *pack {
+                Pack::Set(value) => self.pack = value,
+                Pack::Push(value) => {
+                    self.pack_stack.push(self.pack);
+                    self.pack = value.or(self.pack);
+                }
+                Pack::Pop => self.pack = self.pack_stack.pop().flatten(),
+            }
+            self.pos += 1;
+        }
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, Error> {
+        let index = self.pos.min(self.tokens.len().saturating_sub(1));
+        let line = self.tokens.get(index).map_or(1, |(_, line)| *line);
+        Err(Error::new(line, message))
+    }
+
+    fn is_ident(&mut self, ident: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(i)) if i == ident)
+    }
+
+    fn is_punct(&mut self, punct: char) -> bool {
+        self.peek() == Some(&Token::Punct(punct))
+    }
+
+    // Whether the tokens after `struct` start a definition
+    fn is_struct_body(&self) -> bool {
+        self.peek_at(1) == Some(&Token::Punct('{')) || self.peek_at(2) == Some(&Token::Punct('{'))
+    }
+
+    fn expect_punct(&mut self, punct: char) -> Result<(), Error> {
+        if self.is_punct(punct) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            self.error(format!("expected `{}`", punct))
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<String, Error> {
+        match self.peek().cloned() {
+            Some(Token::Ident(ident)) => {
+                self.pos += 1;
+                Ok(ident)
+            }
+            _ => self.error("expected an identifier"),
+        }
+    }
+
+    // Skips to the end of a declaration that nothing is generated from
+    fn skip_declaration(&mut self) {
+        let mut depth = 0usize;
+        let mut function_body = false;
+        let mut last = None;
+        while let Some(token) = self.next() {
+            match token {
+                // Function bodies aren't followed by `;`
+                Token::Punct('{') if depth == 0 && last == Some(Token::Punct(')')) => {
+                    function_body = true;
+                    depth += 1;
+                }
+                Token::Punct('{' | '(' | '[') => depth += 1,
+                Token::Punct('}' | ')' | ']') => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 && function_body {
+                        return;
+                    }
+                }
+                Token::Punct(';') if depth == 0 => return,
+                _ => (),
+            }
+            last = Some(token);
+        }
+    }
+
+    // Finds the closing brace matching the opening brace at the current token
+    fn block_end(&self) -> Option<usize> {
+        let mut depth = 0usize;
+        (self.pos..self.tokens.len()).find(|&i| {
+            match self.tokens[i].0 {
+                Token::Punct('{') => depth += 1,
+                Token::Punct('}') => depth = depth.saturating_sub(1),
+                _ => (),
+            }
+            depth == 0
+        })
+    }
+
+    fn header(&mut self) -> Result<(), Error> {
+        while let Some(token) = self.peek().cloned() {
+            match token {
+                Token::Ident(ident) if ident == "typedef" => {
+                    self.pos += 1;
+                    self.typedef();
+                }
+                Token::Ident(ident)
+                    if ident == "struct" && self.peek_at(2) == Some(&Token::Punct('{')) =>
+                {
+                    self.pos += 1;
+                    self.struct_specifier(None, None)?;
+                    self.skip_declaration();
+                }
+                // The `extern "C" {` of headers shared with C++, and its closing brace
+                Token::Ident(ident)
+                    if ident == "extern" && self.peek_at(1) == Some(&Token::Str) =>
+                {
+                    self.pos += 2;
+                    if self.is_punct('{') {
+                        self.pos += 1;
+                    }
+                }
+                Token::Punct('}') => self.pos += 1,
+                _ => self.skip_declaration(),
+            }
+        }
+        Ok(())
+    }
+
+    // Typedefs of types that can't be generated are skipped, leaving their names unknown
+    fn typedef(&mut self) {
+        let start = self.pos;
+        if self.typedef_names().is_err() {
+            self.pos = start;
+        }
+        self.skip_declaration();
+    }
+
+    fn typedef_names(&mut self) -> Result<(), Error> {
+        let mut alias = None;
+        if self.is_ident("struct") && self.is_struct_body() {
+            // Names the struct after the first declarator following its body
+            let mut depth = 0;
+            let mut i = self.pos;
+            while let Some((token, _)) = self.tokens.get(i) {
+                match token {
+                    Token::Punct('{') => depth += 1,
+                    Token::Punct('}') => depth -= 1,
+                    Token::Punct(';' | ',') if depth == 0 => break,
+                    _ => (),
+                }
+                i += 1;
+            }
+            if let Some((Token::Ident(name), _)) = self.tokens.get(i - 1) {
+                alias = Some(name.clone());
+            }
+        }
+        let ty = self.specifier(alias.as_deref(), None)?;
+        loop {
+            if self.is_punct('*') || self.is_punct('(') {
+                return Ok(());
+            }
+            let name = self.expect_ident()?;
+            let ty = match ty {
+                Type::Skipped => Type::Skipped,
+                _ => self.array_dims(ty.clone())?,
+            };
+            self.types.insert(name, ty);
+            if !self.is_punct(',') {
+                return Ok(());
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn array_dims(&mut self, element: Type) -> Result<Type, Error> {
+        let mut dims = Vec::new();
+        while self.is_punct('[') {
+            self.pos += 1;
+            let len = match self.next() {
+                Some(Token::Number(len)) => len,
+                Some(Token::Ident(name)) if self.defines.contains_key(&name) => self.defines[&name],
+                Some(Token::Punct(']')) => {
+                    return self.error("flexible array members are not supported");
+                }
+                _ => return self.error("array lengths must be a number or a macro defined as one"),
+            };
+            if len == 0 {
+                return self.error("zero-length arrays are not supported");
+            }
+            dims.push(len as usize);
+            self.expect_punct(']')?;
+        }
+        Ok(dims
+            .into_iter()
+            .rev()
+            .fold(element, |ty, len| Type::Array(Box::new(ty), len)))
+    }
+
+    // Parses the type of a declaration, up to its declarators.
+    // A struct defined in it is named `name`, or else its tag, or else `fallback`
+    fn specifier(&mut self, name: Option<&str>, fallback: Option<&str>) -> Result<Type, Error> {
+        while self.is_ident("const") || self.is_ident("volatile") {
+            self.pos += 1;
+        }
+        let ty = match self.peek().cloned() {
+            Some(Token::Ident(ident)) if ident == "struct" => {
+                if self.is_struct_body() {
+                    self.pos += 1;
+                    self.struct_specifier(name, fallback)?
+                } else {
+                    self.pos += 1;
+                    let tag = self.expect_ident()?;
+                    self.lookup(&format!("struct {}", tag))?
+                }
+            }
+            Some(Token::Ident(ident)) if ident == "void" => {
+                return self.error("pointer members are not supported");
+            }
+            Some(Token::Ident(ident)) if ident == "union" => {
+                return self.error("unions are not supported");
+            }
+            Some(Token::Ident(ident)) if ident == "enum" => {
+                self.pos += 1;
+                if matches!(self.peek(), Some(Token::Ident(_))) {
+                    self.pos += 1;
+                }
+                if self.is_punct('{') {
+                    while self.next().is_some_and(|token| token != Token::Punct('}')) {}
+                }
+                Type::Scalar(Scalar::I32)
+            }
+            Some(Token::Ident(ident)) if BUILTIN_WORDS.contains(&ident.as_str()) => {
+                Type::Scalar(self.builtin()?)
+            }
+            Some(Token::Ident(ident)) => {
+                self.pos += 1;
+                match Scalar::from_std_name(&ident) {
+                    Some(scalar) => Type::Scalar(scalar),
+                    None => self.lookup(&ident)?,
+                }
+            }
+            _ => return self.error("expected a type"),
+        };
+        while self.is_ident("const") || self.is_ident("volatile") {
+            self.pos += 1;
+        }
+        Ok(ty)
+    }
+
+    fn lookup(&self, name: &str) -> Result<Type, Error> {
+        match self.types.get(name) {
+            Some(Type::Skipped) => self.error(format!("`{}` is skipped", name)),
+            Some(ty) => Ok(ty.clone()),
+            None => self.error(format!("unknown type `{}`", name)),
+        }
+    }
+
+    fn builtin(&mut self) -> Result<Scalar, Error> {
+        let mut words = Vec::new();
+        while let Some(Token::Ident(ident)) = self.peek() {
+            if !BUILTIN_WORDS.contains(&ident.as_str()) {
+                break;
+            }
+            words.push(ident.clone());
+            self.pos += 1;
+        }
+        let has = |word: &str| words.iter().any(|w| w == word);
+        let pick = |signed, unsigned| if has("unsigned") { unsigned } else { signed };
+        let longs = words.iter().filter(|w| *w == "long").count();
+        Ok(if has("double") && longs != 0 {
+            return self.error("`long double` is not supported");
+        } else if has("double") {
+            Scalar::F64
+        } else if has("float") {
+            Scalar::F32
+        } else if has("_Bool") {
+            Scalar::U8
+        } else if has("char") && !has("signed") && !has("unsigned") {
+            if self.abi.char_signed {
+                Scalar::I8
+            } else {
+                Scalar::U8
+            }
+        } else if has("char") {
+            pick(Scalar::I8, Scalar::U8)
+        } else if has("short") {
+            pick(Scalar::I16, Scalar::U16)
+        } else if longs == 2 || longs == 1 && self.abi.long_size == 8 {
+            pick(Scalar::I64, Scalar::U64)
+        } else {
+            pick(Scalar::I32, Scalar::U32)
+        })
+    }
+
+    fn size_align(&self, ty: &Type) -> (usize, usize) {
+        match ty {
+            Type::Scalar(scalar) => {
+                let size = scalar.size();
+                (size, size.min(self.abi.max_scalar_align))
+            }
+            Type::Struct { len, align, .. } => (*len, *align),
+            Type::Array(element, len) => {
+                let (size, align) = self.size_align(element);
+                (size * len, align)
+            }
+            Type::Skipped => unreachable!(),
+        }
+    }
+
+    fn rust_type(ty: &Type) -> String {
+        match ty {
+            Type::Scalar(scalar) => scalar.rust_name().to_string(),
+            Type::Struct { name, .. } => rust_ident(name),
+            Type::Array(element, len) => format!("[{}; {}]", Self::rust_type(element), len),
+            Type::Skipped => unreachable!(),
+        }
+    }
+
+    // Parses a struct definition after `struct`. Structs defined at the top level,
+    // without a `fallback` name, are skipped if they can't be generated
+    fn struct_specifier(
+        &mut self,
+        name: Option<&str>,
+        fallback: Option<&str>,
+    ) -> Result<Type, Error> {
+        let tag = match self.peek().cloned() {
+            Some(Token::Ident(tag)) => {
+                self.pos += 1;
+                Some(tag)
+            }
+            _ => None,
+        };
+        let name = match name.or(tag.as_deref()).or(fallback) {
+            Some(name) => name.to_string(),
+            None => return self.error("anonymous structs need a typedef name"),
+        };
+        let Some(end) = self.block_end() else {
+            return self.error(format!("`{}` isn't terminated", name));
+        };
+        let ty = match self.struct_body(&name) {
+            Ok(ty) => ty,
+            Err(e) if fallback.is_none() => {
+                self.pos = end + 1;
+                self.skipped.push(format!(
+                    "`{}` is skipped: {} (line {})",
+                    name,
+                    e.message(),
+                    e.line()
+                ));
+                Type::Skipped
+            }
+            Err(e) => return Err(e),
+        };
+        if let Some(tag) = tag {
+            self.types.insert(format!("struct {}", tag), ty.clone());
+        }
+        self.types.insert(name, ty.clone());
+        Ok(ty)
+    }
+
+    fn struct_body(&mut self, name: &str) -> Result<Type, Error> {
+        self.expect_punct('{')?;
+        let mut fields = Vec::new();
+        let mut offset = 0usize;
+        let mut struct_align = 1;
+        let mut anonymous = 0;
+        while !self.is_punct('}') {
+            let fallback = format!("{}_{}", name, anonymous + 1);
+            let base = self.specifier(None, Some(&fallback))?;
+            if matches!(&base, Type::Struct { name, .. } if *name == fallback) {
+                anonymous += 1;
+            }
+            loop {
+                if self.is_punct('*') {
+                    return self.error("pointer members are not supported");
+                }
+                let field_name = self.expect_ident()?;
+                let ty = self.array_dims(base.clone())?;
+                if self.is_punct(':') {
+                    return self.error("bit-field members are not supported");
+                }
+                let (size, align) = self.size_align(&ty);
+                let align = self.pack.map_or(align, |pack| align.min(pack));
+                struct_align = struct_align.max(align);
+                let start = offset.next_multiple_of(align);
+                fields.push(FieldDef {
+                    name: field_name,
+                    ty: Self::rust_type(&ty),
+                    pad_before: start - offset,
+                    ..FieldDef::default()
+                });
+                offset = start + size;
+                if !self.is_punct(',') {
+                    break;
+                }
+                self.pos += 1;
+            }
+            self.expect_punct(';')?;
+        }
+        self.pos += 1;
+        if fields.is_empty() {
+            return self.error("empty structs are not supported");
+        }
+        let len = offset.next_multiple_of(struct_align);
+        if len > offset {
+            fields.push(FieldDef {
+                name: "_padding".to_string(),
+                ty: format!("[u8; {}]", len - offset),
+                ..FieldDef::default()
+            });
+        }
+        self.structs.push(StructDef {
+            name: name.to_string(),
+            big_endian: Some(self.abi.big_endian),
+            len: Some(len),
+            fields,
+            ..StructDef::default()
+        });
+        Ok(Type::Struct {
+            name: name.to_string(),
+            len,
+            align: struct_align,
+        })
+    }
+}
@@ -0,0 +1,426 @@
+//! Generation from Kaitai Struct definitions.
+
+use crate::{write_structs, Error, FieldDef, StructDef};
+use std::collections::HashMap;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::Marker;
+
+/// Generates a structure deriving `ByteStruct` for each type of a Kaitai Struct definition.
+///
+/// This accepts the subset of `.ksy` files that describe fixed-size sequences.
+/// The type named by `meta/id` and each type in `types` become a structure, named in camel case,
+/// with a field for each attribute of `seq` in order. Attributes can be:
+/// - builtin integer and floating point types such as `u1`, `s4be` and `f8`,
+///   packed with the byte order of their suffix, or of `meta/endian` otherwise
+/// - other types of the definition, by name
+/// - raw bytes with a literal `size`, optionally with `type: str`, as byte arrays
+/// - fixed `contents`, as byte arrays checked against the contents when unpacking
+///
+/// and can be repeated a literal number of times with `repeat: expr`, as arrays.
+/// `doc` keys become doc comments, and `enum` keys are ignored, leaving the integers as they are.
+/// Top-level keys other than `meta`, `doc`, `seq` and `types`, such as `instances`,
+/// are ignored as well, as they don't change the layout of the sequence.
+///
+/// Returns an error for anything else, such as attributes with conditions,
+/// sizes that are expressions, or types switched on a value.
+///
+/// # Example
+/// ```
+/// use byte_struct_codegen::structs_from_kaitai;
+///
+/// let ksy = "
+/// meta:
+///   id: bmp_header
+///   endian: le
+/// seq:
+///   - id: magic
+///     contents: BM
+///   - id: file_size
+///     type: u4
+///   - id: reserved
+///     size: 4
+/// ";
+/// assert_eq!(
+///     structs_from_kaitai(ksy).unwrap(),
+///     "#[derive(Clone, PartialEq, Debug, byte_struct::ByteStruct)]\n\
+///      #[byte_struct(endian = \"le\")]\n\
+///      #[allow(non_camel_case_types, non_snake_case)]\n\
+///      pub struct BmpHeader {\n    \
+///          #[byte_struct(validate = \"BmpHeader::is_valid_magic\")]\n    \
+///          pub magic: [u8; 2],\n    \
+///          pub file_size: u32,\n    \
+///          pub reserved: [u8; 4],\n\
+///      }\n\
+///      \n\
+///      impl BmpHeader {\n    \
+///          /// The value `magic` is required to hold\n    \
+///          pub const MAGIC: [u8; 2] = [0x42, 0x4D];\n\
+///      \n    \
+///          fn is_valid_magic(value: &[u8; 2]) -> bool {\n        \
+///              *value == Self::MAGIC\n    \
+///          }\n\
+///      }\n"
+/// );
+/// ```
+pub fn structs_from_kaitai(ksy: &str) -> Result<String, Error> {
+    let root = parse_yaml(ksy)?;
+    let meta = root.require("meta")?;
+    let id = meta.require("id")?.as_str()?;
+    let mut names = HashMap::new();
+    collect_types(&root, &mut names)?;
+    let mut defs = Vec::new();
+    generate_type(&root, &camel_case(id), None, &names, &mut defs)?;
+    Ok(write_structs(&defs))
+}
+
+// A YAML node, with the line it starts on
+struct Node {
+    line: usize,
+    value: Value,
+}
+
+enum Value {
+    Scalar(String),
+    Seq(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+impl Node {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, Error> {
+        Err(Error::new(self.line, message))
+    }
+
+    fn get(&self, key: &str) -> Option<&Node> {
+        match &self.value {
+            Value::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn require(&self, key: &str) -> Result<&Node, Error> {
+        match self.get(key) {
+            Some(node) => Ok(node),
+            None => self.error(format!("missing `{}`", key)),
+        }
+    }
+
+    fn as_str(&self) -> Result<&str, Error> {
+        match &self.value {
+            Value::Scalar(value) => Ok(value),
+            _ => self.error("expected a single value"),
+        }
+    }
+
+    fn as_seq(&self) -> Result<&[Node], Error> {
+        match &self.value {
+            Value::Seq(nodes) => Ok(nodes),
+            _ => self.error("expected a list"),
+        }
+    }
+
+    fn as_map(&self) -> Result<&[(String, Node)], Error> {
+        match &self.value {
+            Value::Map(entries) => Ok(entries),
+            _ => self.error("expected a map"),
+        }
+    }
+
+    fn as_u64(&self) -> Result<u64, Error> {
+        let text = self.as_str()?.replace('_', "");
+        let value = match text.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => text.parse(),
+        };
+        value.or_else(|_| self.error("expected an integer literal"))
+    }
+}
+
+// Assembles the nodes from parser events
+#[derive(Default)]
+struct TreeBuilder {
+    // Nodes being built, with the key they go under in their parent map
+    stack: Vec<(Option<String>, Node)>,
+    root: Option<Node>,
+    error: Option<Error>,
+}
+
+impl TreeBuilder {
+    fn add(&mut self, node: Node) {
+        let Some((_, parent)) = self.stack.last_mut() else {
+            self.root = Some(node);
+            return;
+        };
+        match &mut parent.value {
+            Value::Seq(nodes) => nodes.push(node),
+            Value::Map(_) => match self.stack.pop() {
+                // The node is the value of a pending key
+                Some((Some(key), mut parent)) => {
+                    if let Value::Map(entries) = &mut parent.value {
+                        entries.push((key, node));
+                    }
+                    self.stack.push((None, parent));
+                }
+                Some((None, parent)) => {
+                    let key = match node.value {
+                        Value::Scalar(key) => key,
+                        _ => {
+                            self.error
+                                .get_or_insert(Error::new(node.line, "keys must be text"));
+                            String::new()
+                        }
+                    };
+                    self.stack.push((Some(key), parent));
+                }
+                None => unreachable!(),
+            },
+            Value::Scalar(_) => unreachable!(),
+        }
+    }
+}
+
+impl MarkedEventReceiver for TreeBuilder {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        let line = mark.line();
+        match event {
+            Event::Scalar(value, ..) => self.add(Node {
+                line,
+                value: Value::Scalar(value),
+            }),
+            Event::SequenceStart(..) => self.stack.push((
+                None,
+                Node {
+                    line,
+                    value: Value::Seq(Vec::new()),
+                },
+            )),
+            Event::MappingStart(..) => self.stack.push((
+                None,
+                Node {
+                    line,
+                    value: Value::Map(Vec::new()),
+                },
+            )),
+            Event::SequenceEnd | Event::MappingEnd => {
+                if let Some((_, node)) = self.stack.pop() {
+                    self.add(node);
+                }
+            }
+            Event::Alias(_) => {
+                self.error
+                    .get_or_insert(Error::new(line, "aliases are not supported"));
+            }
+            _ => (),
+        }
+    }
+}
+
+fn parse_yaml(text: &str) -> Result<Node, Error> {
+    let mut builder = TreeBuilder::default();
+    Parser::new_from_str(text)
+        .load(&mut builder, false)
+        .map_err(|e| Error::new(e.marker().line(), e.info()))?;
+    if let Some(error) = builder.error {
+        return Err(error);
+    }
+    match builder.root {
+        Some(root) => Ok(root),
+        None => Err(Error::new(1, "the definition is empty")),
+    }
+}
+
+fn camel_case(id: &str) -> String {
+    id.split('_')
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+        })
+        .collect()
+}
+
+// Names the structure of each type, including nested ones
+fn collect_types(spec: &Node, names: &mut HashMap<String, String>) -> Result<(), Error> {
+    let Some(types) = spec.get("types") else {
+        return Ok(());
+    };
+    for (id, nested) in types.as_map()? {
+        if names.insert(id.clone(), camel_case(id)).is_some() {
+            return nested.error(format!("type `{}` is defined more than once", id));
+        }
+        collect_types(nested, names)?;
+    }
+    Ok(())
+}
+
+// Reads the byte order of `meta/endian`
+fn meta_endian(spec: &Node) -> Result<Option<bool>, Error> {
+    let Some(endian) = spec.get("meta").and_then(|meta| meta.get("endian")) else {
+        return Ok(None);
+    };
+    match endian.value {
+        Value::Scalar(ref value) if value == "le" => Ok(Some(false)),
+        Value::Scalar(ref value) if value == "be" => Ok(Some(true)),
+        Value::Scalar(_) => endian.error("`endian` must be `le` or `be`"),
+        _ => endian.error("switched byte orders are not supported"),
+    }
+}
+
+fn generate_type(
+    spec: &Node,
+    name: &str,
+    parent_endian: Option<bool>,
+    names: &HashMap<String, String>,
+    defs: &mut Vec<StructDef>,
+) -> Result<(), Error> {
+    let big_endian = meta_endian(spec)?.or(parent_endian);
+    let seq = spec.require("seq")?.as_seq()?;
+    if seq.is_empty() {
+        return spec.error("empty sequences are not supported");
+    }
+    let mut fields = Vec::new();
+    for (index, attr) in seq.iter().enumerate() {
+        fields.push(generate_field(attr, index, big_endian, names)?);
+    }
+    defs.push(StructDef {
+        name: name.to_string(),
+        doc: spec
+            .get("doc")
+            .map(Node::as_str)
+            .transpose()?
+            .map(str::to_string),
+        big_endian,
+        fields,
+        ..StructDef::default()
+    });
+    if let Some(types) = spec.get("types") {
+        for (id, nested) in types.as_map()? {
+            generate_type(nested, &names[id], big_endian, names, defs)?;
+        }
+    }
+    Ok(())
+}
+
+const ATTRIBUTE_KEYS: &[&str] = &[
+    "id",
+    "type",
+    "size",
+    "contents",
+    "repeat",
+    "repeat-expr",
+    "doc",
+    "enum",
+    "encoding",
+];
+
+fn generate_field(
+    attr: &Node,
+    index: usize,
+    big_endian: Option<bool>,
+    names: &HashMap<String, String>,
+) -> Result<FieldDef, Error> {
+    for (key, value) in attr.as_map()? {
+        if !ATTRIBUTE_KEYS.contains(&key.as_str()) {
+            return value.error(format!("`{}` is not supported", key));
+        }
+    }
+    let mut field = FieldDef {
+        name: match attr.get("id") {
+            Some(id) => id.as_str()?.to_string(),
+            None => format!("unnamed{}", index),
+        },
+        doc: attr
+            .get("doc")
+            .map(Node::as_str)
+            .transpose()?
+            .map(str::to_string),
+        ..FieldDef::default()
+    };
+    let size = attr.get("size").map(Node::as_u64).transpose()?;
+    field.ty = match (attr.get("type"), size, attr.get("contents")) {
+        (None, None, Some(contents)) => {
+            let bytes = contents_bytes(contents)?;
+            let ty = format!("[u8; {}]", bytes.len());
+            field.contents = Some(bytes);
+            ty
+        }
+        (None, Some(size), None) => format!("[u8; {}]", size),
+        (Some(ty), Some(size), None) if ty.as_str()? == "str" => format!("[u8; {}]", size),
+        (Some(ty), None, None) => {
+            let ty_name = ty.as_str()?;
+            match builtin_type(ty_name) {
+                Some((rust, len, suffix)) => {
+                    if len > 1 && suffix.is_none() && big_endian.is_none() {
+                        return ty.error(format!(
+                            "`{}` needs a byte order, from a suffix or `meta/endian`",
+                            ty_name
+                        ));
+                    }
+                    field.big_endian = suffix.filter(|&big| Some(big) != big_endian);
+                    rust.to_string()
+                }
+                None => match names.get(ty_name) {
+                    Some(name) => name.clone(),
+                    None => return ty.error(format!("unknown type `{}`", ty_name)),
+                },
+            }
+        }
+        _ => {
+            return attr.error(
+                "attributes need exactly one of `type`, `size` or `contents`, \
+                 or `type: str` with `size`",
+            );
+        }
+    };
+    if let Some(repeat) = attr.get("repeat") {
+        if repeat.as_str()? != "expr" {
+            return repeat.error("only `repeat: expr` is supported");
+        }
+        let count = attr.require("repeat-expr")?.as_u64()?;
+        field.ty = format!("[{}; {}]", field.ty, count);
+    }
+    Ok(field)
+}
+
+// Returns the Rust type, the length, and the byte order of the suffix of a builtin type
+fn builtin_type(name: &str) -> Option<(&'static str, usize, Option<bool>)> {
+    let (base, suffix) = match name.len().checked_sub(2).map(|i| name.split_at(i)) {
+        Some((base, "le")) => (base, Some(false)),
+        Some((base, "be")) => (base, Some(true)),
+        _ => (name, None),
+    };
+    let (rust, len) = match base {
+        "u1" => ("u8", 1),
+        "u2" => ("u16", 2),
+        "u4" => ("u32", 4),
+        "u8" => ("u64", 8),
+        "s1" => ("i8", 1),
+        "s2" => ("i16", 2),
+        "s4" => ("i32", 4),
+        "s8" => ("i64", 8),
+        "f4" => ("f32", 4),
+        "f8" => ("f64", 8),
+        _ => return None,
+    };
+    Some((rust, len, suffix))
+}
+
+// Reads `contents`, given as text or as a list of bytes and text
+fn contents_bytes(contents: &Node) -> Result<Vec<u8>, Error> {
+    let parts = match &contents.value {
+        Value::Seq(parts) => parts.iter().collect(),
+        _ => vec![contents],
+    };
+    let mut bytes = Vec::new();
+    for part in parts {
+        let text = part.as_str()?;
+        match part.as_u64() {
+            Ok(byte) if byte <= 0xFF => bytes.push(byte as u8),
+            Ok(_) => return part.error("bytes must be at most 255"),
+            Err(_) => bytes.extend_from_slice(text.as_bytes()),
+        }
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,240 @@
+//! Generates Rust source of [`byte_struct`](https://docs.rs/byte_struct) types
+//! from existing format descriptions, for use in build scripts.
+//!
+//! [`structs_from_c`] turns the struct declarations of a C header into structures
+//! that derive `ByteStruct`, with the padding the C compiler would insert for a given [`Abi`].
+//! With the `kaitai` feature, `structs_from_kaitai` does the same for the fixed-size sequences
+//! of a Kaitai Struct `.ksy` definition.
+//!
+//! # Example
+//! In `build.rs`:
+//! ```no_run
+//! use byte_struct_codegen::{structs_from_c, Abi};
+//! use std::path::Path;
+//!
+//! let header = std::fs::read_to_string("vendor/sdk.h").unwrap();
+//! let rust = structs_from_c(&header, &Abi::X86_64_SYSV).unwrap();
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! std::fs::write(Path::new(&out_dir).join("sdk.rs"), rust).unwrap();
+//! println!("cargo:rerun-if-changed=vendor/sdk.h");
+//! ```
+//!
+//! And in the crate:
+//! ```ignore
+//! mod sdk {
+//!     include!(concat!(env!("OUT_DIR"), "/sdk.rs"));
+//! }
+//! ```
+
+mod c;
+#[cfg(feature = "kaitai")]
+mod kaitai;
+
+pub use c::structs_from_c;
+#[cfg(feature = "kaitai")]
+pub use kaitai::structs_from_kaitai;
+
+use std::fmt;
+
+/// The properties of a C ABI that determine the layout of structs.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Abi {
+    /// Whether scalars are stored as big-endian
+    pub big_endian: bool,
+    /// The size of `long` and `unsigned long` in bytes
+    pub long_size: usize,
+    /// The largest alignment of a scalar within a struct.
+    /// Scalars are aligned to their size up to this value
+    pub max_scalar_align: usize,
+    /// Whether plain `char` is signed
+    pub char_signed: bool,
+}
+
+impl Abi {
+    /// The System V ABI on x86-64, used by Linux and macOS
+    pub const X86_64_SYSV: Abi = Abi {
+        big_endian: false,
+        long_size: 8,
+        max_scalar_align: 8,
+        char_signed: true,
+    };
+    /// The Windows ABI on x86-64
+    pub const X86_64_WINDOWS: Abi = Abi {
+        big_endian: false,
+        long_size: 4,
+        max_scalar_align: 8,
+        char_signed: true,
+    };
+    /// The System V ABI on x86, where 8-byte scalars are only aligned to 4 bytes within structs
+    pub const I386_SYSV: Abi = Abi {
+        big_endian: false,
+        long_size: 4,
+        max_scalar_align: 4,
+        char_signed: true,
+    };
+    /// The ABI of Linux on AArch64, where plain `char` is unsigned
+    pub const AARCH64_LINUX: Abi = Abi {
+        big_endian: false,
+        long_size: 8,
+        max_scalar_align: 8,
+        char_signed: false,
+    };
+}
+
+/// An error in the input of code generation.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Error {
+    line: usize,
+    message: String,
+}
+
+impl Error {
+    pub(crate) fn new(line: usize, message: impl Into<String>) -> Self {
+        Error {
+            line,
+            message: message.into(),
+        }
+    }
+
+    /// The line of the input where the error is, starting from 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// A description of the error
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+// A structure to generate, with the fields in declaration order
+#[derive(Default)]
+pub(crate) struct StructDef {
+    pub name: String,
+    pub doc: Option<String>,
+    pub big_endian: Option<bool>,
+    pub len: Option<usize>,
+    pub fields: Vec<FieldDef>,
+}
+
+#[derive(Default)]
+pub(crate) struct FieldDef {
+    pub name: String,
+    pub doc: Option<String>,
+    pub ty: String,
+    pub pad_before: usize,
+    pub big_endian: Option<bool>,
+    // The bytes the field is required to hold
+    pub contents: Option<Vec<u8>>,
+}
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+// Turns a name from the input into a Rust identifier
+pub(crate) fn rust_ident(name: &str) -> String {
+    match name {
+        "self" | "Self" | "super" | "crate" | "_" => format!("{}_", name),
+        _ if KEYWORDS.contains(&name) => format!("r#{}", name),
+        _ => name.to_string(),
+    }
+}
+
+fn write_doc(out: &mut String, doc: Option<&str>, indent: &str) {
+    for line in doc.iter().flat_map(|doc| doc.trim_end().lines()) {
+        if line.is_empty() {
+            out.push_str(&format!("{}///\n", indent));
+        } else {
+            out.push_str(&format!("{}/// {}\n", indent, line));
+        }
+    }
+}
+
+fn endian_option(big_endian: Option<bool>) -> Option<String> {
+    big_endian.map(|big| format!("endian = \"{}\"", if big { "be" } else { "le" }))
+}
+
+fn write_options(out: &mut String, options: &[Option<String>], indent: &str) {
+    let options: Vec<_> = options.iter().flatten().map(String::as_str).collect();
+    if !options.is_empty() {
+        out.push_str(&format!(
+            "{}#[byte_struct({})]\n",
+            indent,
+            options.join(", ")
+        ));
+    }
+}
+
+pub(crate) fn write_structs(defs: &[StructDef]) -> String {
+    let mut out = String::new();
+    for (i, def) in defs.iter().enumerate() {
+        if i != 0 {
+            out.push('\n');
+        }
+        let name = rust_ident(&def.name);
+        write_doc(&mut out, def.doc.as_deref(), "");
+        out.push_str("#[derive(Clone, PartialEq, Debug, byte_struct::ByteStruct)]\n");
+        let assert_len = def.len.map(|len| format!("assert_len = {}", len));
+        write_options(&mut out, &[endian_option(def.big_endian), assert_len], "");
+        out.push_str("#[allow(non_camel_case_types, non_snake_case)]\n");
+        out.push_str(&format!("pub struct {} {{\n", name));
+        for field in &def.fields {
+            write_doc(&mut out, field.doc.as_deref(), "    ");
+            let pad_before = Some(field.pad_before)
+                .filter(|&pad| pad != 0)
+                .map(|pad| format!("pad_before = {}", pad));
+            let validate = field
+                .contents
+                .as_ref()
+                .map(|_| format!("validate = \"{}::is_valid_{}\"", name, field.name));
+            let options = [pad_before, endian_option(field.big_endian), validate];
+            write_options(&mut out, &options, "    ");
+            out.push_str(&format!(
+                "    pub {}: {},\n",
+                rust_ident(&field.name),
+                field.ty
+            ));
+        }
+        out.push_str("}\n");
+        let checked: Vec<_> = def.fields.iter().filter(|f| f.contents.is_some()).collect();
+        if checked.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\nimpl {} {{\n", name));
+        for (i, field) in checked.into_iter().enumerate() {
+            if i != 0 {
+                out.push('\n');
+            }
+            let contents = field.contents.as_ref().unwrap();
+            let bytes: Vec<_> = contents.iter().map(|b| format!("0x{:02X}", b)).collect();
+            out.push_str(&format!(
+                "    /// The value `{}` is required to hold\n    \
+                 pub const {}: [u8; {}] = [{}];\n\n    \
+                 fn is_valid_{}(value: &[u8; {}]) -> bool {{\n        \
+                 *value == Self::{}\n    }}\n",
+                field.name,
+                field.name.to_uppercase(),
+                contents.len(),
+                bytes.join(", "),
+                field.name,
+                contents.len(),
+                field.name.to_uppercase(),
+            ));
+        }
+        out.push_str("}\n");
+    }
+    out
+}
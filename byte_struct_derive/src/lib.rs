@@ -12,7 +12,8 @@ extern crate proc_macro;
 
 use crate::proc_macro::TokenStream;
 use proc_macro2::Span;
-use quote::quote;
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::spanned::Spanned;
 
 #[derive(Clone, Copy)]
 enum Endianness {
@@ -44,6 +45,9 @@ enum Endianness {
 /// it must implement [`ByteStruct`] as well, so that its packing method is not byte-order-dependent.
 /// This is true for all `ByteStruct`-derived structures, but not for primitive types.
 ///
+/// The generated code refers to items of `byte_struct` by their full paths,
+/// so they don't need to be imported where the derive is used.
+///
 /// [`ByteStruct`]: https://docs.rs/byte_struct/*/byte_struct/trait.ByteStruct.html
 /// [`ByteStructUnspecifiedByteOrder`]: https://docs.rs/byte_struct/*/byte_struct/trait.ByteStructUnspecifiedByteOrder.html
 ///
@@ -91,7 +95,634 @@ enum Endianness {
 ///     g: Struct2,
 /// }
 /// ```
-#[proc_macro_derive(ByteStruct, attributes(byte_struct_le, byte_struct_be))]
+///
+/// ## Unified attribute
+///
+/// All options can also be given in a single `#[byte_struct(...)]` attribute,
+/// which avoids collisions with attributes of other derives. Options can be combined freely,
+/// and the legacy attributes below remain supported.
+///
+/// On the struct:
+/// - `endian = "le"` / `endian = "be"`: same as `#[byte_struct_le]` / `#[byte_struct_be]`
+/// - `setters`: same as `#[byte_struct_setters]`
+/// - `trailer`: lays out the fields backwards from the end of the structure,
+///   see [Trailers](#trailers)
+/// - `getters`: generates functions that unpack a single field, see [Single field getters](#single-field-getters)
+/// - `assert_len = N`: same as `#[byte_struct_assert_len(N)]`
+/// - `sanitize = "path"` / `sanitize(decode = "path", encode = "path")`:
+///   transforms the raw bytes of the whole struct, see [Buffer sanitizer](#buffer-sanitizer)
+/// - `no_unspecified`: keeps the struct from being embedded in other structures,
+///   see [Standalone structures](#standalone-structures)
+/// - `read_only` / `write_only`: only implements unpacking or packing,
+///   see [Read-only and write-only structures](#read-only-and-write-only-structures)
+/// - `tag = N`: implements `ByteStructTag` with `TAG = N`, which identifies the struct
+///   as a message in a `Registry`
+/// - `register = N`: same as `#[byte_struct_register(addr = N)]`
+/// - `columns`: generates a struct of columns and functions that unpack many records into it,
+///   see [Columnar unpacking](#columnar-unpacking)
+/// - `arbitrary`: implements `arbitrary::Arbitrary` by unpacking `BYTE_LEN` bytes
+///   of the fuzzer input with `try_read_bytes`, rejecting the input if they fail to unpack.
+///   This requires the `arbitrary` feature of `byte_struct`
+/// - `context = "Type"`: implements `ByteStructReadCtx<Type>` and `ByteStructWriteCtx<Type>`
+///   instead of `ByteStructRead` and `ByteStructWrite`, see [Parse context](#parse-context)
+/// - `crate = "path"`: the path to the `byte_struct` crate used in the generated code,
+///   for crates that use it through a re-export. Defaults to `::byte_struct`.
+///   This is also accepted by `#[derive(ByteStructVisit)]` and `#[derive(ByteStructDebugJson)]`
+///
+/// On a field:
+/// - `endian = "le"` / `endian = "be"`: same as `#[byte_struct_le]` / `#[byte_struct_be]`
+/// - `pad_before = N`: inserts `N` bytes of padding before the field,
+///   which are packed as zeros and ignored when unpacking
+/// - `offset = N`: same as `#[byte_struct_offset(N)]`
+/// - `transform = "path"` / `transform(decode = "path", encode = "path")`:
+///   transforms the raw bytes of the field, see [Field transforms](#field-transforms)
+/// - `union`: same as `#[byte_struct_union]`
+/// - `validate = "path"`: same as `#[byte_struct_validate = "path"]`
+/// - `bit_planes`: same as `#[byte_struct_bit_planes]`
+/// - `map(...)`: same as `#[byte_struct_map(...)]`
+/// - `switch(...)`: same as `#[byte_struct_switch(...)]`
+/// - `internet_checksum`: makes the field hold the Internet checksum of the structure,
+///   see [Internet checksums](#internet-checksums)
+/// - `none = value`: same as `#[byte_struct_none = value]`
+/// - `present(...)`: same as `#[byte_struct_present(...)]`
+/// - `ctx`: packs the field with the context of the structure, see [Parse context](#parse-context)
+/// - `len = N`: the length of a `&'a [u8]` field, see [Borrowed fields](#borrowed-fields)
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct(endian = "be", assert_len = 8)]
+/// struct Header {
+///     kind: u8,
+///     #[byte_struct(pad_before = 1, endian = "le")]
+///     size: u16,
+///     #[byte_struct(validate = "is_valid_id")]
+///     id: u32,
+/// }
+/// ```
+///
+/// ## Absolute offsets
+///
+/// `#[byte_struct_offset(N)]` on a field places it at offset `N` from the start of the structure,
+/// for layouts specified by offset such as register blocks and firmware headers.
+/// The gap before the field is reserved: it is packed as zeros and ignored when unpacking,
+/// like `pad_before`. Fields without an offset follow the previous field as usual,
+/// and `BYTE_LEN` ends with the last field.
+///
+/// Fields must be declared in the order of their offsets. An offset that overlaps
+/// the fields before it fails the build. This can't be combined with `pad_before`.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Registers {
+///     id: u32,
+///     #[byte_struct_offset(0x10)]
+///     control: u32,
+///     status: u32,
+///     #[byte_struct_offset(0x40)]
+///     data: [u8; 16],
+/// }
+/// ```
+///
+/// ## Trailers
+///
+/// `#[byte_struct(trailer)]` on the struct lays out the fields backwards from its end:
+/// the first field declared is the last one in the bytes, and each following field
+/// is placed before the previous one. This matches structures that are specified from the end
+/// of a buffer, such as bootloader trailers, where new fields are added in front of the old ones
+/// and are declared after them. `ByteStructLayout::FIELDS` lists the fields in the order of the bytes.
+///
+/// Trailers are usually read with `read_bytes_from_end` and written with `write_bytes_to_end`,
+/// which place any structure at the end of a buffer.
+/// This is not supported together with `byte_struct_switch`, `byte_struct_present`,
+/// `byte_struct_union`, `pad_before` or `byte_struct_offset`, which refer to the declaration order.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct(endian = "le", trailer)]
+/// struct BootTrailer {
+///     magic: u32,    // The last 4 bytes
+///     version: u16,  // The 2 bytes before the magic
+///     // Added in version 2
+///     image_len: u32,
+/// }
+///
+/// let trailer = BootTrailer::read_bytes_from_end(&image);
+/// ```
+///
+/// ## Union views
+///
+/// `#[byte_struct_union]` on a field makes it another view of the bytes of the field before it,
+/// for formats that interpret the same bytes in different ways depending on flags.
+/// The primary field and the views marked after it form a group at the offset of the primary field.
+/// All views are unpacked along with the primary field, while only the primary field is packed,
+/// so the views are ignored when packing. A view can't be longer than the primary field,
+/// which fails the build, and doesn't add to `BYTE_LEN`.
+///
+/// As all views are unpacked, views that can reject values make the whole structure fail to unpack;
+/// this can be avoided with `Option` fields that depend on the flags.
+/// Views don't get setters, and can't be combined with `byte_struct_switch`, `internet_checksum`,
+/// `pad_before` or `byte_struct_offset`.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Value {
+///     flags: u8,
+///     raw: [u8; 8],
+///     #[byte_struct_union]
+///     as_int: i64,
+///     #[byte_struct_union]
+///     as_float: f64,
+/// }
+/// ```
+///
+/// ## Array lengths
+///
+/// Array fields can use any constant expression as their length,
+/// such as an associated constant or an enum discriminant, and it is reflected in `BYTE_LEN` as is.
+///
+/// ```ignore
+/// impl Header {
+///     const NAME_LEN: usize = 8;
+/// }
+///
+/// #[derive(ByteStruct)]
+/// struct Entry {
+///     name: [u8; Header::NAME_LEN],
+///     #[byte_struct_le]
+///     slots: [u16; Kind::Last as usize],
+/// }
+/// ```
+///
+/// ## Generic structures
+///
+/// Generic structures are supported, with the bounds required by the field types
+/// declared on the structure. Marker fields such as `PhantomData<T>` and `()` occupy no bytes.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Handle<T> {
+///     index: u32,
+///     kind: PhantomData<T>,
+/// }
+/// ```
+///
+/// ## Layout reflection
+///
+/// The derive also implements [`ByteStructLayout`], whose `FIELDS` constant lists the name,
+/// offset, packed length and byte order of each field.
+///
+/// [`ByteStructLayout`]: https://docs.rs/byte_struct/*/byte_struct/trait.ByteStructLayout.html
+///
+/// ## Inspecting byte orders
+///
+/// The derive also generates an associated function `field_byte_orders()`
+/// that returns the name of each field together with the [`ByteOrder`] it is actually packed with.
+/// This can be used to verify how byte order attributes are resolved on complex structures.
+///
+/// [`ByteOrder`]: https://docs.rs/byte_struct/*/byte_struct/enum.ByteOrder.html
+///
+/// ## Length assertion
+///
+/// `#[byte_struct_assert_len(N)]` on the struct fails the build unless `BYTE_LEN` equals `N`.
+/// This catches fields that are accidentally added, removed or resized
+/// in structures whose size is fixed by a specification.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// #[byte_struct_assert_len(6)]
+/// struct Header {
+///     magic: u32,
+///     version: u16,
+/// }
+/// ```
+///
+/// ## Single field setters
+///
+/// With `#[byte_struct_setters]` on the struct, the derive also generates an associated function
+/// `set_<field>(bytes, value)` for each field, which packs `value` into an existing buffer
+/// at the offset of the field without touching the bytes of other fields.
+/// Tagged union fields don't get a setter, as their tag lives in another field.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// #[byte_struct_setters]
+/// struct Rect {
+///     width: u16,
+///     height: u16,
+/// }
+///
+/// let mut bytes = [0; 4];
+/// Rect::set_height(&mut bytes, 5);
+/// assert_eq!(bytes, [0, 0, 5, 0]);
+/// ```
+///
+/// ## Single field getters
+///
+/// With `#[byte_struct(getters)]` on the struct, the derive also generates associated functions
+/// `read_field_<field>(bytes)` and `try_read_field_<field>(bytes)` for each field,
+/// which unpack the field alone from its offset in `bytes` without unpacking the rest of the struct.
+/// Together with the offsets in `ByteStructLayout::FIELDS`, this allows filtering many records
+/// on a few fields before unpacking the ones that are kept.
+/// `read_field_<field>` panics if `bytes` is too short or the field is malformed,
+/// while `try_read_field_<field>` reports them as an error.
+///
+/// Fields whose unpacking depends on another field, with `byte_struct_switch` or `byte_struct_present`,
+/// don't get getters. This is not supported together with `sanitize` or `context`,
+/// or on write-only structs.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct(endian = "le", getters)]
+/// struct Rect {
+///     width: u16,
+///     height: u16,
+/// }
+///
+/// assert_eq!(Rect::read_field_height(&[0, 0, 5, 0]), 5);
+/// ```
+///
+/// ## Buffer sanitizer
+///
+/// `#[byte_struct(sanitize = "path::fix")]` on the struct calls `fix` as `fn(&mut [u8])`
+/// over the raw bytes of the whole struct, on a copy of the input before unpacking
+/// and on the output after packing. This handles buffer-level quirks such as byte-swapped
+/// 16-bit word streams in one place, while fields are declared as they are specified.
+/// The function must undo itself; for transforms that don't, such as scrambling, give both directions
+/// with `sanitize(decode = "path::descramble", encode = "path::scramble")`.
+///
+/// Offsets reported by the layout and by errors refer to the sanitized bytes.
+/// The option is not supported on generic structs or together with setters.
+///
+/// ```ignore
+/// fn swap_words(bytes: &mut [u8]) {
+///     for word in bytes.chunks_exact_mut(2) {
+///         word.swap(0, 1);
+///     }
+/// }
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct(endian = "le", sanitize = "swap_words")]
+/// struct Sample {
+///     channel: u8,
+///     gain: u8,
+///     value: u16,
+/// }
+/// ```
+///
+/// ## Field transforms
+///
+/// `#[byte_struct(transform = "path::fix")]` on a field calls `fix` as `fn(&mut [u8])`
+/// over the raw bytes of the field only, on a copy of them before unpacking the field
+/// and on them after packing it. This undoes light obfuscation of parts of a format,
+/// such as XOR with a key, within the definition of the structure.
+/// As with `sanitize`, give both directions with
+/// `transform(decode = "path::decrypt", encode = "path::encrypt")` for transforms that don't undo themselves,
+/// such as rolling ciphers.
+///
+/// Offsets reported by errors refer to the transformed bytes, and `validate` checks the unpacked value.
+/// The option is not supported on generic structs or on fields borrowing from the bytes.
+///
+/// ```ignore
+/// fn xor_key(bytes: &mut [u8]) {
+///     for (byte, key) in bytes.iter_mut().zip(b"KEY".iter().cycle()) {
+///         *byte ^= key;
+///     }
+/// }
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct SaveFile {
+///     version: u16,
+///     #[byte_struct(transform = "xor_key")]
+///     gold: u32,
+/// }
+/// ```
+///
+/// ## Standalone structures
+///
+/// With `#[byte_struct(no_unspecified)]` on the struct, `write_bytes`, `read_bytes` and `try_read_bytes`
+/// are generated as inherent functions instead of implementations of `ByteStructWrite` and `ByteStructRead`.
+/// The struct then doesn't implement `ByteStruct` nor `ByteStructUnspecifiedByteOrder`,
+/// so the compiler rejects embedding it as a field of another structure, where a byte order
+/// given to the parent could be mistaken as applying to it. `ByteStructLen` and `ByteStructLayout`
+/// are still implemented.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct(endian = "be", no_unspecified)]
+/// struct Packet {
+///     size: u16,
+///     crc: u32,
+/// }
+///
+/// let packet = Packet::read_bytes(&bytes[..]);
+/// ```
+///
+/// ## Read-only and write-only structures
+///
+/// With `#[byte_struct(read_only)]` on the struct, only `ByteStructRead` is implemented,
+/// and with `#[byte_struct(write_only)]`, only `ByteStructWrite` is implemented.
+/// The fields then only need to implement the corresponding trait,
+/// so types that can only be sensibly unpacked, such as a reply computed by a device,
+/// or only packed, such as a command with a derived checksum, can be nested.
+/// Such fields must not have a byte order attribute, either on the field or on the struct,
+/// as the byte order is passed through `ByteStructUnspecifiedByteOrder`, which requires both.
+/// Setters can't be combined with `read_only`.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct(write_only)]
+/// struct Command {
+///     #[byte_struct_le]
+///     opcode: u16,
+///     payload: Checksummed<Payload>,
+/// }
+/// ```
+///
+/// ## Device registers
+///
+/// `#[byte_struct_register(addr = N)]` on the struct implements `ByteStructRegister`,
+/// which maps the struct to consecutive registers of a device starting at register `N`.
+/// The attribute also accepts `endian = "le"` / `endian = "be"` as the byte order of the struct.
+/// With the `embedded-hal` feature of `byte_struct`, such structures can be read and written
+/// with `read_register` and `write_register` over I²C, or their SPI counterparts.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct_register(addr = 0x3B, endian = "be")]
+/// struct Accel {
+///     x: i16,
+///     y: i16,
+///     z: i16,
+/// }
+///
+/// let accel: Accel = read_register(&mut i2c, 0x68)?;
+/// ```
+///
+/// ## Columnar unpacking
+///
+/// `#[byte_struct(columns)]` on a struct named `Foo` generates a struct `FooColumns`
+/// with the same visibility, which has a `Vec` of each field with the visibility of the field,
+/// and the functions `Foo::decode_columns(bytes, count)` and `Foo::try_decode_columns(bytes, count)`.
+/// They unpack `count` consecutive records from `bytes` one column at a time,
+/// without building the records themselves. This requires the `alloc` feature of `byte_struct`,
+/// and is not supported on generic structs, with `sanitize` or with tagged union fields.
+/// `try_decode_columns` reports errors located at the record as an array element.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct(endian = "le", columns)]
+/// struct Trade {
+///     price: u64,
+///     quantity: u32,
+/// }
+///
+/// let columns: TradeColumns = Trade::decode_columns(&bytes, 1000);
+/// let volume: u64 = columns.quantity.iter().map(|q| *q as u64).sum();
+/// ```
+///
+/// ## Fallible unpacking
+///
+/// Besides `read_bytes`, the derive implements `ByteStructRead::try_read_bytes`, which returns a
+/// `ByteStructError` naming the innermost field that failed and its offset instead of panicking.
+/// This covers slices that are too short, rejected validations and unknown tags of tagged union fields.
+/// The error also carries the path of field names and array indices from the outermost structure,
+/// such as `s.s1.c[1].y`, which is included when the error is displayed.
+///
+/// ## Converted fields
+///
+/// A field whose type differs from its packed representation can be converted with
+/// `#[byte_struct_map(wire = "WireType", into = "path::decode", from = "path::encode")]`.
+/// The field occupies `WireType::BYTE_LEN` bytes and is packed as `WireType`, following the byte order
+/// attributes as usual. `decode` is called as `fn(WireType) -> FieldType` after unpacking,
+/// and `encode` is called as `fn(&FieldType) -> WireType` before packing.
+///
+/// ```ignore
+/// fn millis_to_duration(millis: u32) -> Duration {
+///     Duration::from_millis(millis.into())
+/// }
+///
+/// fn duration_to_millis(duration: &Duration) -> u32 {
+///     duration.as_millis() as u32
+/// }
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_be]
+/// struct Timeout {
+///     #[byte_struct_map(wire = "u32", into = "millis_to_duration", from = "duration_to_millis")]
+///     timeout: Duration,
+/// }
+/// ```
+///
+/// ## Field validation
+///
+/// `#[byte_struct_validate = "path::check"]` calls `check` as `fn(&FieldType) -> bool`
+/// with each unpacked value of the field. `read_bytes` panics if `check` returns `false`,
+/// in the same way as unpacking from a slice that is too short,
+/// while `try_read_bytes` returns an error.
+///
+/// ```ignore
+/// fn is_valid_version(version: &u8) -> bool {
+///     *version <= 3
+/// }
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Header {
+///     #[byte_struct_validate = "is_valid_version"]
+///     version: u8,
+/// }
+/// ```
+///
+/// ## Internet checksums
+///
+/// `#[byte_struct(internet_checksum)]` on a field of 2 bytes makes it hold the ones' complement
+/// checksum of RFC 1071 over the bytes of the whole structure, as in the IPv4 and ICMP headers.
+/// `write_bytes` ignores the value of the field and stores the checksum computed
+/// with the field as zero. When unpacking, the stored checksum is kept as the value of the field.
+/// `read_bytes` doesn't check it, so that a corrupted packet doesn't abort the caller,
+/// while `try_read_bytes` reports a mismatch as an error of kind `ValidationFailed`.
+/// To cover only part of a packet, put the covered fields in a nested structure.
+/// Checksums that also cover data outside of the structure, such as the pseudo-header
+/// of UDP and TCP, can be computed with `byte_struct::InternetChecksum`.
+/// This is not supported together with `sanitize`, `columns` or setters,
+/// as setters would change the bytes without sealing the checksum again.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct_be]
+/// struct EchoRequest {
+///     kind: u8,
+///     code: u8,
+///     #[byte_struct(internet_checksum)]
+///     checksum: u16,
+///     identifier: u16,
+///     sequence: u16,
+/// }
+/// ```
+///
+/// ## Bit-plane fields
+///
+/// An array of structures generated by `bitfields!` is normally packed element by element.
+/// With `#[byte_struct_bit_planes]`, it is packed as bit planes instead:
+/// the first plane holds bit 0 of every element, the next plane holds bit 1, and so on,
+/// so that the bits of each member are stored together.
+/// Within a plane, each element takes one bit, starting from the least significant bit of the first byte,
+/// and the plane is padded to whole bytes. Only the bits covered by the members are stored.
+/// Byte order attributes have no effect on the field.
+///
+/// ```ignore
+/// bitfields!(
+///     Pixel: u8 => partial {
+///         pub color: 2,
+///         pub blink: 1,
+///     }
+/// );
+///
+/// #[derive(ByteStruct)]
+/// struct Row {
+///     // Occupies 3 planes of 2 bytes
+///     #[byte_struct_bit_planes]
+///     pixels: [Pixel; 16],
+/// }
+/// ```
+///
+/// ## Tagged union fields
+///
+/// A field whose type is an enum with single-field tuple variants can be selected by
+/// the value of a preceding field with `#[byte_struct_switch(on = "field", tag => Variant, ...)]`.
+/// The field occupies as many bytes as the largest variant payload. Shorter payloads are padded
+/// with zeros when packing. Unpacking a tag that is not listed panics with `read_bytes`,
+/// or returns an error with `try_read_bytes`.
+///
+/// The byte order attributes on the field (or on the struct) apply to the variant payloads.
+///
+/// When packing, the tag field is packed from the active variant instead of its stored value,
+/// so that the two can't disagree. If several tags select the variant, the stored tag is packed
+/// if it is one of them, or the first of them otherwise.
+///
+/// ```ignore
+/// enum Body {
+///     Short(u16),
+///     Long(u64),
+/// }
+///
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Message {
+///     kind: u8,
+///     // Always occupies 8 bytes
+///     #[byte_struct_switch(on = "kind", 0 => Short, 1 => Long)]
+///     body: Body,
+/// }
+/// ```
+///
+/// ## Optional fields
+///
+/// A field of type `Option<T>` is packed as `T`, with one of two ways to tell whether it holds a value:
+/// - `#[byte_struct_none = value]` reserves a sentinel value of `T` for `None`.
+///   `None` is packed as the sentinel, and unpacking the sentinel gives `None`,
+///   so `Some` of the sentinel itself is unpacked as `None` as well.
+/// - `#[byte_struct_present(on = "field")]` takes the presence from a preceding field,
+///   which holds a value if it isn't equal to its `Default` value, such as a non-zero flag.
+///   `when = "expr"` replaces this test with `expr`, called as `fn(&FieldType) -> bool`
+///   on the preceding field, such as a closure that tests a bit of it.
+///   The bytes of an absent value are ignored when unpacking, and packed as zeros.
+///   The preceding field isn't updated when packing, so it needs to be kept consistent
+///   with the optional field. This is not supported together with `columns`.
+///
+/// The field always occupies `T::BYTE_LEN` bytes, and the byte order attributes apply to `T`.
+///
+/// Fields that consume their bytes only when present are not supported, as every structure
+/// has a constant `BYTE_LEN`. In particular, fields appended in later versions of a format
+/// can't be skipped in the shorter, older versions. A structure that makes them optional
+/// on a version field describes all versions at the size of the latest one, and can only unpack
+/// an older version after its bytes are copied into a zero-filled buffer of `BYTE_LEN`.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Header {
+///     #[byte_struct_none = 0xFFFF_FFFF]
+///     parent: Option<u32>,
+///     flags: u8,
+///     #[byte_struct_present(on = "flags", when = "|flags: &u8| flags & 0x80 != 0")]
+///     extension: Option<u16>,
+/// }
+/// ```
+///
+/// ## Parse context
+///
+/// `#[byte_struct(context = "Type")]` makes the structure take a context of type `Type`
+/// from the caller, such as a byte order or a version read from a file header.
+/// The structure then implements `ByteStructReadCtx<Type>` and `ByteStructWriteCtx<Type>`
+/// (as allowed by `read_only` and `write_only`) in place of `ByteStructRead` and `ByteStructWrite`.
+///
+/// Fields marked with `#[byte_struct(ctx)]` are packed with the context through the same traits,
+/// which can be implemented by hand or derived by a nested structure with the same context.
+/// Other fields are packed as usual, and the context is ignored for them.
+/// This is not supported together with `setters`, `columns`, `arbitrary` or `no_unspecified`.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct(context = "Endian", endian = "le")]
+/// struct Record {
+///     kind: u8,
+///     // Implements ByteStructReadCtx<Endian> and ByteStructWriteCtx<Endian>
+///     #[byte_struct(ctx)]
+///     value: Native,
+/// }
+///
+/// let record = Record::read_bytes_with_ctx(&bytes, &Endian { big: true });
+/// ```
+///
+/// ## Borrowed fields
+///
+/// A structure with a lifetime parameter `'a` borrows from the bytes it is unpacked from,
+/// and implements `ByteStructReadRef<'a>` in place of `ByteStructRead`.
+/// Fields whose type has the lifetime refer to their bytes in place instead of copying them:
+/// - `&'a [u8; N]` borrows `N` bytes.
+/// - `&'a [u8]` borrows the number of bytes given by `#[byte_struct(len = N)]`.
+///   Packing a slice of another length panics.
+/// - Other types are unpacked with `ByteStructReadRef<'a>`, such as nested structures
+///   that borrow as well.
+///
+/// Other fields are packed as usual. Only one lifetime parameter is supported,
+/// and this is not supported together with `setters`, `arbitrary`, `context` or `no_unspecified`.
+///
+/// ```ignore
+/// #[derive(ByteStruct)]
+/// #[byte_struct_le]
+/// struct Entry<'a> {
+///     id: u32,
+///     hash: &'a [u8; 32],
+///     #[byte_struct(len = 4096)]
+///     blob: &'a [u8],
+/// }
+///
+/// let entry = Entry::read_bytes_ref(&bytes[..]);
+/// ```
+#[proc_macro_derive(ByteStruct, attributes(
+    byte_struct_le,
+    byte_struct_be,
+    byte_struct_switch,
+    byte_struct_map,
+    byte_struct_none,
+    byte_struct_present,
+    byte_struct_validate,
+    byte_struct_setters,
+    byte_struct_assert_len,
+    byte_struct_offset,
+    byte_struct_union,
+    byte_struct_bit_planes,
+    byte_struct_register,
+    byte_struct,
+))]
 pub fn byte_struct_macro_derive(input: TokenStream) -> TokenStream {
     byte_struct_macro_derive_impl(input, Endianness::Unspecified)
 }
@@ -114,66 +745,474 @@ pub fn byte_struct_be_macro_derive(input: TokenStream) -> TokenStream {
     byte_struct_macro_derive_impl(input, Endianness::Big)
 }
 
-fn byte_struct_macro_derive_impl(input: TokenStream, endianness_input: Endianness) -> TokenStream {
-    let ast: syn::DeriveInput = syn::parse(input).unwrap();
+/// Derives trait [`ByteStructVisit`] for a data structure that also derives [`ByteStruct`].
+///
+/// The generated `visit_fields` passes each field to the visitor in declaration order,
+/// together with its entry in `ByteStructLayout::FIELDS`.
+/// All field types must be `'static` so that they can be passed as `&dyn Any`.
+///
+/// [`ByteStruct`]: https://docs.rs/byte_struct/*/byte_struct/trait.ByteStruct.html
+/// [`ByteStructVisit`]: https://docs.rs/byte_struct/*/byte_struct/trait.ByteStructVisit.html
+#[proc_macro_derive(ByteStructVisit, attributes(byte_struct))]
+pub fn byte_struct_visit_macro_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    byte_struct_visit_expand(ast).unwrap_or_else(syn::Error::into_compile_error).into()
+}
 
-    let mut found_le = false;
-    let mut found_be = false;
-    for attr in ast.attrs {
-        let syn::Attribute{meta: syn::Meta::Path(syn::Path{segments, ..}), ..} = attr else {continue};
-        if segments.len() != 1 {
-            continue;
-        }
-        match segments[0].ident.to_string().as_str() {
-            "byte_struct_le" => found_le = true,
-            "byte_struct_be" => found_be = true,
-            _ => ()
+fn byte_struct_visit_expand(ast: syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let krate = parse_struct_options(&ast.attrs)?.krate;
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    if let syn::Data::Struct(syn::DataStruct{fields: syn::Fields::Named(
+        syn::FieldsNamed{named, ..}), ..}) = ast.data {
+
+        let ident = named.iter().map(|n| n.ident.clone().unwrap());
+        let index = 0 .. named.len();
+        let gen = quote! {
+            impl #impl_generics #krate::ByteStructVisit for #name #ty_generics #where_clause {
+                fn visit_fields<V: #krate::FieldVisitor + ?Sized>(&self, visitor: &mut V) {
+                    #(
+                        visitor.visit_field(
+                            &<Self as #krate::ByteStructLayout>::FIELDS[#index],
+                            &self.#ident,
+                        );
+                    )*
+                }
+            }
         };
-    }
-    if found_be && found_le {
-        panic!("Found conflicting byte_struct_le and byte_struct_be attributes");
-    }
-    let endianness = if found_le {
-        Endianness::Little
-    } else if found_be {
-        Endianness::Big
+        Ok(gen)
     } else {
-        endianness_input
-    };
+        Err(syn::Error::new_spanned(name, "Only support struct with named fields!"))
+    }
+}
+
+/// Derives trait [`ByteStructDebugJson`] for a data structure.
+///
+/// The generated `write_debug_json` writes the structure as a JSON object
+/// with each field in declaration order. All field types must implement [`ByteStructDebugJson`].
+///
+/// [`ByteStructDebugJson`]: https://docs.rs/byte_struct/*/byte_struct/trait.ByteStructDebugJson.html
+#[proc_macro_derive(ByteStructDebugJson, attributes(byte_struct))]
+pub fn byte_struct_debug_json_macro_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    byte_struct_debug_json_expand(ast).unwrap_or_else(syn::Error::into_compile_error).into()
+}
 
+fn byte_struct_debug_json_expand(ast: syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let krate = parse_struct_options(&ast.attrs)?.krate;
     let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
     if let syn::Data::Struct(syn::DataStruct{fields: syn::Fields::Named(
         syn::FieldsNamed{named, ..}), ..}) = ast.data {
 
-        let mut ty0 = Vec::<syn::Type>::new();
-        let mut ident1 = Vec::<syn::Ident>::new();
+        let ident: Vec<_> = named.iter().map(|n| n.ident.clone().unwrap()).collect();
+        let key = ident.iter().enumerate().map(|(i, ident)| {
+            format!("{}\"{}\":", if i == 0 { '{' } else { ',' }, ident)
+        });
+        let close = if ident.is_empty() { "{}" } else { "}" };
+        let gen = quote! {
+            impl #impl_generics #krate::ByteStructDebugJson for #name #ty_generics #where_clause {
+                fn write_debug_json<W: ::core::fmt::Write + ?Sized>(&self, out: &mut W)
+                    -> ::core::fmt::Result {
+                    #(
+                        out.write_str(#key)?;
+                        #krate::ByteStructDebugJson::write_debug_json(&self.#ident, out)?;
+                    )*
+                    out.write_str(#close)
+                }
+            }
+        };
+        Ok(gen)
+    } else {
+        Err(syn::Error::new_spanned(name, "Only support struct with named fields!"))
+    }
+}
+
+fn byte_struct_macro_derive_impl(input: TokenStream, endianness_input: Endianness) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    byte_struct_expand(ast, endianness_input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn byte_struct_expand(ast: syn::DeriveInput, endianness_input: Endianness)
+    -> syn::Result<proc_macro2::TokenStream> {
+
+    let StructOptions{endianness, setters: found_setters, getters, trailer, assert_len, sanitize, no_unspecified, access, tag, register,
+        columns, arbitrary, context, krate} = parse_struct_options(&ast.attrs)?;
+    let endianness = endianness.unwrap_or(endianness_input);
+    if !ast.generics.params.is_empty() {
+        if let Some(expected) = &assert_len {
+            return Err(syn::Error::new_spanned(expected,
+                "byte_struct_assert_len is not supported on generic structs"));
+        }
+        if let Some(Sanitize{decode, ..}) = &sanitize {
+            return Err(syn::Error::new_spanned(decode,
+                "sanitize is not supported on generic structs"));
+        }
+        if columns {
+            return Err(syn::Error::new_spanned(&ast.ident,
+                "columns is not supported on generic structs"));
+        }
+    }
+    if columns && access == Access::WriteOnly {
+        return Err(syn::Error::new_spanned(&ast.ident,
+            "columns is not supported on write-only structs"));
+    }
+    if arbitrary && access == Access::WriteOnly {
+        return Err(syn::Error::new_spanned(&ast.ident,
+            "arbitrary is not supported on write-only structs"));
+    }
+    if let (true, Some(Sanitize{decode, ..})) = (columns, &sanitize) {
+        // Records are unpacked a field at a time, so the whole record is never sanitized
+        return Err(syn::Error::new_spanned(decode,
+            "sanitize is not supported together with columns"));
+    }
+    if found_setters && access == Access::ReadOnly {
+        return Err(syn::Error::new_spanned(&ast.ident,
+            "setters are not supported on read-only structs"));
+    }
+    if getters && access == Access::WriteOnly {
+        return Err(syn::Error::new_spanned(&ast.ident,
+            "getters are not supported on write-only structs"));
+    }
+    if let (true, Some(Sanitize{decode, ..})) = (getters, &sanitize) {
+        // Getters read from the buffer in place, which would skip the sanitizer
+        return Err(syn::Error::new_spanned(decode,
+            "sanitize is not supported together with getters"));
+    }
+    if let Some(context) = &context {
+        // These are generated on top of the functions without a context
+        if found_setters || getters || columns || arbitrary || no_unspecified {
+            return Err(syn::Error::new_spanned(context,
+                "context is not supported together with setters, getters, columns, arbitrary or no_unspecified"));
+        }
+    }
+    // Fields with the lifetime of the struct borrow from the bytes being unpacked
+    let mut lifetimes = ast.generics.lifetimes();
+    let borrow_lt = lifetimes.next().map(|param| param.lifetime.clone());
+    if let Some(extra) = lifetimes.next() {
+        return Err(syn::Error::new_spanned(extra, "only one lifetime parameter is supported"));
+    }
+    if let Some(lt) = &borrow_lt {
+        if found_setters || arbitrary || context.is_some() || no_unspecified {
+            return Err(syn::Error::new_spanned(lt,
+                "structs with a lifetime don't support setters, arbitrary, context or no_unspecified"));
+        }
+    }
+    if let (true, Some(Sanitize{decode, ..})) = (found_setters, &sanitize) {
+        // Setters write into the buffer in place, which would skip the sanitizer
+        return Err(syn::Error::new_spanned(decode,
+            "sanitize is not supported together with setters"));
+    }
+
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    if let syn::Data::Struct(syn::DataStruct{fields: syn::Fields::Named(
+        syn::FieldsNamed{named, ..}), ..}) = ast.data {
+
+        let mut ty0 = Vec::<syn::Type>::new();
+        let mut ident1 = Vec::<syn::Ident>::new();
+        let mut field_vis = Vec::<syn::Visibility>::new();
         let mut field_endianness = Vec::<Endianness>::new();
+        let mut field_switch = Vec::<Option<Switch>>::new();
+        let mut field_map = Vec::<Option<Map>>::new();
+        let mut field_validate = Vec::<Option<syn::Path>>::new();
+        let mut field_bit_planes = Vec::<bool>::new();
+        let mut field_pad = Vec::<Option<syn::Expr>>::new();
+        let mut field_at = Vec::<Option<syn::Expr>>::new();
+        let mut field_union = Vec::<bool>::new();
+        let mut field_presence = Vec::<Option<Presence>>::new();
+        let mut field_ctx = Vec::<bool>::new();
+        let mut field_borrow = Vec::<Option<Borrow>>::new();
+        let mut field_transform = Vec::<Option<Sanitize>>::new();
+        let mut checksum_field = None;
         for n in named {
-            ty0.push(n.ty.clone());
-            ident1.push(n.ident.unwrap().clone());
+            let field_ident = n.ident.clone().unwrap();
             let mut found_le = false;
             let mut found_be = false;
+            let mut switch = None;
+            let mut map = None;
+            let mut validate = None;
+            let mut bit_planes = false;
+            let mut pad_before = None;
+            let mut at = None;
+            let mut union = false;
+            let mut transform = None;
+            let mut internet_checksum = false;
+            let mut presence = None;
+            let mut ctx = false;
+            let mut borrow_len = None;
             for attr in n.attrs {
-                let syn::Attribute{meta: syn::Meta::Path(syn::Path{segments, ..}), ..} = attr else {continue};
+                if attr.path().is_ident("byte_struct") {
+                    attr.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("endian") {
+                            match parse_endian(&meta)? {
+                                Endianness::Little => found_le = true,
+                                _ => found_be = true,
+                            }
+                        } else if meta.path.is_ident("pad_before") {
+                            pad_before = Some(meta.value()?.parse::<syn::Expr>()?);
+                        } else if meta.path.is_ident("offset") {
+                            at = Some(meta.value()?.parse::<syn::Expr>()?);
+                        } else if meta.path.is_ident("union") {
+                            union = true;
+                        } else if meta.path.is_ident("transform") {
+                            transform = Some(Sanitize::parse_meta(&meta)?);
+                        } else if meta.path.is_ident("validate") {
+                            validate = Some(meta.value()?.parse::<syn::LitStr>()?.parse::<syn::Path>()?);
+                        } else if meta.path.is_ident("bit_planes") {
+                            bit_planes = true;
+                        } else if meta.path.is_ident("map") {
+                            let content;
+                            syn::parenthesized!(content in meta.input);
+                            map = Some(content.parse::<Map>()?);
+                        } else if meta.path.is_ident("switch") {
+                            let content;
+                            syn::parenthesized!(content in meta.input);
+                            switch = Some(content.parse::<Switch>()?);
+                        } else if meta.path.is_ident("internet_checksum") {
+                            internet_checksum = true;
+                        } else if meta.path.is_ident("none") {
+                            presence = Some(Presence::Sentinel(meta.value()?.parse::<syn::Expr>()?));
+                        } else if meta.path.is_ident("present") {
+                            let content;
+                            syn::parenthesized!(content in meta.input);
+                            presence = Some(Presence::Flag(content.parse::<Present>()?));
+                        } else if meta.path.is_ident("ctx") {
+                            ctx = true;
+                        } else if meta.path.is_ident("len") {
+                            borrow_len = Some(meta.value()?.parse::<syn::Expr>()?);
+                        } else {
+                            return Err(meta.error(
+                                "expected `endian`, `pad_before`, `offset`, `union`, `transform`, `validate`, `bit_planes`, `map`, `switch`, \
+                                `internet_checksum`, `none`, `present`, `ctx` or `len`"));
+                        }
+                        Ok(())
+                    })?;
+                    if found_be && found_le {
+                        return Err(syn::Error::new_spanned(attr,
+                            "Found conflicting byte_struct_le and byte_struct_be attributes"));
+                    }
+                    continue;
+                }
+                if attr.path().is_ident("byte_struct_validate") {
+                    let syn::Meta::NameValue(syn::MetaNameValue{value: syn::Expr::Lit(
+                        syn::ExprLit{lit: syn::Lit::Str(path), ..}), ..}) = &attr.meta else {
+                        return Err(syn::Error::new_spanned(attr,
+                            "Expected #[byte_struct_validate = \"path\"]"));
+                    };
+                    validate = Some(path.parse::<syn::Path>()?);
+                    continue;
+                }
+                if attr.path().is_ident("byte_struct_switch") {
+                    switch = Some(attr.parse_args::<Switch>()?);
+                    continue;
+                }
+                if attr.path().is_ident("byte_struct_offset") {
+                    at = Some(attr.parse_args::<syn::Expr>()?);
+                    continue;
+                }
+                if attr.path().is_ident("byte_struct_map") {
+                    map = Some(attr.parse_args::<Map>()?);
+                    continue;
+                }
+                if attr.path().is_ident("byte_struct_none") {
+                    let syn::Meta::NameValue(syn::MetaNameValue{value, ..}) = &attr.meta else {
+                        return Err(syn::Error::new_spanned(attr,
+                            "Expected #[byte_struct_none = value]"));
+                    };
+                    presence = Some(Presence::Sentinel(value.clone()));
+                    continue;
+                }
+                if attr.path().is_ident("byte_struct_present") {
+                    presence = Some(Presence::Flag(attr.parse_args::<Present>()?));
+                    continue;
+                }
+                let syn::Attribute{meta: syn::Meta::Path(syn::Path{segments, ..}), ..} = &attr else {continue};
                 if segments.len() != 1 {
                     continue;
                 }
                 match segments[0].ident.to_string().as_str() {
                     "byte_struct_le" => found_le = true,
                     "byte_struct_be" => found_be = true,
+                    "byte_struct_bit_planes" => bit_planes = true,
+                    "byte_struct_union" => union = true,
                     _ => ()
                 };
+                if found_be && found_le {
+                    return Err(syn::Error::new_spanned(attr,
+                        "Found conflicting byte_struct_le and byte_struct_be attributes"));
+                }
             }
-            if found_be && found_le {
-                panic!("Found conflicting byte_struct_le and byte_struct_be attributes");
-            }
-            if found_be {
+            let borrowed = borrow_lt.as_ref().is_some_and(|lt| mentions_lifetime(n.ty.to_token_stream(), lt));
+            if bit_planes || ctx || borrowed {
+                // Bit planes are packed bit by bit, and fields with a context or borrowing from the bytes
+                // by their own rules, so no byte order applies
+                field_endianness.push(Endianness::Unspecified);
+            } else if found_be {
                 field_endianness.push(Endianness::Big);
             } else if found_le {
                 field_endianness.push(Endianness::Little);
             } else {
                 field_endianness.push(endianness);
             }
+            if let Some(Switch{on, ..}) = &switch {
+                if !ident1.contains(on) {
+                    return Err(syn::Error::new(on.span(),
+                        "byte_struct_switch must be on a field declared before this field"));
+                }
+            }
+            if switch.is_some() && map.is_some() {
+                return Err(syn::Error::new_spanned(&field_ident,
+                    "byte_struct_switch and byte_struct_map can't be used on the same field"));
+            }
+            if columns && switch.is_some() {
+                // The tag is in another column while the field is unpacked
+                return Err(syn::Error::new_spanned(&field_ident,
+                    "byte_struct_switch is not supported together with columns"));
+            }
+            if bit_planes && (switch.is_some() || map.is_some()) {
+                return Err(syn::Error::new_spanned(&field_ident,
+                    "byte_struct_bit_planes can't be used with byte_struct_switch or byte_struct_map"));
+            }
+            if internet_checksum {
+                if checksum_field.is_some() {
+                    return Err(syn::Error::new_spanned(&field_ident,
+                        "only one field can hold an internet_checksum"));
+                }
+                if switch.is_some() || bit_planes {
+                    return Err(syn::Error::new_spanned(&field_ident,
+                        "internet_checksum can't be used with byte_struct_switch or byte_struct_bit_planes"));
+                }
+                if let Some(Sanitize{decode, ..}) = &sanitize {
+                    // The checksum would be computed over the bytes before they are sanitized
+                    return Err(syn::Error::new_spanned(decode,
+                        "sanitize is not supported together with internet_checksum"));
+                }
+                if columns {
+                    // Columns are unpacked field by field, without the bytes the checksum covers
+                    return Err(syn::Error::new_spanned(&field_ident,
+                        "internet_checksum is not supported together with columns"));
+                }
+                if found_setters {
+                    // A setter would change the bytes without sealing the checksum again
+                    return Err(syn::Error::new_spanned(&field_ident,
+                        "internet_checksum is not supported together with setters"));
+                }
+                checksum_field = Some(ident1.len());
+            }
+            if presence.is_some() {
+                if switch.is_some() || map.is_some() || bit_planes || internet_checksum {
+                    return Err(syn::Error::new_spanned(&field_ident,
+                        "byte_struct_none and byte_struct_present can't be used with byte_struct_switch, \
+                        byte_struct_map, byte_struct_bit_planes or internet_checksum"));
+                }
+                if option_inner(&n.ty).is_none() {
+                    return Err(syn::Error::new_spanned(&n.ty,
+                        "byte_struct_none and byte_struct_present require the field type to be an Option"));
+                }
+            }
+            if ctx {
+                if context.is_none() {
+                    return Err(syn::Error::new_spanned(&field_ident,
+                        "ctx requires a context on the struct, given by #[byte_struct(context = \"Type\")]"));
+                }
+                if switch.is_some() || map.is_some() || bit_planes || internet_checksum || presence.is_some() {
+                    return Err(syn::Error::new_spanned(&field_ident,
+                        "ctx can't be used with byte_struct_switch, byte_struct_map, byte_struct_bit_planes, \
+                        internet_checksum, byte_struct_none or byte_struct_present"));
+                }
+            }
+            if borrowed && (switch.is_some() || map.is_some() || bit_planes || internet_checksum
+                || presence.is_some()) {
+                return Err(syn::Error::new_spanned(&field_ident,
+                    "a field borrowing from the bytes can't be used with byte_struct_switch, byte_struct_map, \
+                    byte_struct_bit_planes, internet_checksum, byte_struct_none or byte_struct_present"));
+            }
+            let borrow = match (borrowed, is_byte_slice(&n.ty), borrow_len) {
+                (true, true, Some(count)) => Some(Borrow::Slice(count)),
+                (true, true, None) => return Err(syn::Error::new_spanned(&n.ty,
+                    "a borrowed slice needs its length given by #[byte_struct(len = N)]")),
+                (true, false, None) => Some(Borrow::Value),
+                (false, _, None) => None,
+                (_, _, Some(count)) => return Err(syn::Error::new_spanned(count,
+                    "len is only supported on fields of type &'a [u8], where 'a is the lifetime of the struct")),
+            };
+            if let Some(Presence::Flag(Present{on, ..})) = &presence {
+                if !ident1.contains(on) {
+                    return Err(syn::Error::new(on.span(),
+                        "byte_struct_present must be on a field declared before this field"));
+                }
+                if columns {
+                    // The flag is in another column while the field is unpacked
+                    return Err(syn::Error::new_spanned(&field_ident,
+                        "byte_struct_present is not supported together with columns"));
+                }
+            }
+            if let (Some(_), Some(at)) = (&pad_before, &at) {
+                return Err(syn::Error::new_spanned(at,
+                    "byte_struct_offset and pad_before can't be used on the same field"));
+            }
+            if union {
+                if ident1.is_empty() {
+                    return Err(syn::Error::new_spanned(&field_ident,
+                        "byte_struct_union must be on a field declared after the field it overlaps"));
+                }
+                if switch.is_some() || internet_checksum || pad_before.is_some() || at.is_some() {
+                    return Err(syn::Error::new_spanned(&field_ident,
+                        "byte_struct_union can't be used with byte_struct_switch, internet_checksum, \
+                        pad_before or byte_struct_offset"));
+                }
+            }
+            if trailer && (switch.is_some() || matches!(presence, Some(Presence::Flag(_))) || union
+                || pad_before.is_some() || at.is_some()) {
+                return Err(syn::Error::new_spanned(&field_ident,
+                    "byte_struct_switch, byte_struct_present, byte_struct_union, pad_before and \
+                    byte_struct_offset are not supported together with trailer"));
+            }
+            if let Some(Sanitize{decode, ..}) = &transform {
+                // The transformed bytes are copied to a buffer of the length of the field
+                if !ast.generics.params.is_empty() {
+                    return Err(syn::Error::new_spanned(decode,
+                        "transform is not supported on generic structs"));
+                }
+                if borrow.is_some() {
+                    return Err(syn::Error::new_spanned(decode,
+                        "transform is not supported on fields borrowing from the bytes"));
+                }
+            }
+            ty0.push(n.ty);
+            ident1.push(field_ident);
+            field_vis.push(n.vis);
+            field_switch.push(switch);
+            field_map.push(map);
+            field_validate.push(validate);
+            field_bit_planes.push(bit_planes);
+            field_pad.push(pad_before);
+            field_at.push(at);
+            field_union.push(union);
+            field_presence.push(presence);
+            field_ctx.push(ctx);
+            field_borrow.push(borrow);
+            field_transform.push(transform);
+        }
+        if trailer {
+            // The fields are laid out in the reverse order of their declaration
+            ty0.reverse();
+            ident1.reverse();
+            field_vis.reverse();
+            field_endianness.reverse();
+            field_switch.reverse();
+            field_map.reverse();
+            field_validate.reverse();
+            field_bit_planes.reverse();
+            field_pad.reverse();
+            field_at.reverse();
+            field_union.reverse();
+            field_presence.reverse();
+            field_ctx.reverse();
+            field_borrow.reverse();
+            field_transform.reverse();
+            checksum_field = checksum_field.map(|i| ident1.len() - 1 - i);
         }
 
         let (write_bytes_fn, read_bytes_fn): (Vec<_>, Vec<_>) =
@@ -187,40 +1226,1241 @@ fn byte_struct_macro_derive_impl(input: TokenStream, endianness_input: Endiannes
                 syn::Ident::new(name_str.1, Span::call_site()))
             }).unzip();
 
-        // quote! seems not liking using the same object twice in the content
-        let ty1 = ty0.clone();
-        let ty2 = ty0.clone();
-        let ty3 = ty0.clone();
-        let ident2 = ident1.clone();
-        let ident3 = ident1.clone();
-        let gen = quote! {
-            impl ByteStruct for #name {
-                fn write_bytes(&self, bytes: &mut [u8]) {
-                    let mut cur: usize = 0;
-                    #({
-                        let len = <#ty1>::BYTE_LEN;
-                        self.#ident1.#write_bytes_fn(&mut bytes[cur .. (cur + len)]);
-                        cur += len;
+        let mut len = Vec::new();
+        let mut write_field = Vec::new();
+        let mut read_field = Vec::new();
+        let mut validate_field = Vec::new();
+        let mut try_read_field = Vec::new();
+        let mut try_validate_field = Vec::new();
+        let mut field_order = Vec::new();
+        let mut setter = Vec::new();
+        let mut getter = Vec::new();
+        // Unpacked values are bound to locals with mixed-site hygiene, so that fields named like
+        // the other locals of the generated code, such as `len` or `bytes`, don't shadow them
+        let local1 = ident1.iter().map(|ident| {
+            let mut local = ident.clone();
+            local.set_span(ident.span().resolved_at(Span::mixed_site()));
+            local
+        }).collect::<Vec<_>>();
+        for i in 0 .. ident1.len() {
+            let (ty, ident, local) = (&ty0[i], &ident1[i], &local1[i]);
+            let field_name = ident.to_string();
+            validate_field.push(field_validate[i].as_ref().map(|validate| {
+                let message = format!("Validation failed for field `{}`", ident);
+                quote! {
+                    if !#validate(&#local) {
+                        panic!(#message);
+                    }
+                }
+            }));
+            try_validate_field.push(field_validate[i].as_ref().map(|validate| {
+                quote! {
+                    if !#validate(&#local) {
+                        return Err(#krate::ByteStructError::new(#krate::ErrorKind::ValidationFailed, Some(#field_name), cur));
+                    }
+                }
+            }));
+            let (write_fn, read_fn) = (&write_bytes_fn[i], &read_bytes_fn[i]);
+            let try_read_fn = syn::Ident::new(&format!("try_{}", read_fn), Span::call_site());
+            let probe_ty = match (&field_map[i], &field_presence[i]) {
+                (Some(Map{wire, ..}), _) => wire,
+                (None, Some(_)) => option_inner(ty).unwrap(),
+                (None, None) => ty,
+            };
+            field_order.push(match field_endianness[i] {
+                Endianness::Little => quote! {
+                    (#field_name, (&#krate::__private::OrderProbe::<#probe_ty>::new()).resolve(#krate::ByteOrder::Little))
+                },
+                Endianness::Big => quote! {
+                    (#field_name, (&#krate::__private::OrderProbe::<#probe_ty>::new()).resolve(#krate::ByteOrder::Big))
+                },
+                Endianness::Unspecified => quote! { (#field_name, #krate::ByteOrder::Fixed) },
+            });
+            // Calls through the trait spanned at the field type,
+            // so that a field missing a byte order is reported at the field
+            let (packed_write, packed_read) = match field_endianness[i] {
+                Endianness::Unspecified => (
+                    quote_spanned! {probe_ty.span()=> <#probe_ty as #krate::ByteStructWrite> },
+                    quote_spanned! {probe_ty.span()=> <#probe_ty as #krate::ByteStructRead> },
+                ),
+                _ => (
+                    quote_spanned! {probe_ty.span()=> <#probe_ty as #krate::ByteStructUnspecifiedByteOrder> },
+                    quote_spanned! {probe_ty.span()=> <#probe_ty as #krate::ByteStructUnspecifiedByteOrder> },
+                ),
+            };
+            if field_bit_planes[i] {
+                let syn::Type::Array(syn::TypeArray{elem, len: count, ..}) = ty else {
+                    return Err(syn::Error::new_spanned(ty,
+                        "byte_struct_bit_planes requires the field type to be an array"));
+                };
+                len.push(quote! { #krate::__private::bit_planes_len::<#elem>(#count) });
+                write_field.push(quote! {
+                    #krate::__private::write_bit_planes(&field[..], &mut bytes[cur .. (cur + len)]);
+                });
+                read_field.push(quote! {
+                    let #local: #ty = #krate::__private::read_bit_planes(&bytes[cur .. (cur + len)]);
+                });
+                try_read_field.push(quote! {
+                    let #local: #ty = #krate::__private::read_bit_planes(&bytes[cur .. (cur + len)]);
+                });
+                continue;
+            }
+            if let Some(Map{wire, into, from}) = &field_map[i] {
+                len.push(quote! { <#wire as #krate::ByteStructLen>::BYTE_LEN });
+                write_field.push(quote! {
+                    let wire: #wire = #from(field);
+                    #packed_write::#write_fn(&wire, &mut bytes[cur .. (cur + len)]);
+                });
+                read_field.push(quote! {
+                    let #local = #into(#packed_read::#read_fn(&bytes[cur .. (cur + len)]));
+                });
+                try_read_field.push(quote! {
+                    let #local = #into(#packed_read::#try_read_fn(&bytes[cur .. (cur + len)])
+                        .map_err(|e| e.within(Some(#field_name), cur))?);
+                });
+                continue;
+            }
+            match (&field_borrow[i], &borrow_lt) {
+                (Some(Borrow::Slice(count)), _) => {
+                    len.push(quote! { #count });
+                    write_field.push(quote! {
+                        bytes[cur .. (cur + len)].copy_from_slice(field);
+                    });
+                    read_field.push(quote! {
+                        let #local: #ty = &bytes[cur .. (cur + len)];
+                    });
+                    try_read_field.push(quote! {
+                        let #local: #ty = &bytes[cur .. (cur + len)];
+                    });
+                    continue;
+                }
+                (Some(Borrow::Value), Some(lt)) => {
+                    len.push(quote! { <#ty as #krate::ByteStructLen>::BYTE_LEN });
+                    write_field.push(quote! {
+                        <#ty as #krate::ByteStructWrite>::write_bytes(field, &mut bytes[cur .. (cur + len)]);
+                    });
+                    read_field.push(quote! {
+                        let #local = <#ty as #krate::ByteStructReadRef<#lt>>::read_bytes_ref(
+                            &bytes[cur .. (cur + len)]);
+                    });
+                    try_read_field.push(quote! {
+                        let #local = <#ty as #krate::ByteStructReadRef<#lt>>::try_read_bytes_ref(
+                            &bytes[cur .. (cur + len)]).map_err(|e| e.within(Some(#field_name), cur))?;
+                    });
+                    continue;
+                }
+                _ => (),
+            }
+            if let (true, Some(context)) = (field_ctx[i], &context) {
+                len.push(quote! { <#ty as #krate::ByteStructLen>::BYTE_LEN });
+                write_field.push(quote! {
+                    <#ty as #krate::ByteStructWriteCtx<#context>>::write_bytes_with_ctx(
+                        field, &mut bytes[cur .. (cur + len)], ctx);
+                });
+                read_field.push(quote! {
+                    let #local = <#ty as #krate::ByteStructReadCtx<#context>>::read_bytes_with_ctx(
+                        &bytes[cur .. (cur + len)], ctx);
+                });
+                try_read_field.push(quote! {
+                    let #local = <#ty as #krate::ByteStructReadCtx<#context>>::try_read_bytes_with_ctx(
+                        &bytes[cur .. (cur + len)], ctx).map_err(|e| e.within(Some(#field_name), cur))?;
+                });
+                continue;
+            }
+            match &field_presence[i] {
+                Some(Presence::Sentinel(none)) => {
+                    len.push(quote! { <#probe_ty as #krate::ByteStructLen>::BYTE_LEN });
+                    write_field.push(quote! {
+                        match field {
+                            Some(value) => #packed_write::#write_fn(value, &mut bytes[cur .. (cur + len)]),
+                            None => {
+                                let none: #probe_ty = #none;
+                                #packed_write::#write_fn(&none, &mut bytes[cur .. (cur + len)]);
+                            }
+                        }
+                    });
+                    read_field.push(quote! {
+                        let value = #packed_read::#read_fn(&bytes[cur .. (cur + len)]);
+                        let none: #probe_ty = #none;
+                        let #local = if value == none { None } else { Some(value) };
+                    });
+                    try_read_field.push(quote! {
+                        let value = #packed_read::#try_read_fn(&bytes[cur .. (cur + len)])
+                            .map_err(|e| e.within(Some(#field_name), cur))?;
+                        let none: #probe_ty = #none;
+                        let #local = if value == none { None } else { Some(value) };
+                    });
+                    continue;
+                }
+                Some(Presence::Flag(Present{on, when})) => {
+                    let mut on = on.clone();
+                    on.set_span(on.span().resolved_at(Span::mixed_site()));
+                    let present = match when {
+                        Some(when) => quote! { (#when)(&#on) },
+                        None => quote! { #on != ::core::default::Default::default() },
+                    };
+                    len.push(quote! { <#probe_ty as #krate::ByteStructLen>::BYTE_LEN });
+                    write_field.push(quote! {
+                        match field {
+                            Some(value) => #packed_write::#write_fn(value, &mut bytes[cur .. (cur + len)]),
+                            None => {
+                                for byte in &mut bytes[cur .. (cur + len)] {
+                                    *byte = 0;
+                                }
+                            }
+                        }
+                    });
+                    read_field.push(quote! {
+                        let #local = if #present {
+                            Some(#packed_read::#read_fn(&bytes[cur .. (cur + len)]))
+                        } else {
+                            None
+                        };
+                    });
+                    try_read_field.push(quote! {
+                        let #local = if #present {
+                            Some(#packed_read::#try_read_fn(&bytes[cur .. (cur + len)])
+                                .map_err(|e| e.within(Some(#field_name), cur))?)
+                        } else {
+                            None
+                        };
+                    });
+                    continue;
+                }
+                None => (),
+            }
+            let Some(switch) = &field_switch[i] else {
+                len.push(quote! { <#ty as #krate::ByteStructLen>::BYTE_LEN });
+                write_field.push(quote! {
+                    #packed_write::#write_fn(field, &mut bytes[cur .. (cur + len)]);
+                });
+                read_field.push(quote! {
+                    let #local = #packed_read::#read_fn(&bytes[cur .. (cur + len)]);
+                });
+                try_read_field.push(quote! {
+                    let #local = #packed_read::#try_read_fn(&bytes[cur .. (cur + len)])
+                        .map_err(|e| e.within(Some(#field_name), cur))?;
+                });
+                continue;
+            };
+
+            let syn::Type::Path(syn::TypePath{qself: None, path: enum_path}) = ty else {
+                return Err(syn::Error::new_spanned(ty,
+                    "byte_struct_switch requires the field type to be an enum"));
+            };
+            let (write_trait, read_trait) = match field_endianness[i] {
+                Endianness::Unspecified => (quote! { #krate::ByteStructWrite }, quote! { #krate::ByteStructRead }),
+                _ => (
+                    quote! { #krate::ByteStructUnspecifiedByteOrder },
+                    quote! { #krate::ByteStructUnspecifiedByteOrder },
+                ),
+            };
+            let mut on = switch.on.clone();
+            on.set_span(on.span().resolved_at(Span::mixed_site()));
+            let tags = switch.arms.iter().map(|(tag, _)| tag);
+            let variants = switch.arms.iter().map(|(_, variant)| variant);
+            let tags2 = tags.clone();
+            let variants2 = variants.clone();
+            let mut unique_variants = Vec::<&syn::Ident>::new();
+            for (_, variant) in &switch.arms {
+                if !unique_variants.contains(&variant) {
+                    unique_variants.push(variant);
+                }
+            }
+            let unique_variants2 = unique_variants.clone();
+            let unknown_tag = format!("Unknown tag for field `{}`", ident);
+            len.push(quote! {{
+                let mut max = 0;
+                #(
+                    let len = #krate::__private::variant_len(#enum_path::#unique_variants);
+                    if len > max {
+                        max = len;
+                    }
+                )*
+                max
+            }});
+            write_field.push(quote! {
+                let field_bytes = &mut bytes[cur .. (cur + len)];
+                let used = match field {
+                    #(#enum_path::#unique_variants2(value) => {
+                        let used = #krate::__private::variant_len(#enum_path::#unique_variants2);
+                        #write_trait::#write_fn(value, &mut field_bytes[.. used]);
+                        used
                     })*
+                };
+                for byte in &mut field_bytes[used ..] {
+                    *byte = 0;
                 }
-                fn read_bytes(bytes: &[u8]) -> Self {
-                    let mut cur: usize = 0;
-                    #(
-                        let len = <#ty2>::BYTE_LEN;
-                        let #ident2 = <#ty3>::#read_bytes_fn(&bytes[cur .. (cur + len)]);
-                        cur += len;
-                    )*
-                    #name { #(#ident3),* }
+            });
+            read_field.push(quote! {
+                let field_bytes = &bytes[cur .. (cur + len)];
+                #[allow(unreachable_patterns)]
+                let #local = match #on {
+                    #(#tags => #enum_path::#variants(#read_trait::#read_fn(
+                        &field_bytes[.. #krate::__private::variant_len(#enum_path::#variants)])),)*
+                    _ => panic!(#unknown_tag),
+                };
+            });
+            try_read_field.push(quote! {
+                let field_bytes = &bytes[cur .. (cur + len)];
+                #[allow(unreachable_patterns)]
+                let #local = match #on {
+                    #(#tags2 => #enum_path::#variants2(#read_trait::#try_read_fn(
+                        &field_bytes[.. #krate::__private::variant_len(#enum_path::#variants2)])
+                        .map_err(|e| e.within(Some(#field_name), cur))?),)*
+                    _ => return Err(#krate::ByteStructError::new(#krate::ErrorKind::InvalidValue, Some(#field_name), cur)),
+                };
+            });
+        }
+
+        // Transformed fields are unpacked from a transformed copy of their bytes,
+        // and transformed in place after packing
+        for (i, transform) in field_transform.iter().enumerate() {
+            let Some(Sanitize{decode, encode}) = transform else {
+                continue;
+            };
+            let (ty, local, field_len) = (&ty0[i], &local1[i], &len[i]);
+            let (read, try_read, write) = (&read_field[i], &try_read_field[i], &write_field[i]);
+            let copy = quote! {
+                let mut transformed = [0u8; #field_len];
+                transformed.copy_from_slice(&bytes[cur .. (cur + len)]);
+                #decode(&mut transformed[..]);
+                let bytes = &transformed[..];
+            };
+            read_field[i] = quote! {
+                let #local = {
+                    #copy
+                    let cur: usize = 0;
+                    #read
+                    #local
+                };
+            };
+            try_read_field[i] = quote! {
+                let #local = {
+                    #copy
+                    (|| -> Result<#ty, #krate::ByteStructError> {
+                        let cur: usize = 0;
+                        #try_read
+                        Ok(#local)
+                    })()
+                }.map_err(|e| e.within(None, cur))?;
+            };
+            write_field[i] = quote! {
+                #write
+                #encode(&mut bytes[cur .. (cur + len)]);
+            };
+        }
+
+        let field_count = ident1.len();
+        // The bytes occupied by each field including its leading padding,
+        // and the offset of each field after its leading padding
+        // The sums are overflow-checked, so that an overflowing BYTE_LEN fails the build
+        // Each offset follows the end of the previous field, except for fields at an absolute offset,
+        // which are padded from there and checked not to overlap it when BYTE_LEN is evaluated.
+        // Union views take the offset of their primary field, and are left out of the sums
+        let mut span = Vec::new();
+        let mut offset = Vec::<proc_macro2::TokenStream>::new();
+        let mut primary = 0;
+        for i in 0 .. field_count {
+            if field_union[i] {
+                let (primary_offset, primary_len, view_len) = (&offset[primary], &len[primary], &len[i]);
+                offset.push(primary_offset.clone());
+                span.push(quote! { #krate::__private::union_span(#view_len, #primary_len) });
+                continue;
+            }
+            let prev_end = match i {
+                0 => quote! { 0 },
+                _ => {
+                    let (prev_offset, prev_len) = (&offset[primary], &len[primary]);
+                    quote! { #krate::__private::len_sum(&[#prev_offset, #prev_len]) }
+                }
+            };
+            primary = i;
+            if let Some(at) = &field_at[i] {
+                field_pad[i] = Some(syn::parse_quote! { #krate::__private::offset_pad(#at, #prev_end) });
+                offset.push(quote! { #at });
+            } else {
+                offset.push(match &field_pad[i] {
+                    Some(pad) => quote! { #krate::__private::len_sum(&[#prev_end, #pad]) },
+                    None => prev_end,
+                });
+            }
+            let field_len = &len[i];
+            span.push(match &field_pad[i] {
+                Some(pad) => quote! { #krate::__private::len_sum(&[#pad, #field_len]) },
+                None => quote! { #field_len },
+            });
+        }
+        // Views are unpacked at their own offset without moving the cursor, and not packed
+        let mut step = len.clone();
+        for i in (0 .. field_count).filter(|&i| field_union[i]) {
+            let (field_offset, field_len, local) = (&offset[i], &len[i], &local1[i]);
+            let (read, validate) = (&read_field[i], validate_field[i].take());
+            let (try_read, try_validate) = (&try_read_field[i], try_validate_field[i].take());
+            read_field[i] = quote! {
+                let #local = {
+                    let cur: usize = #field_offset;
+                    let len = #field_len;
+                    #read
+                    #validate
+                    #local
+                };
+            };
+            try_read_field[i] = quote! {
+                let #local = {
+                    let cur: usize = #field_offset;
+                    let len = #field_len;
+                    #try_read
+                    #try_validate
+                    #local
+                };
+            };
+            write_field[i] = quote! { let _ = field; };
+            step[i] = quote! { 0 };
+        }
+        let write_pad = field_pad.iter().map(|pad| pad.as_ref().map(|pad| quote! {
+            let pad: usize = #pad;
+            for byte in &mut bytes[cur .. (cur + pad)] {
+                *byte = 0;
+            }
+            cur += pad;
+        }));
+        let read_pad = field_pad.iter().map(|pad| pad.as_ref().map(|pad| quote! {
+            cur += #pad;
+        })).collect::<Vec<_>>();
+        let read_pad2 = read_pad.clone();
+        // The checksum is sealed once all fields are packed, and verified by try_read_bytes once
+        // all fields are unpacked, so that it covers the bytes of the whole structure
+        let (checksum_write, checksum_try_read) = match checksum_field {
+            Some(i) => {
+                let (field_offset, field_len) = (&offset[i], &len[i]);
+                let len_message = format!("Field `{}` of {} must be 2 bytes to hold an internet_checksum",
+                    ident1[i], name);
+                let checked_offset = quote! {
+                    const {
+                        assert!(#field_len == 2, #len_message);
+                        #field_offset
+                    }
+                };
+                let field_name = ident1[i].to_string();
+                (
+                    Some(quote! {
+                        #krate::InternetChecksum::new().seal(
+                            &mut bytes[.. <Self as #krate::ByteStructLen>::BYTE_LEN], #checked_offset);
+                    }),
+                    Some(quote! {
+                        if !#krate::InternetChecksum::new().verify(&bytes[.. <Self as #krate::ByteStructLen>::BYTE_LEN]) {
+                            return Err(#krate::ByteStructError::new(#krate::ErrorKind::ValidationFailed,
+                                Some(#field_name), #checked_offset));
+                        }
+                    }),
+                )
+            }
+            None => (None, None),
+        };
+        // The struct is not generic if it has a sanitizer, so its length can size a local buffer
+        let (sanitize_write, sanitize_read, sanitize_try_read) = match &sanitize {
+            Some(Sanitize{decode, encode}) => (
+                Some(quote! {
+                    #encode(&mut bytes[.. <#name as #krate::ByteStructLen>::BYTE_LEN]);
+                }),
+                Some(quote! {
+                    let mut sanitized = [0u8; <#name as #krate::ByteStructLen>::BYTE_LEN];
+                    sanitized.copy_from_slice(&bytes[.. <#name as #krate::ByteStructLen>::BYTE_LEN]);
+                    #decode(&mut sanitized[..]);
+                    let bytes = &sanitized[..];
+                }),
+                Some(quote! {
+                    if bytes.len() < <#name as #krate::ByteStructLen>::BYTE_LEN {
+                        return Err(#krate::ByteStructError::new(#krate::ErrorKind::ShortBuffer, None, bytes.len()));
+                    }
+                    let mut sanitized = [0u8; <#name as #krate::ByteStructLen>::BYTE_LEN];
+                    sanitized.copy_from_slice(&bytes[.. <#name as #krate::ByteStructLen>::BYTE_LEN]);
+                    #decode(&mut sanitized[..]);
+                    let bytes = &sanitized[..];
+                }),
+            ),
+            None => (None, None, None),
+        };
+        // Generic structs can't be named in a const item, and get their offsets checked by the sums only
+        let offset_assertion = ast.generics.params.is_empty().then(|| {
+            let message = (0 .. field_count).map(|i|
+                format!("Field `{}` of {} extends past BYTE_LEN", ident1[i], name));
+            let (field_offset, field_len) = (&offset, &len);
+            quote! {
+                const _: () = {
+                    #(assert!(
+                        #krate::__private::len_sum(&[#field_offset, #field_len])
+                            <= <#name as #krate::ByteStructLen>::BYTE_LEN,
+                        #message
+                    );)*
+                };
+            }
+        });
+        let len_assertion = assert_len.map(|expected| {
+            let message = format!("BYTE_LEN of {} doesn't match byte_struct_assert_len", name);
+            quote! {
+                const _: () = assert!(<#name as #krate::ByteStructLen>::BYTE_LEN == #expected, #message);
+            }
+        });
+        let register_impl = register.map(|addr| quote! {
+            impl #impl_generics #krate::ByteStructRegister for #name #ty_generics #where_clause {
+                const ADDR: u8 = #addr;
+            }
+        });
+        let arbitrary_impl = arbitrary.then(|| {
+            let mut generics = ast.generics.clone();
+            generics.params.insert(0, syn::parse_quote!('arbitrary));
+            let (arbitrary_impl_generics, _, _) = generics.split_for_impl();
+            quote! {
+                impl #arbitrary_impl_generics #krate::__private::arbitrary::Arbitrary<'arbitrary>
+                    for #name #ty_generics #where_clause {
+                    fn arbitrary(u: &mut #krate::__private::arbitrary::Unstructured<'arbitrary>)
+                        -> #krate::__private::arbitrary::Result<Self> {
+                        let bytes = u.bytes(<Self as #krate::ByteStructLen>::BYTE_LEN)?;
+                        Self::try_read_bytes(bytes)
+                            .map_err(|_| #krate::__private::arbitrary::Error::IncorrectFormat)
+                    }
+
+                    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+                        let len = <Self as #krate::ByteStructLen>::BYTE_LEN;
+                        (len, Some(len))
+                    }
+                }
+            }
+        });
+        let tag_impl = tag.map(|tag| quote! {
+            impl #impl_generics #krate::ByteStructTag for #name #ty_generics #where_clause {
+                const TAG: u32 = #tag;
+            }
+        });
+        if found_setters {
+            for i in 0 .. field_count {
+                // Writing a tagged union field alone would leave its tag inconsistent,
+                // and union views aren't packed
+                if field_switch[i].is_some() || field_union[i] {
+                    continue;
+                }
+                let (ty, ident) = (&ty0[i], &ident1[i]);
+                let setter_name = format_ident!("set_{}", ident);
+                let doc = format!("Packs `value` into the bytes of field `{}` in `bytes`, \
+                    leaving the other fields untouched.", ident);
+                let (field_len, field_offset, write) = (&len[i], &offset[i], &write_field[i]);
+                setter.push(quote! {
+                    #[doc = #doc]
+                    ///
+                    /// Panics if `bytes` is too short to contain the field.
+                    #[allow(dead_code)]
+                    pub fn #setter_name(bytes: &mut [u8], value: #ty) {
+                        let cur: usize = #field_offset;
+                        let len = #field_len;
+                        let field = &value;
+                        #write
+                    }
+                });
+            }
+        }
+        if getters {
+            // Fields of a struct with a lifetime borrow from the bytes for it
+            let bytes_ty = match &borrow_lt {
+                Some(lt) => quote! { &#lt [u8] },
+                None => quote! { &[u8] },
+            };
+            for i in 0 .. field_count {
+                // The tag or flag of these lives in another field
+                if field_switch[i].is_some() || matches!(field_presence[i], Some(Presence::Flag(_))) {
+                    continue;
+                }
+                let (ty, ident, local) = (&ty0[i], &ident1[i], &local1[i]);
+                let field_name = ident.to_string();
+                let (getter_name, try_getter_name) = (format_ident!("read_field_{}", ident),
+                    format_ident!("try_read_field_{}", ident));
+                let doc = format!("Unpacks field `{}` alone from `bytes`, without unpacking the other fields.", ident);
+                let try_doc = format!("Unpacks field `{}` alone from `bytes`, without unpacking the other fields, \
+                    reporting malformed input as an error.", ident);
+                let (field_len, field_offset) = (&len[i], &offset[i]);
+                let (read, validate) = (&read_field[i], &validate_field[i]);
+                let (try_read, try_validate) = (&try_read_field[i], &try_validate_field[i]);
+                getter.push(quote! {
+                    #[doc = #doc]
+                    ///
+                    /// Panics if `bytes` is too short to contain the field, or if the field is malformed.
+                    #[allow(dead_code)]
+                    pub fn #getter_name(bytes: #bytes_ty) -> #ty {
+                        let cur: usize = #field_offset;
+                        let len = #field_len;
+                        #read
+                        #validate
+                        #local
+                    }
+
+                    #[doc = #try_doc]
+                    #[allow(dead_code)]
+                    pub fn #try_getter_name(bytes: #bytes_ty) -> Result<#ty, #krate::ByteStructError> {
+                        let cur: usize = #field_offset;
+                        let len = #field_len;
+                        if bytes.len() < cur + len {
+                            return Err(#krate::ByteStructError::new(#krate::ErrorKind::ShortBuffer, Some(#field_name), cur));
+                        }
+                        #try_read
+                        #try_validate
+                        Ok(#local)
+                    }
+                });
+            }
+        }
+        let field_info = (0 .. field_count).map(|i| {
+            let field_name = ident1[i].to_string();
+            let (field_len, field_offset) = (&len[i], &offset[i]);
+            let byte_order = match field_endianness[i] {
+                Endianness::Little => quote! { #krate::ByteOrder::Little },
+                Endianness::Big => quote! { #krate::ByteOrder::Big },
+                Endianness::Unspecified => quote! { #krate::ByteOrder::Fixed },
+            };
+            quote! {
+                #krate::FieldInfo {
+                    name: #field_name,
+                    offset: #field_offset,
+                    len: #field_len,
+                    byte_order: #byte_order,
+                }
+            }
+        });
+
+        let columns_impl = columns.then(|| {
+            let vis = &ast.vis;
+            let columns_name = format_ident!("{}Columns", name);
+            let doc = format!("The fields of many `{}` records, each stored as a column.", name);
+            let decode_column = (0 .. field_count).map(|i| {
+                let (ident, local) = (&ident1[i], &local1[i]);
+                let (field_offset, field_len) = (&offset[i], &len[i]);
+                let (read, validate) = (&read_field[i], &validate_field[i]);
+                quote! {
+                    #ident: (0 .. count).map(|record| {
+                        let bytes = &bytes[record * <#name as #krate::ByteStructLen>::BYTE_LEN ..];
+                        let cur: usize = #field_offset;
+                        let len = #field_len;
+                        #read
+                        #validate
+                        #local
+                    }).collect()
+                }
+            });
+            let try_decode_column = (0 .. field_count).map(|i| {
+                let (ty, ident, local) = (&ty0[i], &ident1[i], &local1[i]);
+                let (field_offset, field_len) = (&offset[i], &len[i]);
+                let (read, validate) = (&try_read_field[i], &try_validate_field[i]);
+                quote! {
+                    #ident: (0 .. count).map(|record| {
+                        let start = record * <#name as #krate::ByteStructLen>::BYTE_LEN;
+                        let bytes = &bytes[start ..];
+                        (|| -> Result<#ty, #krate::ByteStructError> {
+                            let cur: usize = #field_offset;
+                            let len = #field_len;
+                            #read
+                            #validate
+                            Ok(#local)
+                        })().map_err(|e| e.within_element(record, start))
+                    }).collect::<Result<_, _>>()?
+                }
+            });
+            let (ty, ident) = (&ty0, &ident1);
+            quote! {
+                #[doc = #doc]
+                #vis struct #columns_name {
+                    #(#field_vis #ident: #krate::__private::Vec<#ty>,)*
+                }
+
+                impl #name {
+                    /// Unpacks `count` consecutive records from `bytes` into columns.
+                    ///
+                    /// Panics if `bytes` is shorter than `count` records.
+                    #[allow(dead_code)]
+                    pub fn decode_columns(bytes: &[u8], count: usize) -> #columns_name {
+                        assert!(
+                            #krate::__private::records_fit::<#name>(bytes, count),
+                            "Buffer too short for the records"
+                        );
+                        #columns_name {
+                            #(#decode_column,)*
+                        }
+                    }
+
+                    /// Unpacks `count` consecutive records from `bytes` into columns,
+                    /// reporting malformed input as an error.
+                    #[allow(dead_code)]
+                    pub fn try_decode_columns(bytes: &[u8], count: usize)
+                        -> Result<#columns_name, #krate::ByteStructError> {
+                        if !#krate::__private::records_fit::<#name>(bytes, count) {
+                            return Err(#krate::ByteStructError::new(#krate::ErrorKind::ShortBuffer, None, bytes.len()));
+                        }
+                        Ok(#columns_name {
+                            #(#try_decode_column,)*
+                        })
+                    }
+                }
+            }
+        });
+
+        // quote! seems not liking using the same object twice in the content
+        let len1 = step.clone();
+        let len2 = step.clone();
+        let len3 = step;
+        let (ident2, local2) = (ident1.clone(), local1.clone());
+        // The tag of a tagged union field is packed from the active variant, so that it can't
+        // disagree with it. A variant with several tags keeps the stored tag if it is one of them
+        let mut bind_field = ident1.iter().map(|ident| quote! { let field = &self.#ident; }).collect::<Vec<_>>();
+        let mut tagged = Vec::new();
+        for (i, switch) in field_switch.iter().enumerate() {
+            let Some(Switch{on, arms}) = switch else {
+                continue;
+            };
+            let Some(j) = ident1.iter().position(|ident| ident == on) else {
+                continue;
+            };
+            if tagged.contains(&j) {
+                continue;
+            }
+            tagged.push(j);
+            let syn::Type::Path(syn::TypePath{qself: None, path: enum_path}) = &ty0[i] else {
+                continue;
+            };
+            let mut variant_arms = Vec::new();
+            let mut seen = Vec::<&syn::Ident>::new();
+            for (_, variant) in arms {
+                if seen.contains(&variant) {
+                    continue;
+                }
+                seen.push(variant);
+                let tags = arms.iter().filter(|(_, v)| v == variant).map(|(tag, _)| tag).collect::<Vec<_>>();
+                let first = tags[0];
+                if tags.len() > 1 {
+                    variant_arms.push(quote! {
+                        #enum_path::#variant(_) if matches!(self.#on, #(#tags)|*) => &self.#on,
+                    });
+                }
+                variant_arms.push(quote! {
+                    #enum_path::#variant(_) => {
+                        tag = #first;
+                        &tag
+                    }
+                });
+            }
+            let (tag_ty, body) = (&ty0[j], &ident1[i]);
+            bind_field[j] = quote! {
+                let tag: #tag_ty;
+                let field = match &self.#body {
+                    #(#variant_arms)*
+                };
+            };
+        }
+        let field_name1 = ident1.iter().map(|ident| ident.to_string());
+        // The functions become inherent ones if the trait impls are suppressed
+        let fn_vis = if no_unspecified {
+            quote! { #[allow(dead_code)] pub }
+        } else {
+            quote! {}
+        };
+        // With a context, the same functions take it as an extra argument,
+        // and with a lifetime, the unpacking functions borrow the bytes for it
+        let (write_sig, read_sig, try_read_sig) = match (&context, &borrow_lt) {
+            (_, Some(lt)) => (
+                quote! { fn write_bytes(&self, bytes: &mut [u8]) },
+                quote! { fn read_bytes_ref(bytes: &#lt [u8]) -> Self },
+                quote! { fn try_read_bytes_ref(bytes: &#lt [u8]) -> Result<Self, #krate::ByteStructError> },
+            ),
+            (Some(context), None) => (
+                quote! {
+                    #[allow(unused_variables)]
+                    fn write_bytes_with_ctx(&self, bytes: &mut [u8], ctx: &#context)
+                },
+                quote! {
+                    #[allow(unused_variables)]
+                    fn read_bytes_with_ctx(bytes: &[u8], ctx: &#context) -> Self
+                },
+                quote! {
+                    #[allow(unused_variables)]
+                    fn try_read_bytes_with_ctx(bytes: &[u8], ctx: &#context) -> Result<Self, #krate::ByteStructError>
+                },
+            ),
+            (None, None) => (
+                quote! { #fn_vis fn write_bytes(&self, bytes: &mut [u8]) },
+                quote! { #fn_vis fn read_bytes(bytes: &[u8]) -> Self },
+                quote! { #fn_vis fn try_read_bytes(bytes: &[u8]) -> Result<Self, #krate::ByteStructError> },
+            ),
+        };
+        let write_fns = quote! {
+            #write_sig {
+                let mut cur: usize = 0;
+                #({
+                    #write_pad
+                    let len = #len1;
+                    #bind_field
+                    #write_field
+                    cur += len;
+                })*
+                #checksum_write
+                #sanitize_write
+            }
+        };
+        let read_fns = quote! {
+            #read_sig {
+                #sanitize_read
+                let mut cur: usize = 0;
+                #(
+                    #read_pad
+                    let len = #len2;
+                    #read_field
+                    #validate_field
+                    cur += len;
+                )*
+                #name { #(#ident1: #local1),* }
+            }
+            #try_read_sig {
+                #sanitize_try_read
+                let mut cur: usize = 0;
+                #(
+                    #read_pad2
+                    let len = #len3;
+                    if bytes.len() < cur + len {
+                        return Err(#krate::ByteStructError::new(#krate::ErrorKind::ShortBuffer, Some(#field_name1), cur));
+                    }
+                    #try_read_field
+                    #try_validate_field
+                    cur += len;
+                )*
+                #checksum_try_read
+                Ok(#name { #(#ident2: #local2),* })
+            }
+        };
+        let (write_fns, read_fns) = match access {
+            Access::ReadWrite => (Some(write_fns), Some(read_fns)),
+            Access::ReadOnly => (None, Some(read_fns)),
+            Access::WriteOnly => (Some(write_fns), None),
+        };
+        // Without the trait impls, the blanket impl of ByteStructUnspecifiedByteOrder doesn't apply either
+        let byte_struct_impl = if no_unspecified {
+            quote! {
+                impl #impl_generics #name #ty_generics #where_clause {
+                    #write_fns
+                    #read_fns
+                }
+            }
+        } else {
+            let (write_trait, read_trait) = match (&context, &borrow_lt) {
+                (_, Some(lt)) => (quote! { #krate::ByteStructWrite }, quote! { #krate::ByteStructReadRef<#lt> }),
+                (Some(context), None) => (
+                    quote! { #krate::ByteStructWriteCtx<#context> },
+                    quote! { #krate::ByteStructReadCtx<#context> },
+                ),
+                (None, None) => (quote! { #krate::ByteStructWrite }, quote! { #krate::ByteStructRead }),
+            };
+            let write_impl = write_fns.map(|write_fns| quote! {
+                impl #impl_generics #write_trait for #name #ty_generics #where_clause {
+                    #write_fns
                 }
+            });
+            let read_impl = read_fns.map(|read_fns| quote! {
+                impl #impl_generics #read_trait for #name #ty_generics #where_clause {
+                    #read_fns
+                }
+            });
+            quote! {
+                #write_impl
+                #read_impl
+            }
+        };
+        let gen = quote! {
+            #byte_struct_impl
+
+            impl #impl_generics #krate::ByteStructLen for #name #ty_generics #where_clause {
+                const BYTE_LEN: usize = #krate::__private::len_sum(&[#(#span),*]);
             }
 
-            impl ByteStructLen for #name {
-                const BYTE_LEN: usize = #(<#ty0>::BYTE_LEN)+*;
+            impl #impl_generics #krate::ByteStructLayout for #name #ty_generics #where_clause {
+                const FIELDS: &'static [#krate::FieldInfo<'static>] = &[#(#field_info),*];
+            }
+
+            #tag_impl
+
+            #register_impl
+
+            #columns_impl
+
+            #arbitrary_impl
+
+            #len_assertion
+
+            #offset_assertion
+
+            impl #impl_generics #name #ty_generics #where_clause {
+                /// Returns each field name with the byte order it is packed with,
+                /// after resolving byte order attributes on the field and on the struct.
+                ///
+                /// Fields whose type implements `ByteStruct` are reported as `byte_struct::ByteOrder::Fixed`
+                /// because byte order attributes have no effect on them.
+                #[allow(dead_code)]
+                pub fn field_byte_orders() -> [(&'static str, #krate::ByteOrder); #field_count] {
+                    #[allow(unused_imports)]
+                    use #krate::__private::{ResolveDeclared, ResolveFixed};
+                    [#(#field_order),*]
+                }
+
+                #(#setter)*
+
+                #(#getter)*
             }
         };
-        gen.into()
+        Ok(gen)
 
     } else {
-        panic!("Only support struct with named fields!");
+        Err(syn::Error::new_spanned(name, "Only support struct with named fields!"))
+    }
+}
+
+/// Options given by the attributes on the struct
+struct StructOptions {
+    endianness: Option<Endianness>,
+    setters: bool,
+    getters: bool,
+    trailer: bool,
+    assert_len: Option<syn::Expr>,
+    sanitize: Option<Sanitize>,
+    no_unspecified: bool,
+    access: Access,
+    tag: Option<syn::Expr>,
+    register: Option<syn::Expr>,
+    columns: bool,
+    arbitrary: bool,
+    context: Option<syn::Type>,
+    krate: syn::Path,
+}
+
+/// Which of packing and unpacking is generated
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Access {
+    ReadWrite,
+    ReadOnly,
+    WriteOnly,
+}
+
+/// Parsed content of `sanitize = "path"` or `sanitize(decode = "path", encode = "path")`
+/// in `#[byte_struct(...)]`, and of `transform` in the same form on a field
+struct Sanitize {
+    decode: syn::Path,
+    encode: syn::Path,
+}
+
+impl Sanitize {
+    fn parse_meta(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Self> {
+        if meta.input.peek(syn::Token![=]) {
+            let path = meta.value()?.parse::<syn::LitStr>()?.parse::<syn::Path>()?;
+            return Ok(Sanitize { decode: path.clone(), encode: path });
+        }
+        let (mut decode, mut encode) = (None, None);
+        meta.parse_nested_meta(|meta| {
+            let path = meta.value()?.parse::<syn::LitStr>()?.parse::<syn::Path>()?;
+            if meta.path.is_ident("decode") {
+                decode = Some(path);
+            } else if meta.path.is_ident("encode") {
+                encode = Some(path);
+            } else {
+                return Err(meta.error("expected `decode` or `encode`"));
+            }
+            Ok(())
+        })?;
+        match (decode, encode) {
+            (Some(decode), Some(encode)) => Ok(Sanitize { decode, encode }),
+            _ => Err(meta.error("both `decode` and `encode` are required")),
+        }
+    }
+}
+
+fn parse_struct_options(attrs: &[syn::Attribute]) -> syn::Result<StructOptions> {
+    let mut found_le = false;
+    let mut found_be = false;
+    let mut setters = false;
+    let mut getters = false;
+    let mut trailer = false;
+    let mut assert_len = None;
+    let mut sanitize = None;
+    let mut no_unspecified = false;
+    let mut access = Access::ReadWrite;
+    let mut tag = None;
+    let mut register = None;
+    let mut columns = false;
+    let mut arbitrary = false;
+    let mut context = None;
+    let mut krate = syn::parse_quote!(::byte_struct);
+    for attr in attrs {
+        if attr.path().is_ident("byte_struct_assert_len") {
+            assert_len = Some(attr.parse_args::<syn::Expr>()?);
+            continue;
+        }
+        if attr.path().is_ident("byte_struct_register") {
+            let mut addr = None;
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("addr") {
+                    addr = Some(meta.value()?.parse::<syn::Expr>()?);
+                } else if meta.path.is_ident("endian") {
+                    match parse_endian(&meta)? {
+                        Endianness::Little => found_le = true,
+                        _ => found_be = true,
+                    }
+                } else {
+                    return Err(meta.error("expected `addr` or `endian`"));
+                }
+                Ok(())
+            })?;
+            if addr.is_none() {
+                return Err(syn::Error::new_spanned(attr, "byte_struct_register requires `addr`"));
+            }
+            if found_be && found_le {
+                return Err(syn::Error::new_spanned(attr,
+                    "Found conflicting byte_struct_le and byte_struct_be attributes"));
+            }
+            register = addr;
+            continue;
+        }
+        if attr.path().is_ident("byte_struct") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("endian") {
+                    match parse_endian(&meta)? {
+                        Endianness::Little => found_le = true,
+                        _ => found_be = true,
+                    }
+                } else if meta.path.is_ident("setters") {
+                    setters = true;
+                } else if meta.path.is_ident("getters") {
+                    getters = true;
+                } else if meta.path.is_ident("trailer") {
+                    trailer = true;
+                } else if meta.path.is_ident("assert_len") {
+                    assert_len = Some(meta.value()?.parse::<syn::Expr>()?);
+                } else if meta.path.is_ident("sanitize") {
+                    sanitize = Some(Sanitize::parse_meta(&meta)?);
+                } else if meta.path.is_ident("no_unspecified") {
+                    no_unspecified = true;
+                } else if meta.path.is_ident("read_only") || meta.path.is_ident("write_only") {
+                    let requested = if meta.path.is_ident("read_only") {
+                        Access::ReadOnly
+                    } else {
+                        Access::WriteOnly
+                    };
+                    if access != Access::ReadWrite && access != requested {
+                        return Err(meta.error("read_only and write_only can't be combined"));
+                    }
+                    access = requested;
+                } else if meta.path.is_ident("tag") {
+                    tag = Some(meta.value()?.parse::<syn::Expr>()?);
+                } else if meta.path.is_ident("register") {
+                    register = Some(meta.value()?.parse::<syn::Expr>()?);
+                } else if meta.path.is_ident("columns") {
+                    columns = true;
+                } else if meta.path.is_ident("arbitrary") {
+                    arbitrary = true;
+                } else if meta.path.is_ident("context") {
+                    context = Some(meta.value()?.parse::<syn::LitStr>()?.parse::<syn::Type>()?);
+                } else if meta.path.is_ident("crate") {
+                    krate = meta.value()?.parse::<syn::LitStr>()?.parse::<syn::Path>()?;
+                } else {
+                    return Err(meta.error(
+                        "expected `endian`, `setters`, `getters`, `trailer`, `assert_len`, `sanitize`, `no_unspecified`, \
+                        `read_only`, `write_only`, `tag`, `register`, `columns`, `arbitrary`, `context` or `crate`"));
+                }
+                Ok(())
+            })?;
+            if found_be && found_le {
+                return Err(syn::Error::new_spanned(attr,
+                    "Found conflicting byte_struct_le and byte_struct_be attributes"));
+            }
+            continue;
+        }
+        let syn::Attribute{meta: syn::Meta::Path(syn::Path{segments, ..}), ..} = attr else {continue};
+        if segments.len() != 1 {
+            continue;
+        }
+        match segments[0].ident.to_string().as_str() {
+            "byte_struct_le" => found_le = true,
+            "byte_struct_be" => found_be = true,
+            "byte_struct_setters" => setters = true,
+            _ => ()
+        };
+        if found_be && found_le {
+            return Err(syn::Error::new_spanned(attr,
+                "Found conflicting byte_struct_le and byte_struct_be attributes"));
+        }
+    }
+    let endianness = if found_le {
+        Some(Endianness::Little)
+    } else if found_be {
+        Some(Endianness::Big)
+    } else {
+        None
+    };
+    Ok(StructOptions { endianness, setters, getters, trailer, assert_len, sanitize, no_unspecified, access, tag, register, columns, arbitrary, context,
+        krate })
+}
+
+/// Parses the value of `endian = "le"` or `endian = "be"` in `#[byte_struct(...)]`
+fn parse_endian(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Endianness> {
+    let value = meta.value()?.parse::<syn::LitStr>()?;
+    match value.value().as_str() {
+        "le" => Ok(Endianness::Little),
+        "be" => Ok(Endianness::Big),
+        _ => Err(syn::Error::new(value.span(), "expected \"le\" or \"be\"")),
+    }
+}
+
+/// Parsed content of `#[byte_struct_switch(on = "field", tag => Variant, ...)]`
+/// or `switch(...)` in `#[byte_struct(...)]`
+struct Switch {
+    on: syn::Ident,
+    arms: Vec<(proc_macro2::TokenStream, syn::Ident)>,
+}
+
+impl syn::parse::Parse for Switch {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let key: syn::Ident = input.parse()?;
+        if key != "on" {
+            return Err(syn::Error::new(key.span(), "expected `on = \"field\"`"));
+        }
+        input.parse::<syn::Token![=]>()?;
+        let on = input.parse::<syn::LitStr>()?.parse::<syn::Ident>()?;
+        let mut arms = Vec::new();
+        while !input.is_empty() {
+            input.parse::<syn::Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let tag = if input.peek(syn::Token![-]) || input.peek(syn::Lit) {
+                let minus = input.parse::<Option<syn::Token![-]>>()?;
+                let lit = input.parse::<syn::Lit>()?;
+                quote! { #minus #lit }
+            } else {
+                let path = input.parse::<syn::Path>()?;
+                quote! { #path }
+            };
+            input.parse::<syn::Token![=>]>()?;
+            arms.push((tag, input.parse::<syn::Ident>()?));
+        }
+        Ok(Switch { on, arms })
+    }
+}
+
+/// How an `Option` field tells whether it holds a value
+enum Presence {
+    /// `#[byte_struct_none = value]` or `none = value` in `#[byte_struct(...)]`
+    Sentinel(syn::Expr),
+    /// `#[byte_struct_present(...)]` or `present(...)` in `#[byte_struct(...)]`
+    Flag(Present),
+}
+
+/// Parsed content of `#[byte_struct_present(on = "field", when = "expr")]`
+struct Present {
+    on: syn::Ident,
+    when: Option<syn::Expr>,
+}
+
+impl syn::parse::Parse for Present {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let tokens: proc_macro2::TokenStream = input.parse()?;
+        let mut on = None;
+        let mut when = None;
+        syn::parse::Parser::parse2(syn::meta::parser(|meta| {
+            let value = meta.value()?.parse::<syn::LitStr>()?;
+            if meta.path.is_ident("on") {
+                on = Some(value.parse()?);
+            } else if meta.path.is_ident("when") {
+                when = Some(value.parse()?);
+            } else {
+                return Err(meta.error("expected `on` or `when`"));
+            }
+            Ok(())
+        }), tokens.clone())?;
+        match on {
+            Some(on) => Ok(Present { on, when }),
+            None => Err(syn::Error::new_spanned(tokens, "byte_struct_present requires `on`")),
+        }
+    }
+}
+
+/// Returns `T` of a field type `Option<T>`
+fn option_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(syn::TypePath{qself: None, path}) = ty else {
+        return None;
+    };
+    let last = path.segments.last()?;
+    let syn::PathArguments::AngleBracketed(args) = &last.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(syn::GenericArgument::Type(inner)) if last.ident == "Option" && args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+/// How a field borrows from the bytes being unpacked
+enum Borrow {
+    /// A `&'a [u8]` slice of the given length
+    Slice(syn::Expr),
+    /// A type implementing `ByteStructReadRef<'a>`
+    Value,
+}
+
+/// Returns whether `tokens` mention the lifetime `lt`
+fn mentions_lifetime(tokens: proc_macro2::TokenStream, lt: &syn::Lifetime) -> bool {
+    let mut after_quote = false;
+    tokens.into_iter().any(|token| {
+        let found = match &token {
+            proc_macro2::TokenTree::Group(group) => mentions_lifetime(group.stream(), lt),
+            proc_macro2::TokenTree::Ident(ident) => after_quote && *ident == lt.ident,
+            _ => false,
+        };
+        after_quote = matches!(&token, proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '\'');
+        found
+    })
+}
+
+/// Returns whether `ty` is a `&[u8]` slice
+fn is_byte_slice(ty: &syn::Type) -> bool {
+    let syn::Type::Reference(syn::TypeReference{elem, mutability: None, ..}) = ty else {
+        return false;
+    };
+    matches!(&**elem, syn::Type::Slice(syn::TypeSlice{elem, ..})
+        if matches!(&**elem, syn::Type::Path(path) if path.path.is_ident("u8")))
+}
+
+/// Parsed content of `#[byte_struct_map(wire = "Type", into = "path", from = "path")]`
+/// or `map(...)` in `#[byte_struct(...)]`
+struct Map {
+    wire: syn::Type,
+    into: syn::Path,
+    from: syn::Path,
+}
+
+impl syn::parse::Parse for Map {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let tokens: proc_macro2::TokenStream = input.parse()?;
+        let mut wire = None;
+        let mut into = None;
+        let mut from = None;
+        syn::parse::Parser::parse2(syn::meta::parser(|meta| {
+            let value = meta.value()?.parse::<syn::LitStr>()?;
+            if meta.path.is_ident("wire") {
+                wire = Some(value.parse()?);
+            } else if meta.path.is_ident("into") {
+                into = Some(value.parse()?);
+            } else if meta.path.is_ident("from") {
+                from = Some(value.parse()?);
+            } else {
+                return Err(meta.error("expected `wire`, `into` or `from`"));
+            }
+            Ok(())
+        }), tokens.clone())?;
+        match (wire, into, from) {
+            (Some(wire), Some(into), Some(from)) => Ok(Map { wire, into, from }),
+            _ => Err(syn::Error::new_spanned(tokens, "byte_struct_map requires `wire`, `into` and `from`")),
+        }
     }
 }
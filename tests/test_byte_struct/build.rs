@@ -0,0 +1,16 @@
+use byte_struct_codegen::{structs_from_c, structs_from_kaitai, Abi};
+use std::path::Path;
+
+fn main() {
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+
+    let header = std::fs::read_to_string("c/vendor.h").unwrap();
+    let rust = structs_from_c(&header, &Abi::X86_64_SYSV).unwrap();
+    std::fs::write(Path::new(&out_dir).join("vendor.rs"), rust).unwrap();
+    println!("cargo:rerun-if-changed=c/vendor.h");
+
+    let ksy = std::fs::read_to_string("ksy/sensor.ksy").unwrap();
+    let rust = structs_from_kaitai(&ksy).unwrap();
+    std::fs::write(Path::new(&out_dir).join("sensor.rs"), rust).unwrap();
+    println!("cargo:rerun-if-changed=ksy/sensor.ksy");
+}
@@ -0,0 +1,51 @@
+#pragma once
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define VENDOR_NAME_LEN 6
+#define VENDOR_SLOTS (2)
+
+typedef uint16_t vendor_id_t;
+
+typedef enum {
+    VENDOR_KIND_A,
+    VENDOR_KIND_B = 0x10,
+} vendor_kind_t;
+
+/* A device record, as laid out by the vendor SDK */
+typedef struct vendor_record {
+    uint8_t flags;
+    vendor_id_t id;         // aligned to 2
+    uint32_t len;
+    char name[VENDOR_NAME_LEN];
+    struct {
+        int16_t x, y;
+    } pos[VENDOR_SLOTS];
+    double scale;
+    vendor_kind_t kind;
+} vendor_record_t;
+
+#pragma pack(push, 1)
+struct vendor_packet {
+    uint8_t type;
+    unsigned long long stamp;
+    struct vendor_record record;
+};
+#pragma pack(pop)
+
+struct vendor_handle {
+    void *context;
+};
+
+int vendor_open(const char *path, struct vendor_handle *handle);
+
+static inline uint32_t vendor_version(void) {
+    return 0x0102;
+}
+
+#ifdef __cplusplus
+}
+#endif